@@ -0,0 +1,60 @@
+using Noa.Compiler.Bytecode;
+using Noa.Compiler.Bytecode.Builders;
+using Noa.Compiler.Symbols;
+
+namespace Noa.Compiler.Emit.Tests;
+
+public class BlockEmitterTests
+{
+    private static IReadOnlyList<Opcode> EmitMain(string text)
+    {
+        var ast = Ast.Create(new Source(text, "test-input"));
+        ast.HasErrors.ShouldBeFalse();
+
+        var strings = new StringSectionBuilder();
+        var (_, main) = FunctionSectionBuilder.Create(strings.GetOrAdd("main"));
+        var functionBuilders = new Dictionary<IDeclaredFunction, FunctionBuilder>()
+        {
+            [ast.TopLevelFunction] = main
+        };
+
+        FunctionEmitter.EmitFunction(ast.TopLevelFunction, functionBuilders, strings);
+
+        return main.Code.Instructions.Select(x => x.Opcode).ToList();
+    }
+
+    // Negating the opposite comparison would make comparisons with NaN true,
+    // since NaN is neither less than nor greater than anything.
+
+    [Fact]
+    public void LessThanOrEqual_Emits_LessThanOrEqual_InsteadOf_NegatedGreaterThan()
+    {
+        var text = """
+        let a = 1;
+        let b = 2;
+        a <= b
+        """;
+
+        var opcodes = EmitMain(text);
+
+        opcodes.ShouldContain(Opcode.LessThanOrEqual);
+        opcodes.ShouldNotContain(Opcode.GreaterThan);
+        opcodes.ShouldNotContain(Opcode.Not);
+    }
+
+    [Fact]
+    public void GreaterThanOrEqual_Emits_GreaterThanOrEqual_InsteadOf_NegatedLessThan()
+    {
+        var text = """
+        let a = 1;
+        let b = 2;
+        a >= b
+        """;
+
+        var opcodes = EmitMain(text);
+
+        opcodes.ShouldContain(Opcode.GreaterThanOrEqual);
+        opcodes.ShouldNotContain(Opcode.LessThan);
+        opcodes.ShouldNotContain(Opcode.Not);
+    }
+}
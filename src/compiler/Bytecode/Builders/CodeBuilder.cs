@@ -27,6 +27,11 @@ internal sealed class CodeBuilder(CodeBuilder? previous) : IWritable
     /// </summary>
     public uint AddressOffset => length;
 
+    /// <summary>
+    /// The instructions which have been added to the builder.
+    /// </summary>
+    public IReadOnlyList<Instruction> Instructions => instructions;
+
     public void Write(Carpenter writer)
     {
         foreach (var i in instructions) writer.Write(i);
@@ -106,6 +111,13 @@ internal sealed class CodeBuilder(CodeBuilder? previous) : IWritable
         Add(Opcode.PushFloat, bytes);
     }
 
+    public void PushInt(long value)
+    {
+        var bytes = new byte[8];
+        BinaryPrimitives.WriteInt64BigEndian(bytes, value);
+        Add(Opcode.PushInt, bytes);
+    }
+
     public void PushBool(bool value)
     {
         var b = value ? (byte)1 : (byte)0;
@@ -202,6 +214,34 @@ internal sealed class CodeBuilder(CodeBuilder? previous) : IWritable
     public void WriteElement() => Add(Opcode.WriteElement);
 
     public void ReadElement() => Add(Opcode.ReadElement);
+
+    public void IntDiv() => Add(Opcode.IntDiv);
+
+    public void Mod() => Add(Opcode.Mod);
+
+    public void Neg() => Add(Opcode.Neg);
+
+    public void Pow() => Add(Opcode.Pow);
+
+    public void LessThanOrEqual() => Add(Opcode.LessThanOrEqual);
+
+    public void GreaterThanOrEqual() => Add(Opcode.GreaterThanOrEqual);
+
+    public void NotEqual() => Add(Opcode.NotEqual);
+
+    public void BitAnd() => Add(Opcode.BitAnd);
+
+    public void BitOr() => Add(Opcode.BitOr);
+
+    public void BitXor() => Add(Opcode.BitXor);
+
+    public void BitNot() => Add(Opcode.BitNot);
+
+    public void ShiftLeft() => Add(Opcode.ShiftLeft);
+
+    public void ShiftRight() => Add(Opcode.ShiftRight);
+
+    public void ShiftRightLogical() => Add(Opcode.ShiftRightLogical);
 }
 
 /// <summary>
@@ -23,6 +23,7 @@ internal enum Opcode : byte
     PushString = 0x18,
     PushObject = 0x19,
     PushList = 0x20,
+    PushInt = 0x21,
     Pop = 0x32,
     Dup = 0x33,
     Swap = 0x34,
@@ -51,6 +52,20 @@ internal enum Opcode : byte
     AppendElement = 0x73,
     WriteElement = 0x74,
     ReadElement = 0x75,
+    IntDiv = 0x76,
+    Mod = 0x77,
+    Neg = 0x78,
+    Pow = 0x79,
+    LessThanOrEqual = 0x7A,
+    GreaterThanOrEqual = 0x7B,
+    NotEqual = 0x7C,
+    BitAnd = 0x7D,
+    BitOr = 0x7E,
+    BitXor = 0x7F,
+    BitNot = 0x80,
+    ShiftLeft = 0x81,
+    ShiftRight = 0x82,
+    ShiftRightLogical = 0x83,
     
     // Operations instructions
     Boundary = 0xFF,
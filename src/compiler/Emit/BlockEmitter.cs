@@ -189,9 +189,7 @@ internal class BlockEmitter(
             break;
 
         case UnaryKind.Negate:
-            Code.PushFloat(0);
-            Code.Swap();
-            Code.Sub();
+            Code.Neg();
             break;
 
         case UnaryKind.Not:
@@ -243,8 +241,7 @@ internal class BlockEmitter(
             break;
         
         case BinaryKind.NotEqual:
-            Code.Equal();
-            Code.Not();
+            Code.NotEqual();
             break;
         
         case BinaryKind.LessThan:
@@ -256,13 +253,11 @@ internal class BlockEmitter(
             break;
         
         case BinaryKind.LessThanOrEqual:
-            Code.GreaterThan();
-            Code.Not();
+            Code.LessThanOrEqual();
             break;
         
         case BinaryKind.GreaterThanOrEqual:
-            Code.LessThan();
-            Code.Not();
+            Code.GreaterThanOrEqual();
             break;
         
         default: throw new UnreachableException();
@@ -22,6 +22,33 @@ internal static class NativeImports
 
         // Strings
         Declare(0x100, "toString", ["x"]);
+        Declare(0x101, "substring", ["string", "start", "end"]);
+        Declare(0x102, "indexOf", ["string", "search", "from"]);
+        Declare(0x103, "lastIndexOf", ["string", "search", "from"]);
+        Declare(0x104, "contains", ["string", "search"]);
+        Declare(0x105, "startsWith", ["string", "prefix"]);
+        Declare(0x106, "endsWith", ["string", "suffix"]);
+        Declare(0x107, "split", ["string", "separator"]);
+        Declare(0x108, "join", ["values", "separator"]);
+        Declare(0x109, "replace", ["string", "pattern", "replacement"]);
+        Declare(0x10A, "trim", ["string"]);
+        Declare(0x10B, "trimStart", ["string"]);
+        Declare(0x10C, "trimEnd", ["string"]);
+        Declare(0x10D, "toUpper", ["string"]);
+        Declare(0x10E, "toLower", ["string"]);
+        Declare(0x10F, "repeat", ["string", "count"]);
+        Declare(0x110, "padStart", ["string", "length", "fill"]);
+        Declare(0x111, "padEnd", ["string", "length", "fill"]);
+        Declare(0x112, "chars", ["string"]);
+        Declare(0x113, "codePointAt", ["string", "index"]);
+        Declare(0x114, "fromCodePoint", ["codePoint"]);
+
+        // String builders, at the end of the range of strings
+        Declare(0x160, "stringBuilder", ["initial"]);
+        Declare(0x161, "sbAppend", ["builder", "values"]);
+        Declare(0x162, "sbToString", ["builder"]);
+        Declare(0x163, "sbLength", ["builder"]);
+        Declare(0x164, "sbClear", ["builder"]);
         
         // Lists
         Declare(0x180, "push", ["list", "value"]);
@@ -38,6 +65,126 @@ internal static class NativeImports
         Declare(0x18B, "all", ["source", "predicate"]);
         Declare(0x18C, "find", ["source", "predicate", "fromEnd"]);
         Declare(0x18D, "length", ["list"]);
+        Declare(0x18E, "sort", ["source"]);
+        Declare(0x18F, "sortBy", ["source", "key"]);
+        Declare(0x190, "sortWith", ["source", "compare"]);
+        Declare(0x191, "binarySearch", ["source", "value"]);
+        Declare(0x192, "distinct", ["source"]);
+        Declare(0x193, "range", ["start", "end", "step"]);
+        Declare(0x194, "replicate", ["value", "count"]);
+        Declare(0x195, "zip", ["a", "b"]);
+        Declare(0x196, "enumerate", ["source"]);
+        Declare(0x197, "chunk", ["source", "size"]);
+        Declare(0x198, "window", ["source", "size"]);
+        Declare(0x199, "take", ["source", "count"]);
+        Declare(0x19A, "skip", ["source", "count"]);
+        Declare(0x19B, "takeWhile", ["source", "predicate"]);
+        Declare(0x19C, "skipWhile", ["source", "predicate"]);
+        Declare(0x19D, "groupBy", ["source", "key"]);
+        Declare(0x19E, "partition", ["source", "predicate"]);
+        Declare(0x19F, "sum", ["source"]);
+        Declare(0x1A0, "count", ["source", "predicate"]);
+
+        // Maps
+        Declare(0x200, "mapNew", ["entries"]);
+        Declare(0x201, "mapGet", ["map", "key", "default"]);
+        Declare(0x202, "mapSet", ["map", "key", "value"]);
+        Declare(0x203, "mapHas", ["map", "key"]);
+        Declare(0x204, "mapRemove", ["map", "key"]);
+        Declare(0x205, "mapKeys", ["map"]);
+        Declare(0x206, "mapValues", ["map"]);
+        Declare(0x207, "mapEntries", ["map"]);
+        Declare(0x208, "mapSize", ["map"]);
+
+        // Numbers
+        Declare(0x280, "toInteger", ["value"]);
+        Declare(0x281, "toFloat", ["value"]);
+        Declare(0x282, "isInteger", ["value"]);
+        Declare(0x283, "parseNumber", ["string"]);
+        Declare(0x284, "parseInt", ["string", "radix"]);
+        Declare(0x285, "formatNumber", ["value", "options"]);
+
+        // Bytes
+        Declare(0x300, "bytes", ["values"]);
+        Declare(0x301, "bytesLength", ["bytes"]);
+        Declare(0x302, "bytesGet", ["bytes", "index"]);
+        Declare(0x303, "bytesSlice", ["bytes", "start", "end"]);
+        Declare(0x304, "bytesConcat", ["a", "b"]);
+        Declare(0x305, "bytesToList", ["bytes"]);
+        Declare(0x306, "utf8Encode", ["string"]);
+        Declare(0x307, "utf8Decode", ["bytes", "lossy"]);
+        Declare(0x308, "hexEncode", ["bytes"]);
+        Declare(0x309, "hexDecode", ["string"]);
+        Declare(0x30A, "readFileBytes", ["path"]);
+        Declare(0x30B, "writeFileBytes", ["path", "bytes"]);
+        Declare(0x30C, "getInputBytes", []);
+
+        // Math
+        Declare(0x380, "abs", ["x"]);
+        Declare(0x381, "floor", ["x"]);
+        Declare(0x382, "ceil", ["x"]);
+        Declare(0x383, "round", ["x"]);
+        Declare(0x384, "trunc", ["x"]);
+        Declare(0x385, "sign", ["x"]);
+        Declare(0x386, "sqrt", ["x"]);
+        Declare(0x387, "pow", ["base", "exponent"]);
+        Declare(0x388, "exp", ["x"]);
+        Declare(0x389, "ln", ["x"]);
+        Declare(0x38A, "log", ["x", "base"]);
+        Declare(0x38B, "sin", ["x"]);
+        Declare(0x38C, "cos", ["x"]);
+        Declare(0x38D, "tan", ["x"]);
+        Declare(0x38E, "asin", ["x"]);
+        Declare(0x38F, "acos", ["x"]);
+        Declare(0x390, "atan", ["x"]);
+        Declare(0x391, "atan2", ["y", "x"]);
+        Declare(0x392, "min", ["values"]);
+        Declare(0x393, "max", ["values"]);
+        Declare(0x394, "clamp", ["x", "min", "max"]);
+        Declare(0x395, "isNaN", ["x"]);
+        Declare(0x396, "isFinite", ["x"]);
+        Declare(0x397, "pi", []);
+        Declare(0x398, "e", []);
+        Declare(0x399, "infinity", []);
+        Declare(0x39A, "nan", []);
+
+        // JSON
+        Declare(0x400, "jsonParse", ["string"]);
+        Declare(0x401, "jsonStringify", ["value", "pretty"]);
+
+        // Regular expressions
+        Declare(0x480, "regexMatch", ["string", "pattern"]);
+        Declare(0x481, "regexFindAll", ["string", "pattern"]);
+        Declare(0x482, "regexReplace", ["string", "pattern", "replacement"]);
+        Declare(0x483, "regexSplit", ["string", "pattern"]);
+
+        // Time
+        Declare(0x500, "now", []);
+        Declare(0x501, "monotonic", []);
+        Declare(0x502, "sleep", ["ms"]);
+        Declare(0x503, "toDate", ["timestamp"]);
+        Declare(0x504, "fromDate", ["date"]);
+        Declare(0x505, "formatDate", ["date"]);
+        Declare(0x506, "parseDate", ["string"]);
+
+        // Random numbers
+        Declare(0x580, "random", []);
+        Declare(0x581, "randomInt", ["lo", "hi"]);
+        Declare(0x582, "randomChoice", ["list"]);
+        Declare(0x583, "shuffle", ["list"]);
+        Declare(0x584, "setSeed", ["seed"]);
+
+        // Objects
+        Declare(0x600, "keys", ["object"]);
+        Declare(0x601, "values", ["object"]);
+        Declare(0x602, "entries", ["object"]);
+        Declare(0x603, "hasField", ["object", "name"]);
+        Declare(0x604, "getField", ["object", "name", "default"]);
+        Declare(0x605, "removeField", ["object", "name"]);
+        Declare(0x606, "isMutable", ["object", "name"]);
+        Declare(0x607, "isDynamic", ["object"]);
+        Declare(0x608, "merge", ["a", "b"]);
+        Declare(0x609, "freeze", ["object"]);
         
         return scope;
 
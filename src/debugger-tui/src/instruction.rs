@@ -153,6 +153,13 @@ impl<'insp, 'vm> From<&'insp DebugInspection<'vm>> for InstructionSummary {
                 vec![],
                 vec![]
             ),
+            opcode::PUSH_INT => (
+                "PushInt",
+                vec![
+                    make_operand::<i64>(inspection, "val")
+                ],
+                vec![]
+            ),
             opcode::POP => (
                 "Pop",
                 vec![],
@@ -340,6 +347,116 @@ impl<'insp, 'vm> From<&'insp DebugInspection<'vm>> for InstructionSummary {
                     make_arg(inspection, 0, "index", Some(Type::Number))
                 ]
             ),
+            opcode::INT_DIV => (
+                "IntDiv",
+                vec![],
+                vec![
+                    make_arg(inspection, 1, "left", Some(Type::Integer)),
+                    make_arg(inspection, 0, "right", Some(Type::Integer))
+                ]
+            ),
+            opcode::MOD => (
+                "Mod",
+                vec![],
+                vec![
+                    make_arg(inspection, 1, "left", Some(Type::Number)),
+                    make_arg(inspection, 0, "right", Some(Type::Number))
+                ]
+            ),
+            opcode::NEG => (
+                "Neg",
+                vec![],
+                vec![
+                    make_arg(inspection, 0, "val", Some(Type::Number))
+                ]
+            ),
+            opcode::POW => (
+                "Pow",
+                vec![],
+                vec![
+                    make_arg(inspection, 1, "left", Some(Type::Number)),
+                    make_arg(inspection, 0, "right", Some(Type::Number))
+                ]
+            ),
+            opcode::LESS_THAN_OR_EQUAL => (
+                "LessThanOrEqual",
+                vec![],
+                vec![
+                    make_arg(inspection, 1, "left", Some(Type::Number)),
+                    make_arg(inspection, 0, "right", Some(Type::Number))
+                ]
+            ),
+            opcode::GREATER_THAN_OR_EQUAL => (
+                "GreaterThanOrEqual",
+                vec![],
+                vec![
+                    make_arg(inspection, 1, "left", Some(Type::Number)),
+                    make_arg(inspection, 0, "right", Some(Type::Number))
+                ]
+            ),
+            opcode::NOT_EQUAL => (
+                "NotEqual",
+                vec![],
+                vec![
+                    make_arg(inspection, 1, "left", None),
+                    make_arg(inspection, 0, "right", None)
+                ]
+            ),
+            opcode::BIT_AND => (
+                "BitAnd",
+                vec![],
+                vec![
+                    make_arg(inspection, 1, "left", Some(Type::Integer)),
+                    make_arg(inspection, 0, "right", Some(Type::Integer))
+                ]
+            ),
+            opcode::BIT_OR => (
+                "BitOr",
+                vec![],
+                vec![
+                    make_arg(inspection, 1, "left", Some(Type::Integer)),
+                    make_arg(inspection, 0, "right", Some(Type::Integer))
+                ]
+            ),
+            opcode::BIT_XOR => (
+                "BitXor",
+                vec![],
+                vec![
+                    make_arg(inspection, 1, "left", Some(Type::Integer)),
+                    make_arg(inspection, 0, "right", Some(Type::Integer))
+                ]
+            ),
+            opcode::BIT_NOT => (
+                "BitNot",
+                vec![],
+                vec![
+                    make_arg(inspection, 0, "val", Some(Type::Integer))
+                ]
+            ),
+            opcode::SHIFT_LEFT => (
+                "ShiftLeft",
+                vec![],
+                vec![
+                    make_arg(inspection, 1, "val", Some(Type::Integer)),
+                    make_arg(inspection, 0, "shift", Some(Type::Integer))
+                ]
+            ),
+            opcode::SHIFT_RIGHT => (
+                "ShiftRight",
+                vec![],
+                vec![
+                    make_arg(inspection, 1, "val", Some(Type::Integer)),
+                    make_arg(inspection, 0, "shift", Some(Type::Integer))
+                ]
+            ),
+            opcode::SHIFT_RIGHT_LOGICAL => (
+                "ShiftRightLogical",
+                vec![],
+                vec![
+                    make_arg(inspection, 1, "val", Some(Type::Integer)),
+                    make_arg(inspection, 0, "shift", Some(Type::Integer))
+                ]
+            ),
             opcode::BOUNDARY => (
                 "Boundary",
                 vec![],
@@ -489,6 +606,24 @@ impl IntoOperand for f64 {
     }
 }
 
+impl IntoOperand for i64 {
+    fn typ() -> String {
+        "i64".into()
+    }
+
+    fn length() -> usize {
+        8
+    }
+
+    fn read(bytes: &[u8]) -> Self {
+        Self::from_be_bytes(bytes.try_into().unwrap())
+    }
+
+    fn show(&self) -> String {
+        self.to_string()
+    }
+}
+
 impl IntoOperand for bool {
     fn typ() -> String {
         "bool".into()
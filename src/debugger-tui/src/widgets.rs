@@ -3,7 +3,7 @@ use std::rc::Rc;
 
 use noa_runtime::ark::FuncId;
 use noa_runtime::heap::HeapValue;
-use noa_runtime::value::{List, Object, Type, Value};
+use noa_runtime::value::{List, Map, Object, Type, Value};
 use noa_runtime::vm::frame::{Frame, FrameKind};
 use ratatui::widgets::{Block, BorderType, Borders, Paragraph, Wrap};
 use ratatui::prelude::*;
@@ -104,11 +104,15 @@ impl MainWidget<'_, '_, '_> {
     fn show_type(&self, typ: Type) -> Span<'static> {
         match typ {
             Type::Number => "number".cyan(),
+            Type::Integer => "integer".cyan(),
             Type::Bool => "bool".blue(),
             Type::Function => "function".green(),
             Type::String => "string".yellow(),
             Type::List => "list".magenta(),
             Type::Object => "object".magenta(),
+            Type::Map => "map".magenta(),
+            Type::Bytes => "bytes".magenta(),
+            Type::StringBuilder => "string builder".magenta(),
             Type::Nil => "()".white(),
         }
     }
@@ -117,6 +121,8 @@ impl MainWidget<'_, '_, '_> {
         match value {
             Value::Number(x) => x.to_string().cyan().into(),
 
+            Value::Integer(x) => x.to_string().cyan().into(),
+
             Value::Bool(x) => x.to_string().blue().into(),
 
             Value::InternedString(index) =>
@@ -135,6 +141,13 @@ impl MainWidget<'_, '_, '_> {
 
                         HeapValue::Object(object) => self.show_object(object),
 
+                        HeapValue::Map(map) => self.show_map(map),
+
+                        HeapValue::Bytes(bytes) => self.show_bytes(bytes),
+
+                        HeapValue::StringBuilder(s) =>
+                            format!("sb\"{}\"", s).light_yellow().into(),
+
                         HeapValue::Box(x) => {
                             let inner = self.show_value(*x);
                             let mut spans = inner.spans;
@@ -185,11 +198,8 @@ impl MainWidget<'_, '_, '_> {
 
         spans.push("{".into());
 
-        let mut fields = object.fields.iter().collect::<Vec<_>>();
-        fields.sort_by_key(|(_, field)| field.index);
-
         let mut i = 0;
-        for (field_name, field) in fields {
+        for (field_name, field) in object.fields(self.inspection.shapes) {
             if i >= 1 {
                 spans.push(",".into());
             }
@@ -217,6 +227,46 @@ impl MainWidget<'_, '_, '_> {
         spans.into()
     }
 
+    fn show_map(&self, map: &Map) -> Line<'static> {
+        let mut spans = Vec::new();
+
+        spans.push("map {".into());
+
+        let mut i = 0;
+        for entry in map.ordered() {
+            if i >= 1 {
+                spans.push(",".into());
+            }
+
+            spans.push(" ".into());
+
+            let key_spans = self.show_value(entry.key).spans;
+            let value_spans = self.show_value(entry.val).spans;
+            spans.extend_from_slice(&key_spans);
+            spans.push(": ".into());
+            spans.extend_from_slice(&value_spans);
+
+            i += 1;
+        }
+
+        if i >= 1 {
+            spans.push(" ".into());
+        }
+
+        spans.push("}".into());
+
+        spans.into()
+    }
+
+    fn show_bytes(&self, bytes: &[u8]) -> Line<'static> {
+        let hex = bytes.iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        format!("bytes [{hex}]").light_magenta().into()
+    }
+
     fn show_istr(&self, index: usize) -> Line<'static> {
         if let Some(s) = self.inspection.consts.strings.get(index) {
             format!("i\"{}\"", s.clone()).yellow().into()
@@ -0,0 +1,274 @@
+//! Benchmarks of whole programs running on the vm.
+//!
+//! Each program is assembled directly into bytecode, and its result is checked once before it's measured.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use noa_runtime::ark::FuncId;
+use noa_runtime::opcode::*;
+use noa_runtime::value::Value;
+use noa_runtime::vm::Vm;
+
+use common::{natives, Program};
+
+const ITERATIONS: f64 = 10_000.0;
+
+/// Emits `for (i = 0; i < count; i++) { body }`, where `i` is stored in a variable.
+fn for_loop(p: &mut Program, i: u32, count: f64, body: impl FnOnce(&mut Program)) {
+    let top = p.label();
+    let end = p.label();
+
+    p.float(0.0).store(i)
+        .bind(top)
+        .load(i).float(count).op(LESS_THAN).op(NOT)
+        .jump(JUMP_IF, end);
+
+    body(p);
+
+    p.load(i).float(1.0).op(ADD).store(i)
+        .jump(JUMP, top)
+        .bind(end);
+}
+
+/// The program in `samples/fib.noa`, as the compiler lowers it,
+/// except that `fib(n)` is returned instead of printed.
+///
+/// ```text
+/// func fib(x) {
+///     if x <= 2 { 1 } else { fib(x - 1) + fib(x - 2) }
+/// }
+///
+/// fib(n)
+/// ```
+fn fib_sample(n: f64) -> (Program, FuncId) {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 0, &[]);
+    let fib = p.declare("fib", 1, 0, &[]);
+
+    p.begin(main)
+        .func(fib).float(n).call(1)
+        .op(RET).op(BOUNDARY);
+
+    let base = p.label();
+    let end = p.label();
+    p.begin(fib)
+        .load(0).float(2.0).op(LESS_THAN_OR_EQUAL)
+        .jump(JUMP_IF, base)
+        .func(fib).load(0).float(1.0).op(SUB).call(1)
+        .func(fib).load(0).float(2.0).op(SUB).call(1)
+        .op(ADD)
+        .jump(JUMP, end)
+        .bind(base)
+        .float(1.0)
+        .bind(end)
+        .op(RET)
+        .op(BOUNDARY);
+
+    (p, main)
+}
+
+/// Creates a closure for every iteration and calls it.
+///
+/// ```text
+/// makeAdder(n) = x => x + n
+/// sum = 0
+/// for (i = 0; i < ITERATIONS; i++) { sum = sum + makeAdder(i)(1) }
+/// ```
+fn closures() -> (Program, FuncId) {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 2, &[]);
+    let make_adder = p.declare("makeAdder", 1, 0, &[]);
+    let adder = p.declare("adder", 1, 0, &[0]);
+
+    p.begin(main).float(0.0).store(1);
+    for_loop(&mut p, 0, ITERATIONS, |p| {
+        p.func(make_adder).load(0).call(1)
+            .float(1.0).call(1)
+            .load(1).op(ADD).store(1);
+    });
+    p.load(1).op(RET).op(BOUNDARY);
+
+    p.begin(make_adder)
+        .func(adder).op(RET)
+        .op(BOUNDARY);
+
+    // The captured variable is stored right after the arguments.
+    p.begin(adder)
+        .load(0).load(1).op(ADD).op(RET)
+        .op(BOUNDARY);
+
+    (p, main)
+}
+
+/// Runs a list through `map`, `filter` and `reduce`.
+///
+/// ```text
+/// xs = [0, 1, ..., ITERATIONS - 1]
+/// reduce(filter(map(xs, x => x * 2), x => x % 3 == 0), 0, (r, x) => r + x)
+/// ```
+fn list_combinators() -> (Program, FuncId) {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 2, &[]);
+    let double = p.declare("double", 2, 0, &[]);
+    let is_multiple_of_3 = p.declare("isMultipleOf3", 2, 0, &[]);
+    let add = p.declare("add", 2, 0, &[]);
+
+    p.begin(main).op(PUSH_LIST).store(0);
+    for_loop(&mut p, 1, ITERATIONS, |p| {
+        p.load(0).load(1).op(APPEND_ELEMENT);
+    });
+    p.native(natives::REDUCE)
+        .native(natives::FILTER)
+        .native(natives::MAP).load(0).func(double).call(2)
+        .func(is_multiple_of_3).call(2)
+        .float(0.0).func(add).call(3)
+        .op(RET).op(BOUNDARY);
+
+    p.begin(double)
+        .load(0).float(2.0).op(MULT).op(RET)
+        .op(BOUNDARY);
+
+    p.begin(is_multiple_of_3)
+        .load(0).float(3.0).op(MOD).float(0.0).op(EQUAL).op(RET)
+        .op(BOUNDARY);
+
+    p.begin(add)
+        .load(0).load(1).op(ADD).op(RET)
+        .op(BOUNDARY);
+
+    (p, main)
+}
+
+/// Reads and writes the fields of an object.
+///
+/// ```text
+/// obj = { mut x: 0, y: 2 }
+/// for (i = 0; i < ITERATIONS; i++) { obj.x = obj.x + obj.y }
+/// obj.x
+/// ```
+fn field_access() -> (Program, FuncId) {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 2, &[]);
+
+    p.begin(main)
+        .op(PUSH_OBJECT).op(0)
+        .op(DUP).str("x").float(0.0).op(ADD_FIELD).op(1)
+        .op(DUP).str("y").float(2.0).op(ADD_FIELD).op(0)
+        .store(0);
+    for_loop(&mut p, 1, ITERATIONS, |p| {
+        p.load(0).str("x")
+            .load(0).str("x").op(READ_FIELD)
+            .load(0).str("y").op(READ_FIELD)
+            .op(ADD)
+            .op(WRITE_FIELD);
+    });
+    p.load(0).str("x").op(READ_FIELD)
+        .op(RET).op(BOUNDARY);
+
+    (p, main)
+}
+
+/// Builds a string by repeatedly concatenating onto it.
+///
+/// ```text
+/// s = ""
+/// for (i = 0; i < ITERATIONS / 10; i++) { s = s ++ i ++ "," }
+/// ```
+fn string_concat() -> (Program, FuncId) {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 2, &[]);
+
+    p.begin(main).str("").store(0);
+    for_loop(&mut p, 1, ITERATIONS / 10.0, |p| {
+        p.load(0).load(1).op(CONCAT).str(",").op(CONCAT).store(0);
+    });
+    p.load(0).op(RET).op(BOUNDARY);
+
+    (p, main)
+}
+
+/// Allocates lots of short-lived lists while keeping a long-lived list of objects alive,
+/// with a heap small enough that the garbage collector has to run over and over.
+///
+/// ```text
+/// live = []
+/// for (i = 0; i < LIVE_OBJECTS; i++) { push(live, { value: i }) }
+/// for (i = 0; i < ITERATIONS; i++) { [i, i] }
+/// length(live)
+/// ```
+fn gc_pressure() -> (Program, FuncId) {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 2, &[]);
+
+    p.begin(main).op(PUSH_LIST).store(0);
+    for_loop(&mut p, 1, LIVE_OBJECTS as f64, |p| {
+        p.load(0)
+            .op(PUSH_OBJECT).op(0)
+            .op(DUP).str("value").load(1).op(ADD_FIELD).op(0)
+            .op(APPEND_ELEMENT);
+    });
+    for_loop(&mut p, 1, ITERATIONS, |p| {
+        p.op(PUSH_LIST)
+            .op(DUP).load(1).op(APPEND_ELEMENT)
+            .op(DUP).load(1).op(APPEND_ELEMENT)
+            .op(POP);
+    });
+    p.native(natives::LENGTH).load(0).call(1)
+        .op(RET).op(BOUNDARY);
+
+    (p, main)
+}
+
+const LIVE_OBJECTS: usize = 100;
+const GC_HEAP_SIZE: usize = LIVE_OBJECTS * 2;
+
+fn run(program: &Program, main: FuncId, heap_size: usize) -> (Vm, Value) {
+    let mut vm = program.vm_with_heap(heap_size);
+    let result = vm.call_run(main.into(), &[])
+        .unwrap_or_else(|e| panic!("program failed: {}", e.exception));
+
+    (vm, result)
+}
+
+fn bench(c: &mut Criterion, name: &str, (program, main): (Program, FuncId), heap_size: usize, expected: &str) {
+    let (vm, result) = run(&program, main, heap_size);
+    assert_eq!(vm.to_string(result).unwrap(), expected, "{name} returned the wrong result");
+
+    c.bench_function(name, |b| b.iter(|| {
+        black_box(run(&program, main, heap_size).1)
+    }));
+}
+
+fn programs(c: &mut Criterion) {
+    let n = ITERATIONS as usize;
+
+    bench(c, "fib(20)", common::fib(20.0), common::HEAP_SIZE, "6765");
+
+    bench(c, "fib.noa (fib(25))", fib_sample(25.0), common::HEAP_SIZE, "75025");
+
+    bench(c, "closures", closures(), common::HEAP_SIZE,
+        &(n * (n - 1) / 2 + n).to_string());
+
+    let sum: usize = (0..n).map(|x| x * 2).filter(|x| x % 3 == 0).sum();
+    bench(c, "map/filter/reduce", list_combinators(), common::HEAP_SIZE, &sum.to_string());
+
+    bench(c, "field access", field_access(), common::HEAP_SIZE, &(2 * n).to_string());
+
+    let str: String = (0..n / 10).map(|i| format!("{i},")).collect();
+    bench(c, "string concat", string_concat(), common::HEAP_SIZE, &str);
+
+    bench(c, "gc pressure", gc_pressure(), GC_HEAP_SIZE, &LIVE_OBJECTS.to_string());
+}
+
+criterion_group!(benches, programs);
+criterion_main!(benches);
@@ -0,0 +1,55 @@
+//! Feeds arbitrary bytes to the Ark loader, and creates a vm from whatever it manages to load.
+//!
+//! The loaded program isn't run, since arbitrary programs could call natives which touch the file system.
+//! See the `interpreter` target for running programs.
+
+#![no_main]
+
+use std::io::Cursor;
+
+use binrw::BinRead;
+use libfuzzer_sys::fuzz_target;
+use noa_runtime::ark::{Ark, CodeSection, FunctionSection, StringSection};
+use noa_runtime::exception::Exception;
+use noa_runtime::vm::{Input, Output, Vm};
+
+struct NoInput;
+
+impl Input for NoInput {
+    fn read(&mut self, _: &mut Vec<u8>) -> Result<(), Exception> {
+        Ok(())
+    }
+}
+
+struct NoOutput;
+
+impl Output for NoOutput {
+    fn write(&mut self, _: &[u8]) -> Result<(), Exception> {
+        Ok(())
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(ark) = Ark::read_be(&mut Cursor::new(data)) else {
+        return;
+    };
+
+    let Ark {
+        function_section: FunctionSection { functions, .. },
+        code_section: CodeSection { code, .. },
+        string_section: StringSection { strings, .. },
+        ..
+    } = ark;
+
+    Vm::new(
+        functions,
+        strings,
+        code,
+        256,
+        32,
+        256,
+        Box::new(NoInput),
+        Box::new(NoOutput),
+        None
+    );
+});
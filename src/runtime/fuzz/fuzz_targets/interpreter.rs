@@ -0,0 +1,224 @@
+//! Runs structurally valid but otherwise random programs.
+//!
+//! Programs are generated from the fuzzer input as a list of functions made up of well-formed instructions,
+//! where every jump lands on an instruction, every function and string index exists and every native is safe to call.
+//! Nothing else about the program is valid, so it can underflow the stack, read variables which don't exist,
+//! call non-functions and so on. None of that is allowed to panic the vm, only to end the program with an exception.
+//! Programs run with small limits and a limited amount of fuel so that every run terminates quickly.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use noa_runtime::ark::{FuncId, Function};
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+use noa_runtime::vm::{Input, Output, Vm};
+
+const STACK_SIZE: usize = 256;
+const CALL_STACK_SIZE: usize = 32;
+const HEAP_SIZE: usize = 256;
+const FUEL: u64 = 10_000;
+
+const MAX_FUNCTIONS: usize = 8;
+const MAX_STRINGS: usize = 8;
+
+/// Natives which don't touch anything outside of the vm.
+const NATIVES: &[u32] = &[
+    0x0, 0x1,
+    0x100, 0x101, 0x102, 0x103, 0x104, 0x105, 0x106, 0x107, 0x108, 0x109, 0x10A, 0x10B, 0x10C, 0x10D, 0x10E,
+    0x10F, 0x110, 0x111, 0x112, 0x113, 0x114,
+    0x160, 0x161, 0x162, 0x163, 0x164,
+    0x180, 0x181, 0x182, 0x183, 0x184, 0x185, 0x186, 0x187, 0x188, 0x189, 0x18A, 0x18B, 0x18C, 0x18D,
+    0x18E, 0x18F, 0x190, 0x191, 0x192, 0x193, 0x194, 0x195, 0x196, 0x197, 0x198, 0x199, 0x19A, 0x19B, 0x19C,
+    0x19D, 0x19E, 0x19F, 0x1A0,
+    0x200, 0x201, 0x202, 0x203, 0x204, 0x205, 0x206, 0x207, 0x208,
+    0x280, 0x281, 0x282, 0x283, 0x284, 0x285,
+    0x300, 0x301, 0x302, 0x303, 0x304, 0x305, 0x306, 0x307, 0x308, 0x309, 0x30C,
+    0x380, 0x381, 0x382, 0x383, 0x384, 0x385, 0x386, 0x387, 0x388, 0x389, 0x38A, 0x38B, 0x38C, 0x38D,
+    0x38E, 0x38F, 0x390, 0x391, 0x392, 0x393, 0x394, 0x395, 0x396, 0x397, 0x398, 0x399, 0x39A,
+    0x400, 0x401,
+    0x480, 0x481, 0x482, 0x483,
+    0x500, 0x501, 0x503, 0x504, 0x505, 0x506,
+    0x580, 0x581, 0x582, 0x583, 0x584,
+    0x600, 0x601, 0x602, 0x603, 0x604, 0x605, 0x606, 0x607, 0x608, 0x609,
+];
+
+/// Opcodes without operands.
+const SIMPLE: &[u8] = &[
+    NO_OP, RET, ENTER_TEMP_FRAME, EXIT_TEMP_FRAME, PUSH_NIL, PUSH_LIST, POP, DUP, SWAP,
+    ADD, SUB, MULT, DIV, EQUAL, LESS_THAN, NOT, AND, OR, GREATER_THAN, CONCAT, TO_STRING,
+    WRITE_FIELD, READ_FIELD, APPEND_ELEMENT, WRITE_ELEMENT, READ_ELEMENT,
+    INT_DIV, MOD, NEG, POW, LESS_THAN_OR_EQUAL, GREATER_THAN_OR_EQUAL, NOT_EQUAL,
+    BIT_AND, BIT_OR, BIT_XOR, BIT_NOT, SHIFT_LEFT, SHIFT_RIGHT, SHIFT_RIGHT_LOGICAL, BOUNDARY,
+];
+
+#[derive(Debug, Arbitrary)]
+enum Op {
+    Simple(u8),
+    Jump(u16),
+    JumpIf(u16),
+    Call(u8),
+    PushFloat(f64),
+    PushInt(i64),
+    PushBool(bool),
+    PushFunc(u8),
+    PushNative(u8),
+    PushString(u8),
+    PushObject(bool),
+    StoreVar(u8),
+    LoadVar(u8),
+    StoreVarBoxed(u8),
+    AddField(bool),
+}
+
+impl Op {
+    fn size(&self) -> usize {
+        match self {
+            Op::Simple(_) => 1,
+            Op::PushBool(_) | Op::PushObject(_) | Op::AddField(_) => 2,
+            Op::PushFloat(_) | Op::PushInt(_) => 9,
+            _ => 5
+        }
+    }
+}
+
+#[derive(Debug, Arbitrary)]
+struct FunctionDef {
+    arity: u8,
+    locals: u8,
+    captures: Vec<u8>,
+    body: Vec<Op>,
+}
+
+#[derive(Debug, Arbitrary)]
+struct Program {
+    main: FunctionDef,
+    functions: Vec<FunctionDef>,
+    strings: Vec<String>,
+}
+
+impl Program {
+    fn assemble(self) -> (Vec<Function>, Vec<String>, Vec<u8>) {
+        let mut definitions = vec![self.main];
+        definitions.extend(self.functions.into_iter().take(MAX_FUNCTIONS - 1));
+
+        let mut strings = self.strings;
+        strings.truncate(MAX_STRINGS);
+        strings.push("f".to_owned());
+        let name_index = strings.len() as u32 - 1;
+
+        // The byte address of every instruction, so that jumps can target any of them.
+        let mut addresses = Vec::new();
+        let mut address = 0;
+        for definition in &definitions {
+            for op in &definition.body {
+                addresses.push(address as u32);
+                address += op.size();
+            }
+            addresses.push(address as u32);
+            address += 1;
+        }
+
+        let mut functions = Vec::new();
+        let mut code = Vec::new();
+
+        for (index, definition) in definitions.iter().enumerate() {
+            let arity = (definition.arity % 4) as u32;
+            let locals_count = (definition.locals % 4) as u32;
+            let captures = definition.captures.iter()
+                .take(4)
+                .map(|x| (x % 8) as u32)
+                .collect::<Vec<_>>();
+            // Sometimes reach past the variables of the function.
+            let variables = arity + locals_count + captures.len() as u32 + 2;
+
+            functions.push(Function {
+                id: FuncId(index as u32),
+                name_index,
+                arity,
+                locals_count,
+                captures_count: captures.len() as u32,
+                address: code.len() as u32,
+                captures,
+            });
+
+            for op in &definition.body {
+                let target = |x: u16| addresses[x as usize % addresses.len()];
+
+                match *op {
+                    Op::Simple(x) => code.push(SIMPLE[x as usize % SIMPLE.len()]),
+                    Op::Jump(x) => push_u32(&mut code, JUMP, target(x)),
+                    Op::JumpIf(x) => push_u32(&mut code, JUMP_IF, target(x)),
+                    Op::Call(x) => push_u32(&mut code, CALL, (x % 8) as u32),
+                    Op::PushFloat(x) => {
+                        code.push(PUSH_FLOAT);
+                        code.extend(x.to_be_bytes());
+                    },
+                    Op::PushInt(x) => {
+                        code.push(PUSH_INT);
+                        code.extend(x.to_be_bytes());
+                    },
+                    Op::PushBool(x) => code.extend([PUSH_BOOL, x as u8]),
+                    Op::PushFunc(x) => push_u32(&mut code, PUSH_FUNC, x as u32 % definitions.len() as u32),
+                    Op::PushNative(x) => push_u32(&mut code, PUSH_FUNC, NATIVES[x as usize % NATIVES.len()] | (1 << 31)),
+                    Op::PushString(x) => push_u32(&mut code, PUSH_STRING, x as u32 % strings.len() as u32),
+                    Op::PushObject(x) => code.extend([PUSH_OBJECT, x as u8]),
+                    Op::StoreVar(x) => push_u32(&mut code, STORE_VAR, x as u32 % variables),
+                    Op::LoadVar(x) => push_u32(&mut code, LOAD_VAR, x as u32 % variables),
+                    Op::StoreVarBoxed(x) => push_u32(&mut code, STORE_VAR_BOXED, x as u32 % variables),
+                    Op::AddField(x) => code.extend([ADD_FIELD, x as u8]),
+                }
+            }
+
+            code.push(BOUNDARY);
+        }
+
+        (functions, strings, code)
+    }
+}
+
+fn push_u32(code: &mut Vec<u8>, opcode: u8, operand: u32) {
+    code.push(opcode);
+    code.extend(operand.to_be_bytes());
+}
+
+struct NoInput;
+
+impl Input for NoInput {
+    fn read(&mut self, buf: &mut Vec<u8>) -> Result<(), Exception> {
+        buf.extend_from_slice(b"input");
+        Ok(())
+    }
+}
+
+struct NoOutput;
+
+impl Output for NoOutput {
+    fn write(&mut self, _: &[u8]) -> Result<(), Exception> {
+        Ok(())
+    }
+}
+
+fuzz_target!(|program: Program| {
+    let (functions, strings, code) = program.assemble();
+
+    let mut vm = Vm::new(
+        functions,
+        strings,
+        code,
+        STACK_SIZE,
+        CALL_STACK_SIZE,
+        HEAP_SIZE,
+        Box::new(NoInput),
+        Box::new(NoOutput),
+        None
+    );
+    vm.set_fuel(Some(FUEL));
+    // Keep runs reproducible.
+    vm.set_seed(0);
+
+    if let Ok(value) = vm.call_run(FuncId(0).into(), &[]) {
+        _ = vm.to_string(value);
+    }
+});
@@ -51,15 +51,70 @@ pub enum Exception {
     #[error("cannot write to immutable field \"{0}\"")]
     WriteToImmutableField(String),
 
+    #[error("cannot remove field \"{0}\" from an object which isn't dynamic")]
+    RemoveFromNonDynamic(String),
+
     #[error("cannot index into list using index `{0}`")]
     InvalidIndex(f64),
 
     #[error("index `{0}` is out of bounds of the list, which has a length of `{1}`")]
-    OutOfBoundsIndex(f64, usize),
+    OutOfBoundsIndex(i64, usize),
+
+    #[error("`{0}` is not an integer")]
+    NonIntegral(f64),
+
+    #[error("cannot shift by `{0}` bits, expected a shift between 0 and 63")]
+    InvalidShift(i64),
+
+    #[error("integer overflow")]
+    IntegerOverflow,
+
+    #[error("integer division by zero")]
+    DivisionByZero,
+
+    #[error("{0} cannot be used as a map key")]
+    InvalidMapKey(String),
+
+    #[error("`{0}` is not a valid byte value")]
+    InvalidByte(i64),
+
+    #[error("`{0}` is not a valid Unicode scalar value")]
+    InvalidCodePoint(i64),
+
+    #[error("`{0}` is not a valid radix, expected a radix between 2 and 36")]
+    InvalidRadix(i64),
+
+    #[error("cannot format a number with `{0}` decimals, expected between 0 and 100")]
+    InvalidDecimals(i64),
+
+    #[error("\"{0}\" is not a valid hex string")]
+    InvalidHex(String),
 
     #[error("the string `{0}` is not valid UTF-8")]
     NonUtf8(String),
 
+    #[error("invalid JSON at line {line}, column {column}: {message}")]
+    InvalidJson {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+
+    #[error("{0} cannot be converted into JSON")]
+    NonJsonValue(String),
+
+    #[error("cannot convert a value which contains itself into JSON")]
+    CyclicJson,
+
+    #[error("invalid regular expression: {0}")]
+    InvalidRegex(String),
+
+    #[error("invalid date: {0}")]
+    InvalidDate(String),
+
+    #[error("cannot order {0} and {1}, only numbers and strings can be ordered")]
+    Incomparable(String, String),
+
     #[error("expected {}{} arguments but got {}", expected, if *or_more { " or more" } else { "" }, actual)]
     BadArity {
         expected: u32,
@@ -75,6 +130,15 @@ pub enum Exception {
         actual: String,
     },
 
+    #[error("exited a temporary stack frame which was never entered")]
+    UnbalancedTempFrame,
+
+    #[error("ran out of fuel")]
+    OutOfFuel,
+
+    #[error("value is nested too deeply")]
+    TooDeeplyNested,
+
     #[error("{0}")]
     Custom(String),
 }
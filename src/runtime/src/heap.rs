@@ -1,9 +1,9 @@
 use std::ptr;
 
-use crate::value::{Closure, List, Object, Value};
+use crate::value::{Closure, List, Map, Object, Value};
 
 /// An address to data on a [`Heap`].
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct HeapAddress(pub usize);
 
 /// A 'slot' of memory on a [`Heap`].
@@ -55,9 +55,27 @@ pub enum HeapValue {
     String(String),
     List(List),
     Object(Object),
+    Map(Map),
+    Bytes(Vec<u8>),
+    /// A mutable string which can be appended to without copying.
+    StringBuilder(String),
     Box(Value),
 }
 
+impl HeapValue {
+    /// Gets the amount of elements, fields, entries or bytes the value holds.
+    pub fn size(&self) -> usize {
+        match self {
+            HeapValue::String(str) | HeapValue::StringBuilder(str) => str.len(),
+            HeapValue::List(list) => list.0.len(),
+            HeapValue::Object(obj) => obj.len(),
+            HeapValue::Map(map) => map.entries.len(),
+            HeapValue::Bytes(bytes) => bytes.len(),
+            HeapValue::Box(_) => 1,
+        }
+    }
+}
+
 /// A memory heap for managing heap-allocated data and garbage collection of that data.
 #[derive(Debug)]
 pub struct Heap {
@@ -180,23 +198,27 @@ impl Heap {
             // If the data has already been marked, that means we've already visited it
             // and its contained references.
             if data.marked {
-                return;
+                continue;
             }
 
             data.marked = true;
 
             // Add the contained references to the list of addresses to visit.
             match &data.value  {
-                HeapValue::String(_) => {},
+                HeapValue::String(_) | HeapValue::Bytes(_) | HeapValue::StringBuilder(_) => {},
                 HeapValue::List(List(xs)) => {
                     let addresses = Self::extract_references(
                         xs.iter().copied()
                     );
                     to_visit.extend(addresses);
                 },
-                HeapValue::Object(Object { fields, .. }) => {
+                HeapValue::Object(obj) => {
+                    let addresses = Self::extract_references(obj.values());
+                    to_visit.extend(addresses);
+                },
+                HeapValue::Map(Map { entries, .. }) => {
                     let addresses = Self::extract_references(
-                        fields.values().map(|f| &f.val).copied()
+                        entries.values().flat_map(|e| [e.key, e.val])
                     );
                     to_visit.extend(addresses);
                 },
@@ -302,7 +324,8 @@ impl Heap {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::assert_matches::assert_matches;
+    use crate::value::MapKey;
+    use std::assert_matches;
     use std::iter;
 
     fn alloc(heap: &mut Heap, value: HeapValue, expected_address: usize) -> HeapAddress {
@@ -460,6 +483,25 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn collect_keeps_marking_after_visiting_marked_data() {
+        let mut heap = Heap::new(2);
+
+        alloc(&mut heap, HeapValue::String("uwu".into()), 0);
+        alloc(&mut heap, HeapValue::String("owo".into()), 1);
+
+        // The second reference to 1 is visited after 1 has already been marked,
+        // which shouldn't stop 0 from being marked.
+        heap.collect([
+            Value::Object(HeapAddress(0)),
+            Value::Object(HeapAddress(1)),
+            Value::Object(HeapAddress(1))
+        ].iter().copied());
+
+        assert_eq!(heap.used, 2);
+        assert_eq!(heap.first_free, None);
+    }
+
     #[test]
     fn collect_marks_references_through_objects() {
         let mut heap = Heap::new(4);
@@ -497,6 +539,46 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn collect_marks_references_through_map_keys_and_values() {
+        let mut heap = Heap::new(4);
+
+        let mut map = Map::default();
+        map.insert(
+            MapKey::Reference(HeapAddress(1)),
+            Value::Object(HeapAddress(1)),
+            Value::Object(HeapAddress(2))
+        );
+
+        alloc(&mut heap, HeapValue::Map(map), 0);
+        alloc(&mut heap, HeapValue::String("uwu".into()), 1);
+        alloc(&mut heap, HeapValue::String("owo".into()), 2);
+        alloc(&mut heap, HeapValue::String("^w^".into()), 3);
+
+        heap.collect([
+            Value::Object(HeapAddress(0))
+        ].iter().copied());
+
+        assert_eq!(heap.used, 3);
+        assert_eq!(heap.first_free, Some(3));
+
+        assert_matches!(heap.mem[..], [
+            MemorySlot::Filled(HeapData {
+                value: HeapValue::Map(..),
+                ..
+            }),
+            MemorySlot::Filled(HeapData {
+                value: HeapValue::String(..),
+                ..
+            }),
+            MemorySlot::Filled(HeapData {
+                value: HeapValue::String(..),
+                ..
+            }),
+            MemorySlot::Free(Free { next_free: None }),
+        ]);
+    }
+
     #[test]
     fn collect_handles_cyclic_references() {
         let mut heap = Heap::new(2);
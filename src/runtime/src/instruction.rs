@@ -0,0 +1,365 @@
+//! Decoding of bytecode into instructions.
+//!
+//! Instead of interpreting the raw bytes of the code section directly, the vm decodes the entire code section
+//! into a sequence of [`Instruction`]s when it's created. Every instruction knows its operands up-front,
+//! so the interpreter doesn't have to read and bounds-check operand bytes every time an instruction is executed.
+//!
+//! Jump targets are resolved from byte addresses into indices into the decoded instructions,
+//! which is what the vm's instruction pointer points into. Byte addresses are still what's used
+//! in stack traces and by the debugger, so [`DecodedCode`] keeps track of the byte address of every instruction.
+//! Functions and strings referenced by instructions are resolved up-front as well, so pushing them
+//! doesn't have to check that they exist or look up whether a function captures any variables.
+
+use crate::ark::{FuncId, Function};
+use crate::opcode;
+
+/// A decoded bytecode instruction.
+///
+/// See [`opcode`] and `docs/opcodes.md` for what each instruction does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    NoOp,
+    /// Jumps to an instruction index.
+    Jump(usize),
+    /// Jumps to an instruction index if the popped value is `true`.
+    JumpIf(usize),
+    Call(u32),
+    Ret,
+    EnterTempFrame,
+    ExitTempFrame,
+    PushFloat(f64),
+    PushInt(i64),
+    PushBool(bool),
+    /// Pushes a native function or a user function which doesn't capture any variables.
+    PushFunc(FuncId),
+    /// Pushes a user function which captures variables.
+    PushClosure(FuncId),
+    PushNil,
+    PushString(usize),
+    PushObject(bool),
+    PushList,
+    Pop,
+    Dup,
+    Swap,
+    StoreVar(usize),
+    LoadVar(usize),
+    StoreVarBoxed(usize),
+    Add,
+    Sub,
+    Mult,
+    Div,
+    Equal,
+    LessThan,
+    Not,
+    And,
+    Or,
+    GreaterThan,
+    Concat,
+    ToString,
+    AddField(bool),
+    WriteField,
+    ReadField,
+    AppendElement,
+    WriteElement,
+    ReadElement,
+    IntDiv,
+    Mod,
+    Neg,
+    Pow,
+    LessThanOrEqual,
+    GreaterThanOrEqual,
+    NotEqual,
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    ShiftLeft,
+    ShiftRight,
+    ShiftRightLogical,
+    Boundary,
+    /// An opcode which isn't known to the vm.
+    /// Produces an exception if executed.
+    Unknown(u8),
+    /// An instruction which operands extend past the end of the code section.
+    /// Produces an exception if executed.
+    Truncated,
+    /// A reference to a user function which doesn't exist.
+    /// Produces an exception if executed.
+    InvalidFunction(u32),
+    /// A reference to a string which doesn't exist.
+    /// Produces an exception if executed.
+    InvalidString(usize),
+}
+
+/// A code section decoded into instructions.
+#[derive(Debug, Default)]
+pub struct DecodedCode {
+    /// The decoded instructions.
+    pub instructions: Vec<Instruction>,
+    /// The byte address of each instruction, in ascending order.
+    addresses: Vec<usize>,
+    /// The length of the code section in bytes.
+    length: usize,
+}
+
+impl DecodedCode {
+    /// Decodes a code section which references a set of functions and an amount of strings.
+    pub fn decode(code: &[u8], functions: &[Function], string_count: usize) -> Self {
+        let mut decoder = Decoder {
+            code,
+            address: 0
+        };
+
+        let mut instructions = Vec::new();
+        let mut addresses = Vec::new();
+
+        while decoder.address < code.len() {
+            addresses.push(decoder.address);
+
+            match decoder.decode() {
+                Some(instruction) => instructions.push(instruction),
+                None => {
+                    // There's nothing more to decode after a truncated instruction.
+                    instructions.push(Instruction::Truncated);
+                    break;
+                }
+            }
+        }
+
+        let mut decoded = Self {
+            instructions,
+            addresses,
+            length: code.len(),
+        };
+
+        // Jump targets have been decoded as byte addresses, resolve them into instruction indices.
+        for i in 0..decoded.instructions.len() {
+            let instruction = decoded.instructions[i];
+
+            decoded.instructions[i] = match instruction {
+                Instruction::Jump(address) => Instruction::Jump(decoded.resolve(address)),
+                Instruction::JumpIf(address) => Instruction::JumpIf(decoded.resolve(address)),
+
+                Instruction::PushFunc(id) if !id.is_native() => match functions.get(id.decode() as usize) {
+                    Some(function) if !function.captures.is_empty() => Instruction::PushClosure(id),
+                    Some(_) => instruction,
+                    None => Instruction::InvalidFunction(id.decode()),
+                },
+
+                Instruction::PushString(index) if index >= string_count => Instruction::InvalidString(index),
+
+                _ => instruction
+            };
+        }
+
+        decoded
+    }
+
+    /// Resolves a byte address into the index of the instruction at that address.
+    ///
+    /// Addresses which don't point to the start of an instruction are resolved into an index past the end
+    /// of the instructions, so that trying to execute the instruction at that index is an overrun.
+    pub fn resolve(&self, address: usize) -> usize {
+        self.index_of(address)
+            .unwrap_or(self.instructions.len())
+    }
+
+    /// Gets the index of the instruction at a byte address.
+    pub fn index_of(&self, address: usize) -> Option<usize> {
+        self.addresses.binary_search(&address).ok()
+    }
+
+    /// Gets the byte address of the instruction at an index.
+    ///
+    /// Indices past the end of the instructions are mapped to the end of the code section.
+    pub fn address_of(&self, index: usize) -> usize {
+        self.addresses.get(index)
+            .copied()
+            .unwrap_or(self.length)
+    }
+}
+
+/// Reads instructions from raw bytecode.
+struct Decoder<'a> {
+    code: &'a [u8],
+    address: usize,
+}
+
+impl Decoder<'_> {
+    /// Decodes the instruction at the current address and progresses past it.
+    /// Returns [`None`] if the instruction's operands extend past the end of the code.
+    fn decode(&mut self) -> Option<Instruction> {
+        let instruction = match self.read_u8()? {
+            opcode::NO_OP => Instruction::NoOp,
+            opcode::JUMP => Instruction::Jump(self.read_u32()? as usize),
+            opcode::JUMP_IF => Instruction::JumpIf(self.read_u32()? as usize),
+            opcode::CALL => Instruction::Call(self.read_u32()?),
+            opcode::RET => Instruction::Ret,
+            opcode::ENTER_TEMP_FRAME => Instruction::EnterTempFrame,
+            opcode::EXIT_TEMP_FRAME => Instruction::ExitTempFrame,
+            opcode::PUSH_FLOAT => Instruction::PushFloat(f64::from_be_bytes(self.read()?)),
+            opcode::PUSH_INT => Instruction::PushInt(i64::from_be_bytes(self.read()?)),
+            opcode::PUSH_BOOL => Instruction::PushBool(self.read_u8()? != 0),
+            opcode::PUSH_FUNC => Instruction::PushFunc(FuncId(self.read_u32()?)),
+            opcode::PUSH_NIL => Instruction::PushNil,
+            opcode::PUSH_STRING => Instruction::PushString(self.read_u32()? as usize),
+            opcode::PUSH_OBJECT => Instruction::PushObject(self.read_u8()? != 0),
+            opcode::PUSH_LIST => Instruction::PushList,
+            opcode::POP => Instruction::Pop,
+            opcode::DUP => Instruction::Dup,
+            opcode::SWAP => Instruction::Swap,
+            opcode::STORE_VAR => Instruction::StoreVar(self.read_u32()? as usize),
+            opcode::LOAD_VAR => Instruction::LoadVar(self.read_u32()? as usize),
+            opcode::STORE_VAR_BOXED => Instruction::StoreVarBoxed(self.read_u32()? as usize),
+            opcode::ADD => Instruction::Add,
+            opcode::SUB => Instruction::Sub,
+            opcode::MULT => Instruction::Mult,
+            opcode::DIV => Instruction::Div,
+            opcode::EQUAL => Instruction::Equal,
+            opcode::LESS_THAN => Instruction::LessThan,
+            opcode::NOT => Instruction::Not,
+            opcode::AND => Instruction::And,
+            opcode::OR => Instruction::Or,
+            opcode::GREATER_THAN => Instruction::GreaterThan,
+            opcode::CONCAT => Instruction::Concat,
+            opcode::TO_STRING => Instruction::ToString,
+            opcode::ADD_FIELD => Instruction::AddField(self.read_u8()? != 0),
+            opcode::WRITE_FIELD => Instruction::WriteField,
+            opcode::READ_FIELD => Instruction::ReadField,
+            opcode::APPEND_ELEMENT => Instruction::AppendElement,
+            opcode::WRITE_ELEMENT => Instruction::WriteElement,
+            opcode::READ_ELEMENT => Instruction::ReadElement,
+            opcode::INT_DIV => Instruction::IntDiv,
+            opcode::MOD => Instruction::Mod,
+            opcode::NEG => Instruction::Neg,
+            opcode::POW => Instruction::Pow,
+            opcode::LESS_THAN_OR_EQUAL => Instruction::LessThanOrEqual,
+            opcode::GREATER_THAN_OR_EQUAL => Instruction::GreaterThanOrEqual,
+            opcode::NOT_EQUAL => Instruction::NotEqual,
+            opcode::BIT_AND => Instruction::BitAnd,
+            opcode::BIT_OR => Instruction::BitOr,
+            opcode::BIT_XOR => Instruction::BitXor,
+            opcode::BIT_NOT => Instruction::BitNot,
+            opcode::SHIFT_LEFT => Instruction::ShiftLeft,
+            opcode::SHIFT_RIGHT => Instruction::ShiftRight,
+            opcode::SHIFT_RIGHT_LOGICAL => Instruction::ShiftRightLogical,
+            opcode::BOUNDARY => Instruction::Boundary,
+            x => Instruction::Unknown(x),
+        };
+
+        Some(instruction)
+    }
+
+    /// Reads a fixed amount of bytes.
+    fn read<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.code.get(self.address..(self.address + N))?
+            .try_into()
+            .ok()?;
+
+        self.address += N;
+
+        Some(bytes)
+    }
+
+    fn read_u8(&mut self) -> Option<u8> {
+        self.read::<1>().map(|[x]| x)
+    }
+
+    fn read_u32(&mut self) -> Option<u32> {
+        self.read().map(u32::from_be_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_resolves_jump_targets_into_instruction_indices() {
+        let code = [
+            opcode::PUSH_BOOL, 1,
+            opcode::JUMP_IF, 0, 0, 0, 8,
+            opcode::NO_OP,
+            opcode::RET,
+        ];
+
+        let decoded = DecodedCode::decode(&code, &[], 0);
+
+        assert_eq!(decoded.instructions, [
+            Instruction::PushBool(true),
+            Instruction::JumpIf(3),
+            Instruction::NoOp,
+            Instruction::Ret,
+        ]);
+        assert_eq!(decoded.address_of(3), 8);
+        assert_eq!(decoded.address_of(4), 9);
+        assert_eq!(decoded.index_of(7), Some(2));
+    }
+
+    #[test]
+    fn decode_resolves_jumps_into_the_middle_of_instructions_past_the_end() {
+        let code = [
+            opcode::JUMP, 0, 0, 0, 2,
+        ];
+
+        let decoded = DecodedCode::decode(&code, &[], 0);
+
+        assert_eq!(decoded.instructions, [
+            Instruction::Jump(1),
+        ]);
+    }
+
+    #[test]
+    fn decode_marks_truncated_instructions() {
+        let code = [
+            opcode::POP,
+            opcode::PUSH_FLOAT, 0, 0,
+        ];
+
+        let decoded = DecodedCode::decode(&code, &[], 0);
+
+        assert_eq!(decoded.instructions, [
+            Instruction::Pop,
+            Instruction::Truncated,
+        ]);
+    }
+
+    #[test]
+    fn decode_resolves_function_and_string_references() {
+        let function = |id, captures: &[u32]| Function {
+            id: FuncId(id),
+            name_index: 0,
+            arity: 0,
+            locals_count: 0,
+            captures_count: captures.len() as u32,
+            address: 0,
+            captures: captures.to_vec()
+        };
+        let functions = [function(0, &[]), function(1, &[0])];
+
+        let code = [
+            opcode::PUSH_FUNC, 0, 0, 0, 0,
+            opcode::PUSH_FUNC, 0, 0, 0, 1,
+            opcode::PUSH_FUNC, 0, 0, 0, 2,
+            opcode::PUSH_FUNC, 0x80, 0, 0, 2,
+            opcode::PUSH_STRING, 0, 0, 0, 0,
+            opcode::PUSH_STRING, 0, 0, 0, 1,
+        ];
+
+        let decoded = DecodedCode::decode(&code, &functions, 1);
+
+        assert_eq!(decoded.instructions, [
+            Instruction::PushFunc(FuncId(0)),
+            Instruction::PushClosure(FuncId(1)),
+            Instruction::InvalidFunction(2),
+            Instruction::PushFunc(FuncId(0x8000_0002)),
+            Instruction::PushString(0),
+            Instruction::InvalidString(1),
+        ]);
+    }
+
+    #[test]
+    fn instructions_stay_small() {
+        assert_eq!(size_of::<Instruction>(), 16);
+    }
+}
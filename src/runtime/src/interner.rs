@@ -0,0 +1,148 @@
+//! The runtime string intern table.
+//!
+//! Strings which are used as keys (e.g. of a [`crate::value::Map`]) are interned into an [`Interner`],
+//! which gives every distinct string a single shared [`InternedStr`]. Keys can then store and compare
+//! the shared string by address instead of storing their own copy of the string and comparing it by content.
+//!
+//! The constant strings of the string section are interned up-front, so interning a
+//! [`crate::value::Value::InternedString`] is just a table lookup.
+//! Other strings stay in the table only for as long as something besides the table uses them,
+//! and are evicted by [`Interner::evict_unused`], which the vm calls after every garbage collection.
+
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::rc::Rc;
+
+/// A string interned into an [`Interner`].
+///
+/// Since an intern table never contains the same string twice,
+/// two interned strings from the same table are equal exactly if they share the same allocation.
+#[derive(Debug, Clone)]
+pub struct InternedStr(Rc<str>);
+
+impl PartialEq for InternedStr {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for InternedStr {}
+
+impl Hash for InternedStr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Rc::as_ptr(&self.0).cast::<u8>().hash(state);
+    }
+}
+
+impl Deref for InternedStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A table of interned strings.
+#[derive(Debug, Default)]
+pub struct Interner {
+    strings: HashSet<Rc<str>>,
+    /// The interned constant strings, indexed by the index of the string in the string section.
+    consts: Vec<InternedStr>,
+}
+
+impl Interner {
+    /// Creates a new intern table containing the constant strings of the string section.
+    pub fn new(consts: &[String]) -> Self {
+        let mut interner = Self::default();
+
+        interner.consts = consts.iter()
+            .map(|str| interner.intern(str))
+            .collect();
+
+        interner
+    }
+
+    /// Interns a string, returning the existing string if it's already been interned.
+    pub fn intern(&mut self, str: &str) -> InternedStr {
+        if let Some(interned) = self.lookup(str) {
+            return interned;
+        }
+
+        let str: Rc<str> = Rc::from(str);
+        self.strings.insert(str.clone());
+
+        InternedStr(str)
+    }
+
+    /// Gets a string if it has been interned.
+    pub fn lookup(&self, str: &str) -> Option<InternedStr> {
+        self.strings.get(str)
+            .cloned()
+            .map(InternedStr)
+    }
+
+    /// Gets a constant string by its index in the string section.
+    pub fn lookup_const(&self, index: usize) -> Option<InternedStr> {
+        self.consts.get(index).cloned()
+    }
+
+    /// Removes every string which is no longer used by anything other than the table itself.
+    /// Constant strings are never removed.
+    pub fn evict_unused(&mut self) {
+        self.strings.retain(|str| Rc::strong_count(str) > 1);
+    }
+
+    /// Gets the amount of interned strings.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interning_the_same_string_gives_the_same_string() {
+        let mut interner = Interner::new(&[]);
+
+        let a = interner.intern("abc");
+        let b = interner.intern("def");
+
+        assert_ne!(a, b);
+        assert_eq!(interner.intern("abc"), a);
+        assert_eq!(&*b, "def");
+    }
+
+    #[test]
+    fn constant_strings_are_interned_up_front() {
+        let consts = ["a".to_owned(), "b".to_owned(), "a".to_owned()];
+        let interner = Interner::new(&consts);
+
+        assert_eq!(interner.lookup_const(0), interner.lookup("a"));
+        assert_eq!(interner.lookup_const(2), interner.lookup("a"));
+        assert_eq!(interner.lookup_const(1), interner.lookup("b"));
+        assert_eq!(interner.lookup("c"), None);
+    }
+
+    #[test]
+    fn evicting_removes_only_unused_strings() {
+        let consts = ["a".to_owned()];
+        let mut interner = Interner::new(&consts);
+
+        let b = interner.intern("b");
+        interner.intern("c");
+
+        interner.evict_unused();
+
+        assert_eq!(interner.len(), 2);
+        assert!(interner.lookup("a").is_some());
+        assert_eq!(interner.lookup("b"), Some(b));
+        assert_eq!(interner.lookup("c"), None);
+    }
+}
@@ -1,12 +1,13 @@
-#![feature(never_type)]
-#![feature(assert_matches)]
 #![feature(vec_push_within_capacity)]
 #![feature(string_from_utf8_lossy_owned)]
 
 pub mod opcode;
+pub mod instruction;
 pub mod ark;
 pub mod value;
 pub mod vm;
 pub mod exception;
 pub mod heap;
+pub mod shape;
+pub mod interner;
 mod native;
@@ -1,3 +1,5 @@
+use smallvec::SmallVec;
+
 use crate::value::Value;
 use crate::vm::{Vm, Result};
 
@@ -14,4 +16,11 @@ pub struct NativeFunction {
     pub function: NativeFn,
 }
 
-pub type NativeFn = fn(&mut Vm, Vec<Value>) -> Result<Value>;
+/// The amount of arguments which can be passed to a native function without allocating.
+pub const INLINE_ARGS: usize = 4;
+
+/// The arguments passed to a native function.
+/// Up to [`INLINE_ARGS`] arguments are stored inline, so most calls don't have to allocate.
+pub type Args = SmallVec<[Value; INLINE_ARGS]>;
+
+pub type NativeFn = fn(&mut Vm, Args) -> Result<Value>;
@@ -3,12 +3,25 @@ use std::fs;
 use std::iter;
 
 use crate::exception::Exception;
-use crate::native::NativeFn;
+use crate::native::{Args, NativeFn};
 use crate::vm::{Vm, Result};
-use crate::value::{Closure, List, Value};
+use crate::value::{Closure, List, Type, Value};
 
 use super::NativeFunction;
 
+mod bytes;
+mod json;
+mod lists;
+mod maps;
+mod math;
+mod numbers;
+mod objects;
+mod random;
+mod regex;
+mod string_builders;
+mod strings;
+mod time;
+
 /// Gets a vector of native functions.
 pub fn get_functions() -> HashMap<u32, NativeFunction> {
     let functions: [(u32, &'static str, NativeFn); _] = [
@@ -22,6 +35,33 @@ pub fn get_functions() -> HashMap<u32, NativeFunction> {
 
         // Strings
         (0x100, "toString", to_string),
+        (0x101, "substring", strings::substring),
+        (0x102, "indexOf", index_of),
+        (0x103, "lastIndexOf", strings::last_index_of),
+        (0x104, "contains", contains),
+        (0x105, "startsWith", strings::starts_with),
+        (0x106, "endsWith", strings::ends_with),
+        (0x107, "split", strings::split),
+        (0x108, "join", strings::join),
+        (0x109, "replace", strings::replace),
+        (0x10A, "trim", strings::trim),
+        (0x10B, "trimStart", strings::trim_start),
+        (0x10C, "trimEnd", strings::trim_end),
+        (0x10D, "toUpper", strings::to_upper),
+        (0x10E, "toLower", strings::to_lower),
+        (0x10F, "repeat", strings::repeat),
+        (0x110, "padStart", strings::pad_start),
+        (0x111, "padEnd", strings::pad_end),
+        (0x112, "chars", strings::chars),
+        (0x113, "codePointAt", strings::code_point_at),
+        (0x114, "fromCodePoint", strings::from_code_point),
+
+        // String builders, at the end of the range of strings
+        (0x160, "stringBuilder", string_builders::string_builder),
+        (0x161, "sbAppend", string_builders::sb_append),
+        (0x162, "sbToString", string_builders::sb_to_string),
+        (0x163, "sbLength", string_builders::sb_length),
+        (0x164, "sbClear", string_builders::sb_clear),
         
         // Lists
         (0x180, "push", push),
@@ -38,6 +78,126 @@ pub fn get_functions() -> HashMap<u32, NativeFunction> {
         (0x18B, "all", all),
         (0x18C, "find", find),
         (0x18D, "length", length),
+        (0x18E, "sort", lists::sort),
+        (0x18F, "sortBy", lists::sort_by),
+        (0x190, "sortWith", lists::sort_with),
+        (0x191, "binarySearch", lists::binary_search),
+        (0x192, "distinct", lists::distinct),
+        (0x193, "range", lists::range),
+        (0x194, "replicate", lists::replicate),
+        (0x195, "zip", lists::zip),
+        (0x196, "enumerate", lists::enumerate),
+        (0x197, "chunk", lists::chunk),
+        (0x198, "window", lists::window),
+        (0x199, "take", lists::take),
+        (0x19A, "skip", lists::skip),
+        (0x19B, "takeWhile", lists::take_while),
+        (0x19C, "skipWhile", lists::skip_while),
+        (0x19D, "groupBy", lists::group_by),
+        (0x19E, "partition", lists::partition),
+        (0x19F, "sum", lists::sum),
+        (0x1A0, "count", lists::count),
+
+        // Maps
+        (0x200, "mapNew", maps::map_new),
+        (0x201, "mapGet", maps::map_get),
+        (0x202, "mapSet", maps::map_set),
+        (0x203, "mapHas", maps::map_has),
+        (0x204, "mapRemove", maps::map_remove),
+        (0x205, "mapKeys", maps::map_keys),
+        (0x206, "mapValues", maps::map_values),
+        (0x207, "mapEntries", maps::map_entries),
+        (0x208, "mapSize", maps::map_size),
+
+        // Numbers
+        (0x280, "toInteger", numbers::to_integer),
+        (0x281, "toFloat", numbers::to_float),
+        (0x282, "isInteger", numbers::is_integer),
+        (0x283, "parseNumber", numbers::parse_number),
+        (0x284, "parseInt", numbers::parse_int),
+        (0x285, "formatNumber", numbers::format_number),
+
+        // Bytes
+        (0x300, "bytes", bytes::bytes),
+        (0x301, "bytesLength", bytes::bytes_length),
+        (0x302, "bytesGet", bytes::bytes_get),
+        (0x303, "bytesSlice", bytes::bytes_slice),
+        (0x304, "bytesConcat", bytes::bytes_concat),
+        (0x305, "bytesToList", bytes::bytes_to_list),
+        (0x306, "utf8Encode", bytes::utf8_encode),
+        (0x307, "utf8Decode", bytes::utf8_decode),
+        (0x308, "hexEncode", bytes::hex_encode),
+        (0x309, "hexDecode", bytes::hex_decode),
+        (0x30A, "readFileBytes", bytes::read_file_bytes),
+        (0x30B, "writeFileBytes", bytes::write_file_bytes),
+        (0x30C, "getInputBytes", bytes::get_input_bytes),
+
+        // Math
+        (0x380, "abs", math::abs),
+        (0x381, "floor", math::floor),
+        (0x382, "ceil", math::ceil),
+        (0x383, "round", math::round),
+        (0x384, "trunc", math::trunc),
+        (0x385, "sign", math::sign),
+        (0x386, "sqrt", math::sqrt),
+        (0x387, "pow", math::pow),
+        (0x388, "exp", math::exp),
+        (0x389, "ln", math::ln),
+        (0x38A, "log", math::log),
+        (0x38B, "sin", math::sin),
+        (0x38C, "cos", math::cos),
+        (0x38D, "tan", math::tan),
+        (0x38E, "asin", math::asin),
+        (0x38F, "acos", math::acos),
+        (0x390, "atan", math::atan),
+        (0x391, "atan2", math::atan2),
+        (0x392, "min", math::min),
+        (0x393, "max", math::max),
+        (0x394, "clamp", math::clamp),
+        (0x395, "isNaN", math::is_nan),
+        (0x396, "isFinite", math::is_finite),
+        (0x397, "pi", math::pi),
+        (0x398, "e", math::e),
+        (0x399, "infinity", math::infinity),
+        (0x39A, "nan", math::nan),
+
+        // JSON
+        (0x400, "jsonParse", json::json_parse),
+        (0x401, "jsonStringify", json::json_stringify),
+
+        // Regular expressions
+        (0x480, "regexMatch", regex::regex_match),
+        (0x481, "regexFindAll", regex::regex_find_all),
+        (0x482, "regexReplace", regex::regex_replace),
+        (0x483, "regexSplit", regex::regex_split),
+
+        // Time
+        (0x500, "now", time::now),
+        (0x501, "monotonic", time::monotonic),
+        (0x502, "sleep", time::sleep),
+        (0x503, "toDate", time::to_date),
+        (0x504, "fromDate", time::from_date),
+        (0x505, "formatDate", time::format_date),
+        (0x506, "parseDate", time::parse_date),
+
+        // Random numbers
+        (0x580, "random", random::random),
+        (0x581, "randomInt", random::random_int),
+        (0x582, "randomChoice", random::random_choice),
+        (0x583, "shuffle", random::shuffle),
+        (0x584, "setSeed", random::set_seed),
+
+        // Objects
+        (0x600, "keys", objects::keys),
+        (0x601, "values", objects::values),
+        (0x602, "entries", objects::entries),
+        (0x603, "hasField", objects::has_field),
+        (0x604, "getField", objects::get_field),
+        (0x605, "removeField", objects::remove_field),
+        (0x606, "isMutable", objects::is_mutable),
+        (0x607, "isDynamic", objects::is_dynamic),
+        (0x608, "merge", objects::merge),
+        (0x609, "freeze", objects::freeze),
     ];
 
     functions.into_iter()
@@ -51,7 +211,7 @@ pub fn get_functions() -> HashMap<u32, NativeFunction> {
         .collect()
 }
 
-fn print(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn print(vm: &mut Vm, args: Args) -> Result<Value> {
     let (value, append_newline) = match args[..] {
         [] => (String::from(""), true),
 
@@ -76,7 +236,7 @@ fn print(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     Ok(().into())
 }
 
-fn get_input(vm: &mut Vm, _: Vec<Value>) -> Result<Value> {
+fn get_input(vm: &mut Vm, _: Args) -> Result<Value> {
     let mut buf = Vec::new();
     match vm.input().read(&mut buf) {
         Ok(x) => x,
@@ -93,7 +253,7 @@ fn get_input(vm: &mut Vm, _: Vec<Value>) -> Result<Value> {
     vm.alloc_string(str)
 }
 
-fn read_file(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn read_file(vm: &mut Vm, args: Args) -> Result<Value> {
     let path = match args[..] {
         [] => return Err(vm.exception(
             Exception::BadArity { expected: 1, or_more: false, actual: 0 }
@@ -121,7 +281,7 @@ fn read_file(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     }
 }
 
-fn write_file(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn write_file(vm: &mut Vm, args: Args) -> Result<Value> {
     let arg_count = args.len() as u32;
     let (path, content) = match args[..] {
         [] | [_] => return Err(vm.exception(
@@ -150,7 +310,7 @@ fn write_file(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     }
 }
 
-fn to_string(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn to_string(vm: &mut Vm, args: Args) -> Result<Value> {
     match args[..] {
         [] => {
             vm.alloc_string(String::from(""))
@@ -163,7 +323,7 @@ fn to_string(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     }
 }
 
-fn push(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn push(vm: &mut Vm, args: Args) -> Result<Value> {
     let ((list, _), value) = match args[..] {
         [] | [_] => return Err(vm.exception(
             Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
@@ -180,7 +340,7 @@ fn push(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     Ok(().into())
 }
 
-fn pop(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn pop(vm: &mut Vm, args: Args) -> Result<Value> {
     let (list, _) = match args[..] {
         [] => return Err(vm.exception(
             Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
@@ -197,7 +357,7 @@ fn pop(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     Ok(val)
 }
 
-fn append(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn append(vm: &mut Vm, args: Args) -> Result<Value> {
     let ((list, _), value) = match args[..] {
         [] | [_] => return Err(vm.exception(
             Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
@@ -215,7 +375,7 @@ fn append(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     vm.alloc_list(vec)
 }
 
-fn concat(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn concat(vm: &mut Vm, args: Args) -> Result<Value> {
     let ((a, _), (b, _)) = match args[..] {
         [] | [_] => return Err(vm.exception(
             Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
@@ -234,7 +394,7 @@ fn concat(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     vm.alloc_list(vec)
 }
 
-fn slice(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn slice(vm: &mut Vm, args: Args) -> Result<Value> {
     let ((List(list), _), start, end) = match args[..] {
         [] | [_] | [_, _] => return Err(vm.exception(
             Exception::BadArity { expected: 3, or_more: false, actual: args.len() as u32 }
@@ -242,13 +402,11 @@ fn slice(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
 
         [list, start, end, ..] => (
             vm.coerce_to_list(list)?,
-            vm.coerce_to_number(start)?,
-            vm.coerce_to_number(end)?
+            vm.to_index(start)?,
+            vm.to_index(end)?
         )
     };
     
-    let start = vm.to_integer(start)?;
-    let end = vm.to_integer(end)?;
     let start = start.clamp(0, list.len() as i64);
     let end = end.clamp(0, list.len() as i64);
 
@@ -263,7 +421,7 @@ fn slice(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     vm.alloc_list(slice)
 }
 
-fn map(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn map(vm: &mut Vm, args: Args) -> Result<Value> {
     let ((List(source), _), map) = match args[..] {
         [] | [_] => return Err(vm.exception(
             Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
@@ -284,7 +442,7 @@ fn map(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     vm.alloc_list(result)
 }
 
-fn flat_map(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn flat_map(vm: &mut Vm, args: Args) -> Result<Value> {
     let ((List(source), _), map) = match args[..] {
         [] | [_] => return Err(vm.exception(
             Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
@@ -307,7 +465,7 @@ fn flat_map(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     vm.alloc_list(result)
 }
 
-fn filter(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn filter(vm: &mut Vm, args: Args) -> Result<Value> {
     let ((List(source), _), filter) = match args[..] {
         [] | [_] => return Err(vm.exception(
             Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
@@ -333,7 +491,7 @@ fn filter(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     vm.alloc_list(result)
 }
 
-fn reduce(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn reduce(vm: &mut Vm, args: Args) -> Result<Value> {
     let ((List(source), _), seed, reduce) = match args[..] {
         [] | [_] => return Err(vm.exception(
             Exception::BadArity { expected: 2, or_more: true, actual: args.len() as u32 }
@@ -370,7 +528,7 @@ fn reduce(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     Ok(result)
 }
 
-fn reverse(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn reverse(vm: &mut Vm, args: Args) -> Result<Value> {
     let (List(source), _) = match args[..] {
         [] => return Err(vm.exception(
             Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
@@ -385,7 +543,7 @@ fn reverse(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     vm.alloc_list(result)
 }
 
-fn any(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn any(vm: &mut Vm, args: Args) -> Result<Value> {
     let ((List(source), _), predicate) = match args[..] {
         [] | [_] => return Err(vm.exception(
             Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
@@ -413,7 +571,7 @@ fn any(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     Ok(false.into())
 }
 
-fn all(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn all(vm: &mut Vm, args: Args) -> Result<Value> {
     let ((List(source), _), predicate) = match args[..] {
         [] | [_] => return Err(vm.exception(
             Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
@@ -441,7 +599,7 @@ fn all(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     Ok(true.into())
 }
 
-fn find(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
+fn find(vm: &mut Vm, args: Args) -> Result<Value> {
     let ((List(source), _), predicate, from_end) = match args[..] {
         [] | [_] => return Err(vm.exception(
             Exception::BadArity { expected: 2, or_more: true, actual: args.len() as u32 }
@@ -480,14 +638,36 @@ fn find(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
     }
 }
 
-fn length(vm: &mut Vm, args: Vec<Value>) -> Result<Value> {
-    let (List(list), _) = match args[..] {
+fn length(vm: &mut Vm, args: Args) -> Result<Value> {
+    let val = match args[..] {
         [] => return Err(vm.exception(
             Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
         )),
 
-        [list, ..] => vm.coerce_to_list(list)?,
+        [val, ..] => val,
     };
 
+    // Strings are measured in Unicode scalar values, the same way the string functions index them.
+    if let Some(str) = vm.try_get_string(val)? {
+        return Ok(str.chars().count().into());
+    }
+
+    let (List(list), _) = vm.coerce_to_list(val)?;
+
     Ok(list.len().into())
 }
+
+fn index_of(vm: &mut Vm, args: Args) -> Result<Value> {
+    // Lists are searched for an element, and anything else is searched as a string.
+    match args.first() {
+        Some(&source) if vm.get_type(source)? == Type::List => lists::index_of(vm, args),
+        _ => strings::index_of(vm, args)
+    }
+}
+
+fn contains(vm: &mut Vm, args: Args) -> Result<Value> {
+    match args.first() {
+        Some(&source) if vm.get_type(source)? == Type::List => lists::contains(vm, args),
+        _ => strings::contains(vm, args)
+    }
+}
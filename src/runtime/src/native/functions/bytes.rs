@@ -0,0 +1,276 @@
+use std::fs;
+
+use crate::exception::Exception;
+use crate::heap::HeapValue;
+use crate::native::Args;
+use crate::vm::{Vm, Result};
+use crate::value::{List, Value};
+
+fn alloc_bytes(vm: &mut Vm, bytes: Vec<u8>) -> Result<Value> {
+    vm.heap_alloc(HeapValue::Bytes(bytes))
+        .map(Value::Object)
+}
+
+pub fn bytes(vm: &mut Vm, args: Args) -> Result<Value> {
+    let bytes = match args[..] {
+        [] => Vec::new(),
+
+        [source, ..] => {
+            let (List(source), _) = vm.coerce_to_list(source)?;
+
+            let mut bytes = Vec::with_capacity(source.len());
+            for x in source {
+                let x = vm.coerce_to_integer(*x)?;
+
+                let byte = u8::try_from(x)
+                    .map_err(|_| vm.exception(Exception::InvalidByte(x)))?;
+
+                bytes.push(byte);
+            }
+
+            bytes
+        }
+    };
+
+    alloc_bytes(vm, bytes)
+}
+
+pub fn bytes_length(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (bytes, _) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [bytes, ..] => vm.coerce_to_bytes(bytes)?
+    };
+
+    Ok(bytes.len().into())
+}
+
+pub fn bytes_get(vm: &mut Vm, args: Args) -> Result<Value> {
+    let ((bytes, _), index) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [bytes, index, ..] => (
+            vm.coerce_to_bytes(bytes)?,
+            vm.to_index(index)?
+        )
+    };
+
+    let byte = usize::try_from(index).ok()
+        .and_then(|i| bytes.get(i))
+        .copied()
+        .ok_or_else(|| vm.exception(Exception::OutOfBoundsIndex(index, bytes.len())))?;
+
+    Ok(Value::Integer(byte as i64))
+}
+
+pub fn bytes_slice(vm: &mut Vm, args: Args) -> Result<Value> {
+    let ((bytes, _), start, end) = match args[..] {
+        [] | [_] | [_, _] => return Err(vm.exception(
+            Exception::BadArity { expected: 3, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [bytes, start, end, ..] => (
+            vm.coerce_to_bytes(bytes)?,
+            vm.to_index(start)?,
+            vm.to_index(end)?
+        )
+    };
+
+    let start = start.clamp(0, bytes.len() as i64) as usize;
+    let end = end.clamp(0, bytes.len() as i64) as usize;
+
+    let slice = if start < end {
+        bytes[start..end].to_vec()
+    } else {
+        Vec::new()
+    };
+
+    alloc_bytes(vm, slice)
+}
+
+pub fn bytes_concat(vm: &mut Vm, args: Args) -> Result<Value> {
+    let ((a, _), (b, _)) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [a, b, ..] => (
+            vm.coerce_to_bytes(a)?,
+            vm.coerce_to_bytes(b)?
+        )
+    };
+
+    let bytes = [a.as_slice(), b.as_slice()].concat();
+
+    alloc_bytes(vm, bytes)
+}
+
+pub fn bytes_to_list(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (bytes, _) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [bytes, ..] => vm.coerce_to_bytes(bytes)?
+    };
+
+    let values = bytes.iter()
+        .map(|byte| Value::Integer(*byte as i64))
+        .collect::<Vec<_>>();
+
+    vm.alloc_list(values)
+}
+
+pub fn utf8_encode(vm: &mut Vm, args: Args) -> Result<Value> {
+    let str = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, ..] => vm.to_string(str)?
+    };
+
+    alloc_bytes(vm, str.into_bytes())
+}
+
+pub fn utf8_decode(vm: &mut Vm, args: Args) -> Result<Value> {
+    let ((bytes, _), lossy) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [bytes] => (vm.coerce_to_bytes(bytes)?, false),
+
+        [bytes, lossy, ..] => (
+            vm.coerce_to_bytes(bytes)?,
+            vm.coerce_to_bool(lossy)?
+        )
+    };
+
+    let str = match String::from_utf8(bytes.clone()) {
+        Ok(x) => x,
+        Err(e) if lossy => e.into_utf8_lossy(),
+        Err(e) => return Err(vm.exception(
+            Exception::NonUtf8(e.into_utf8_lossy())
+        ))
+    };
+
+    vm.alloc_string(str)
+}
+
+pub fn hex_encode(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (bytes, _) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [bytes, ..] => vm.coerce_to_bytes(bytes)?
+    };
+
+    let str = bytes.iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+
+    vm.alloc_string(str)
+}
+
+/// Decodes a string of pairs of hexadecimal digits into bytes.
+/// Returns [`None`] if the string contains anything other than pairs of digits.
+fn decode_hex(str: &str) -> Option<Vec<u8>> {
+    let digits = str.as_bytes();
+
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+
+    digits.chunks_exact(2)
+        .map(|pair| {
+            let [high, low] = [pair[0], pair[1]].map(|digit| (digit as char).to_digit(16));
+            Some((high? * 16 + low?) as u8)
+        })
+        .collect()
+}
+
+pub fn hex_decode(vm: &mut Vm, args: Args) -> Result<Value> {
+    let str = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, ..] => vm.to_string(str)?
+    };
+
+    let bytes = decode_hex(&str)
+        .ok_or_else(|| vm.exception(Exception::InvalidHex(str)))?;
+
+    alloc_bytes(vm, bytes)
+}
+
+pub fn read_file_bytes(vm: &mut Vm, args: Args) -> Result<Value> {
+    let path = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: 0 }
+        )),
+
+        [path, ..] => vm.to_string(path)?,
+    };
+
+    match fs::read(path) {
+        Ok(content) => alloc_bytes(vm, content),
+        Err(_) => Ok(().into()),
+    }
+}
+
+pub fn write_file_bytes(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (path, content) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [path, content, ..] => (vm.to_string(path)?, content),
+    };
+
+    let (content, _) = vm.coerce_to_bytes(content)?;
+
+    match fs::write(path, content) {
+        Ok(()) => Ok(Value::Bool(true)),
+        Err(_) => Ok(Value::Bool(false))
+    }
+}
+
+pub fn get_input_bytes(vm: &mut Vm, _: Args) -> Result<Value> {
+    let mut buf = Vec::new();
+    match vm.input().read(&mut buf) {
+        Ok(x) => x,
+        Err(e) => return Err(vm.exception(e))
+    };
+
+    alloc_bytes(vm, buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_hex_decodes_pairs_of_digits() {
+        assert_eq!(decode_hex(""), Some(vec![]));
+        assert_eq!(decode_hex("00ff7fA0"), Some(vec![0x00, 0xFF, 0x7F, 0xA0]));
+    }
+
+    #[test]
+    fn decode_hex_rejects_odd_lengths() {
+        assert_eq!(decode_hex("abc"), None);
+    }
+
+    #[test]
+    fn decode_hex_rejects_non_digits() {
+        assert_eq!(decode_hex("zz"), None);
+        assert_eq!(decode_hex("+f"), None);
+        assert_eq!(decode_hex("é"), None);
+    }
+}
@@ -0,0 +1,537 @@
+//! JSON parsing and serialization.
+//!
+//! JSON objects, arrays, strings, numbers, booleans and null map onto dynamic objects, lists, strings, numbers, booleans and `()`.
+//! Parsing allocates values directly onto the heap, keeping every container which is still being filled rooted
+//! so that its contents survive garbage collection.
+
+use std::iter;
+
+use crate::exception::{Exception, FormattedException};
+use crate::heap::{HeapAddress, HeapValue};
+use crate::native::Args;
+use crate::vm::{Vm, Result, MAX_NESTING_DEPTH};
+use crate::value::{List, Value};
+
+pub fn json_parse(vm: &mut Vm, args: Args) -> Result<Value> {
+    let str = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, ..] => vm.string_argument(str, "jsonParse", "string")?.to_owned()
+    };
+
+    // The source has to be copied out of the heap since parsing allocates.
+    vm.consume_fuel(str.len())?;
+
+    let mut parser = Parser { src: &str, pos: 0, depth: 0 };
+
+    let value = parser.parse_value(vm)?;
+
+    parser.skip_whitespace();
+    if parser.pos < str.len() {
+        return Err(parser.error(vm, "unexpected characters after the end of the value"));
+    }
+
+    Ok(value)
+}
+
+pub fn json_stringify(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (value, pretty) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [value] => (value, false),
+
+        [value, pretty, ..] => (value, vm.coerce_to_bool(pretty)?)
+    };
+
+    let mut writer = Writer {
+        vm,
+        pretty,
+        out: String::new(),
+        ancestors: Vec::new()
+    };
+    writer.write_value(value)?;
+
+    let str = writer.out;
+    vm.alloc_string(str)
+}
+
+/// A recursive descent parser over the source of a JSON value.
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    /// The amount of arrays and objects currently being parsed.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.src.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Consumes a byte, returning an error if the next byte is anything else.
+    fn expect(&mut self, vm: &Vm, byte: u8) -> Result<()> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected(vm))
+        }
+    }
+
+    /// Constructs an error at the current position.
+    fn error(&self, vm: &Vm, message: &str) -> FormattedException {
+        let before = &self.src[..self.pos];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+
+        vm.exception(Exception::InvalidJson {
+            message: message.into(),
+            line,
+            column
+        })
+    }
+
+    /// Constructs an error for the character at the current position.
+    fn unexpected(&self, vm: &Vm) -> FormattedException {
+        match self.src[self.pos..].chars().next() {
+            Some(char) => self.error(vm, &format!("unexpected character {char:?}")),
+            None => self.error(vm, "unexpected end of input")
+        }
+    }
+
+    fn parse_value(&mut self, vm: &mut Vm) -> Result<Value> {
+        vm.consume_fuel(1)?;
+
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some(b'[') => self.nested(vm, Self::parse_array),
+            Some(b'{') => self.nested(vm, Self::parse_object),
+            Some(b'"') => {
+                let str = self.parse_string(vm)?;
+                vm.alloc_string(str)
+            },
+            Some(b'-' | b'0'..=b'9') => self.parse_number(vm),
+            Some(b't') => self.parse_literal(vm, "true", true.into()),
+            Some(b'f') => self.parse_literal(vm, "false", false.into()),
+            Some(b'n') => self.parse_literal(vm, "null", ().into()),
+            _ => Err(self.unexpected(vm))
+        }
+    }
+
+    /// Parses an array or object, which can't be nested any deeper than other values.
+    fn nested(&mut self, vm: &mut Vm, parse: fn(&mut Self, &mut Vm) -> Result<Value>) -> Result<Value> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(vm.exception(Exception::TooDeeplyNested));
+        }
+
+        self.depth += 1;
+        let result = parse(self, vm);
+        self.depth -= 1;
+
+        result
+    }
+
+    fn parse_array(&mut self, vm: &mut Vm) -> Result<Value> {
+        self.expect(vm, b'[')?;
+
+        let list = vm.alloc_list(iter::empty())?;
+
+        vm.with_rooted(list, |vm| {
+            self.skip_whitespace();
+            if self.peek() == Some(b']') {
+                self.pos += 1;
+                return Ok(());
+            }
+
+            loop {
+                let element = self.parse_value(vm)?;
+
+                let (List(list), _) = vm.coerce_to_list_mut(list)?;
+                list.push(element);
+
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(b',') => self.pos += 1,
+                    Some(b']') => {
+                        self.pos += 1;
+                        return Ok(());
+                    },
+                    _ => return Err(self.unexpected(vm))
+                }
+            }
+        })?;
+
+        Ok(list)
+    }
+
+    fn parse_object(&mut self, vm: &mut Vm) -> Result<Value> {
+        self.expect(vm, b'{')?;
+
+        let object = vm.alloc_object(iter::empty(), true)?;
+
+        vm.with_rooted(object, |vm| {
+            self.skip_whitespace();
+            if self.peek() == Some(b'}') {
+                self.pos += 1;
+                return Ok(());
+            }
+
+            loop {
+                self.skip_whitespace();
+                if self.peek() != Some(b'"') {
+                    return Err(self.unexpected(vm));
+                }
+                let name = self.parse_string(vm)?;
+
+                self.skip_whitespace();
+                self.expect(vm, b':')?;
+
+                let value = self.parse_value(vm)?;
+
+                // Fields of dynamic objects are always mutable.
+                // Later fields with the same name replace earlier ones.
+                let (object, shapes) = vm.coerce_to_object_with_shapes(object)?;
+                object.add_field(shapes, name, value, true);
+
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(b',') => self.pos += 1,
+                    Some(b'}') => {
+                        self.pos += 1;
+                        return Ok(());
+                    },
+                    _ => return Err(self.unexpected(vm))
+                }
+            }
+        })?;
+
+        Ok(object)
+    }
+
+    fn parse_string(&mut self, vm: &Vm) -> Result<String> {
+        self.expect(vm, b'"')?;
+
+        let mut str = String::new();
+
+        loop {
+            let Some(char) = self.src[self.pos..].chars().next() else {
+                return Err(self.error(vm, "unterminated string"));
+            };
+
+            match char {
+                '"' => {
+                    self.pos += 1;
+                    return Ok(str);
+                },
+
+                '\\' => {
+                    self.pos += 1;
+
+                    let escaped = match self.peek() {
+                        Some(b'u') => {
+                            self.pos += 1;
+                            self.parse_unicode_escape(vm)?
+                        },
+                        Some(byte) => {
+                            let escaped = match byte {
+                                b'"' => '"',
+                                b'\\' => '\\',
+                                b'/' => '/',
+                                b'b' => '\u{8}',
+                                b'f' => '\u{c}',
+                                b'n' => '\n',
+                                b'r' => '\r',
+                                b't' => '\t',
+                                _ => return Err(self.error(vm, "invalid escape sequence"))
+                            };
+                            self.pos += 1;
+                            escaped
+                        },
+                        None => return Err(self.error(vm, "unterminated string"))
+                    };
+
+                    str.push(escaped);
+                },
+
+                '\0'..='\u{1f}' => return Err(self.error(vm, "control characters have to be escaped in strings")),
+
+                _ => {
+                    self.pos += char.len_utf8();
+                    str.push(char);
+                }
+            }
+        }
+    }
+
+    /// Parses the digits of a `\u` escape, combining surrogate pairs into a single character.
+    fn parse_unicode_escape(&mut self, vm: &Vm) -> Result<char> {
+        let high = self.parse_hex4(vm)?;
+
+        let code_point = match high {
+            0xD800..=0xDBFF => {
+                if !self.src[self.pos..].starts_with("\\u") {
+                    return Err(self.error(vm, "unpaired surrogate in unicode escape"));
+                }
+                self.pos += 2;
+
+                let low = self.parse_hex4(vm)?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(self.error(vm, "unpaired surrogate in unicode escape"));
+                }
+
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+            },
+            0xDC00..=0xDFFF => return Err(self.error(vm, "unpaired surrogate in unicode escape")),
+            _ => high
+        };
+
+        char::from_u32(code_point)
+            .ok_or_else(|| self.error(vm, "invalid unicode escape"))
+    }
+
+    fn parse_hex4(&mut self, vm: &Vm) -> Result<u32> {
+        let digits = self.src.get(self.pos..self.pos + 4)
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error(vm, "expected 4 hex digits in unicode escape"))?;
+
+        self.pos += 4;
+
+        u32::from_str_radix(digits, 16)
+            .map_err(|_| self.error(vm, "expected 4 hex digits in unicode escape"))
+    }
+
+    fn parse_number(&mut self, vm: &Vm) -> Result<Value> {
+        let start = self.pos;
+
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+
+        match self.peek() {
+            Some(b'0') => self.pos += 1,
+            Some(b'1'..=b'9') => self.skip_digits(),
+            _ => return Err(self.unexpected(vm))
+        }
+
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            self.expect_digits(vm)?;
+        }
+
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            self.expect_digits(vm)?;
+        }
+
+        // The grammar above only accepts numbers which Rust can parse as well.
+        let x = self.src[start..self.pos].parse::<f64>()
+            .map_err(|_| self.error(vm, "invalid number"))?;
+
+        Ok(Value::Number(x))
+    }
+
+    fn skip_digits(&mut self) {
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect_digits(&mut self, vm: &Vm) -> Result<()> {
+        match self.peek() {
+            Some(b'0'..=b'9') => {
+                self.skip_digits();
+                Ok(())
+            },
+            _ => Err(self.unexpected(vm))
+        }
+    }
+
+    fn parse_literal(&mut self, vm: &Vm, literal: &str, value: Value) -> Result<Value> {
+        if self.src[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            Ok(value)
+        } else {
+            Err(self.unexpected(vm))
+        }
+    }
+}
+
+/// Writes values as JSON.
+struct Writer<'a> {
+    vm: &'a Vm,
+    pretty: bool,
+    out: String,
+    /// The lists, objects and maps containing the value currently being written,
+    /// used to detect values which contain themselves.
+    ancestors: Vec<HeapAddress>,
+}
+
+impl Writer<'_> {
+    fn push(&mut self, str: &str) -> Result<()> {
+        self.vm.consume_fuel(str.len())?;
+        self.out.push_str(str);
+        Ok(())
+    }
+
+    /// Starts a new line indented to a depth when pretty-printing.
+    fn newline(&mut self, depth: usize) -> Result<()> {
+        if self.pretty {
+            let indent = "  ".repeat(depth);
+            self.push("\n")?;
+            self.push(&indent)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_value(&mut self, val: Value) -> Result<()> {
+        let vm = self.vm;
+
+        vm.consume_fuel(1)?;
+
+        let val = vm.unbox(val)?;
+
+        if let Some(str) = vm.try_get_string(val)? {
+            return self.write_string(str);
+        }
+
+        match val {
+            // JSON has no way to represent NaN or infinity.
+            Value::Number(x) if !x.is_finite() => self.push("null"),
+            Value::Number(x) => self.push(&x.to_string()),
+            Value::Integer(x) => self.push(&x.to_string()),
+            Value::Bool(x) => self.push(if x { "true" } else { "false" }),
+            Value::Nil => self.push("null"),
+
+            Value::Object(address) => {
+                if self.ancestors.contains(&address) {
+                    return Err(vm.exception(Exception::CyclicJson));
+                }
+                if self.ancestors.len() >= MAX_NESTING_DEPTH {
+                    return Err(vm.exception(Exception::TooDeeplyNested));
+                }
+
+                self.ancestors.push(address);
+                let result = self.write_heap_value(val, address);
+                self.ancestors.pop();
+
+                result
+            },
+
+            Value::Function(_) | Value::InternedString(_) => Err(self.non_json_value(val)),
+        }
+    }
+
+    fn write_heap_value(&mut self, val: Value, address: HeapAddress) -> Result<()> {
+        let vm = self.vm;
+
+        match vm.get_heap_value(address)? {
+            HeapValue::List(List(list)) => {
+                self.push("[")?;
+
+                for (i, element) in list.iter().enumerate() {
+                    if i > 0 {
+                        self.push(",")?;
+                    }
+                    self.newline(self.ancestors.len())?;
+                    self.write_value(*element)?;
+                }
+
+                self.close(list.is_empty(), "]")
+            },
+
+            HeapValue::Object(object) => {
+                let fields = object.fields(vm.shapes());
+
+                self.push("{")?;
+
+                for (i, (name, field)) in fields.iter().enumerate() {
+                    self.write_member(i, name, field.val)?;
+                }
+
+                self.close(fields.is_empty(), "}")
+            },
+
+            HeapValue::Map(map) => {
+                let entries = map.ordered();
+
+                self.push("{")?;
+
+                // Keys which aren't strings are written as their string representation.
+                for (i, entry) in entries.iter().enumerate() {
+                    let key = vm.to_str(entry.key)?;
+                    self.write_member(i, &key, entry.val)?;
+                }
+
+                self.close(entries.is_empty(), "}")
+            },
+
+            _ => Err(self.non_json_value(val))
+        }
+    }
+
+    fn write_member(&mut self, index: usize, name: &str, val: Value) -> Result<()> {
+        if index > 0 {
+            self.push(",")?;
+        }
+        self.newline(self.ancestors.len())?;
+        self.write_string(name)?;
+        self.push(if self.pretty { ": " } else { ":" })?;
+        self.write_value(val)
+    }
+
+    /// Closes an array or object, putting the closing bracket on its own line unless it's empty.
+    fn close(&mut self, empty: bool, bracket: &str) -> Result<()> {
+        if !empty {
+            self.newline(self.ancestors.len() - 1)?;
+        }
+
+        self.push(bracket)
+    }
+
+    fn write_string(&mut self, str: &str) -> Result<()> {
+        self.vm.consume_fuel(str.len() + 2)?;
+
+        self.out.push('"');
+
+        for char in str.chars() {
+            match char {
+                '"' => self.out.push_str("\\\""),
+                '\\' => self.out.push_str("\\\\"),
+                '\n' => self.out.push_str("\\n"),
+                '\r' => self.out.push_str("\\r"),
+                '\t' => self.out.push_str("\\t"),
+                '\u{8}' => self.out.push_str("\\b"),
+                '\u{c}' => self.out.push_str("\\f"),
+                '\0'..='\u{1f}' => self.out.push_str(&format!("\\u{:04x}", char as u32)),
+                _ => self.out.push(char)
+            }
+        }
+
+        self.out.push('"');
+
+        Ok(())
+    }
+
+    fn non_json_value(&self, val: Value) -> FormattedException {
+        match self.vm.get_type(val) {
+            Ok(ty) => self.vm.exception(Exception::NonJsonValue(ty.to_string())),
+            Err(e) => e
+        }
+    }
+}
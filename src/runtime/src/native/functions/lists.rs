@@ -0,0 +1,634 @@
+//! Functions for sorting, searching, constructing and splitting lists.
+//!
+//! Sorting is stable, and orders values by the same total order as [`Vm::compare_total`]
+//! unless a comparison function is given. Searching for values compares them using [`Vm::equal`].
+//!
+//! Functions which return several lists, such as `zip` or `chunk`, build them inside a result list
+//! which is kept alive while the rest are allocated.
+
+use std::cmp::Ordering;
+use std::iter;
+use std::mem;
+
+use crate::exception::Exception;
+use crate::heap::HeapValue;
+use crate::native::Args;
+use crate::vm::{Vm, Result};
+use crate::value::{Closure, List, Map, Value};
+
+/// Sorts values using a stable merge sort with a comparison which can fail.
+///
+/// The comparison isn't required to be consistent, in which case the order of the result is unspecified,
+/// but it still contains every value exactly once.
+fn merge_sort<T: Copy>(values: &mut Vec<T>, mut compare: impl FnMut(T, T) -> Result<Ordering>) -> Result<()> {
+    let len = values.len();
+    let mut buffer = values.clone();
+    let mut width = 1;
+
+    while width < len {
+        for start in (0..len).step_by(2 * width) {
+            let mid = (start + width).min(len);
+            let end = (start + 2 * width).min(len);
+            let (mut i, mut j) = (start, mid);
+
+            for slot in &mut buffer[start..end] {
+                // Only taking from the right run when it's strictly less keeps equal values in order.
+                if j < end && (i >= mid || compare(values[j], values[i])? == Ordering::Less) {
+                    *slot = values[j];
+                    j += 1;
+                } else {
+                    *slot = values[i];
+                    i += 1;
+                }
+            }
+        }
+
+        mem::swap(values, &mut buffer);
+        width *= 2;
+    }
+
+    Ok(())
+}
+
+pub fn sort(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (List(source), _) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [source, ..] => vm.coerce_to_list(source)?
+    };
+
+    let mut result = source.clone();
+    merge_sort(&mut result, |a, b| vm.compare_total(a, b))?;
+
+    vm.alloc_list(result)
+}
+
+pub fn sort_by(vm: &mut Vm, args: Args) -> Result<Value> {
+    let ((List(source), _), key) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [source, key, ..] => (
+            vm.coerce_to_list(source)?,
+            vm.coerce_to_function(key)?
+        )
+    };
+
+    let elements = source.clone();
+
+    // The key function can modify the source list, so the result is allocated and kept alive up front,
+    // and the keys are collected into a list which is kept alive as well.
+    let result = vm.alloc_list(elements.clone())?;
+
+    vm.with_rooted(result, |vm| {
+        let keys = vm.alloc_list(iter::empty())?;
+        vm.keep_alive(keys)?;
+
+        for &x in &elements {
+            let key = vm.call_run(key, &[x])?;
+
+            let (List(keys), _) = vm.coerce_to_list_mut(keys)?;
+            keys.push(key);
+        }
+
+        let (List(keys), _) = vm.coerce_to_list(keys)?;
+        let keys = keys.clone();
+
+        let mut order = (0..elements.len()).collect::<Vec<_>>();
+        merge_sort(&mut order, |a, b| vm.compare_total(keys[a], keys[b]))?;
+
+        let (List(result), _) = vm.coerce_to_list_mut(result)?;
+        *result = order.into_iter()
+            .map(|i| elements[i])
+            .collect();
+
+        Ok(())
+    })?;
+
+    Ok(result)
+}
+
+pub fn sort_with(vm: &mut Vm, args: Args) -> Result<Value> {
+    let ((List(source), _), compare) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [source, compare, ..] => (
+            vm.coerce_to_list(source)?,
+            vm.coerce_to_function(compare)?
+        )
+    };
+
+    let mut elements = source.clone();
+
+    // The comparison function can modify the source list, so the result is allocated and kept alive up front.
+    let result = vm.alloc_list(elements.clone())?;
+
+    vm.with_rooted(result, |vm| {
+        merge_sort(&mut elements, |a, b| {
+            let ordering = vm.call_run(compare, &[a, b])?;
+            let ordering = vm.unbox(ordering)?;
+
+            // NaN is treated as equal, the same as zero.
+            Ok(vm.compare(ordering, Value::Integer(0))?.unwrap_or(Ordering::Equal))
+        })?;
+
+        let (List(result), _) = vm.coerce_to_list_mut(result)?;
+        *result = elements;
+
+        Ok(())
+    })?;
+
+    Ok(result)
+}
+
+pub fn binary_search(vm: &mut Vm, args: Args) -> Result<Value> {
+    let ((List(list), _), value) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [list, value, ..] => (vm.coerce_to_list(list)?, value)
+    };
+
+    let mut low = 0;
+    let mut high = list.len();
+
+    while low < high {
+        let mid = low + (high - low) / 2;
+
+        match vm.compare_total(list[mid], value)? {
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid,
+            Ordering::Equal => return Ok(mid.into())
+        }
+    }
+
+    // Encode the index the value would have to be inserted at as a negative number.
+    Ok(Value::Integer(-(low as i64) - 1))
+}
+
+pub fn index_of(vm: &mut Vm, args: Args) -> Result<Value> {
+    let ((List(list), _), value, from) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [list, value] => (vm.coerce_to_list(list)?, value, 0),
+
+        [list, value, from, ..] => (
+            vm.coerce_to_list(list)?,
+            value,
+            vm.integer_argument(from, "indexOf", "from")?
+        )
+    };
+
+    for (i, &x) in list.iter().enumerate().skip(from.max(0) as usize) {
+        if vm.equal(x, value)? {
+            return Ok(i.into());
+        }
+    }
+
+    Ok(Value::Integer(-1))
+}
+
+pub fn contains(vm: &mut Vm, args: Args) -> Result<Value> {
+    let ((List(list), _), value) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [list, value, ..] => (vm.coerce_to_list(list)?, value)
+    };
+
+    for &x in list {
+        if vm.equal(x, value)? {
+            return Ok(true.into());
+        }
+    }
+
+    Ok(false.into())
+}
+
+pub fn distinct(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (List(source), _) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [source, ..] => vm.coerce_to_list(source)?
+    };
+
+    let mut result = Vec::new();
+
+    'outer: for &x in source {
+        for &y in &result {
+            if vm.equal(x, y)? {
+                continue 'outer;
+            }
+        }
+
+        result.push(x);
+    }
+
+    vm.alloc_list(result)
+}
+
+/// Allocates a vector with space for a number of elements,
+/// throwing [`Exception::OutOfMemory`] instead of aborting if it's too large.
+fn list_with_capacity(vm: &Vm, capacity: usize) -> Result<Vec<Value>> {
+    vm.consume_fuel(capacity)?;
+
+    let mut list = Vec::new();
+    list.try_reserve_exact(capacity)
+        .map_err(|_| vm.exception(Exception::OutOfMemory))?;
+
+    Ok(list)
+}
+
+/// Calls a function with a copy of the elements of a list.
+///
+/// The copy is kept alive by a new list until the function returns,
+/// since callbacks can modify the source list and would otherwise be able to free its elements.
+fn with_elements<T>(vm: &mut Vm, source: Value, f: impl FnOnce(&mut Vm, Vec<Value>) -> Result<T>) -> Result<T> {
+    let (List(elements), _) = vm.coerce_to_list(source)?;
+    let elements = elements.clone();
+
+    let copy = vm.alloc_list(elements.clone())?;
+
+    vm.with_rooted(copy, |vm| f(vm, elements))
+}
+
+/// Calls a predicate with every element of a list and its index,
+/// stopping at the first element for which the predicate returns `stop_at`.
+///
+/// Returns the results of the predicate which were computed.
+fn test_elements(vm: &mut Vm, elements: &[Value], predicate: Closure, stop_at: Option<bool>) -> Result<Vec<bool>> {
+    let mut results = Vec::new();
+
+    for (i, &x) in elements.iter().enumerate() {
+        let result = vm.call_run(predicate, &[x, i.into()])?;
+        let result = vm.coerce_to_bool(result)?;
+
+        results.push(result);
+
+        if Some(result) == stop_at {
+            break;
+        }
+    }
+
+    Ok(results)
+}
+
+/// Allocates a list of lists, each of which is created from a slice of the elements of a list.
+fn alloc_list_of_lists<'a>(vm: &mut Vm, lists: impl IntoIterator<Item = &'a [Value]>) -> Result<Value> {
+    let result = vm.alloc_list(iter::empty())?;
+
+    vm.with_rooted(result, |vm| {
+        for list in lists {
+            let list = vm.alloc_list(list.iter().copied())?;
+
+            let (List(result), _) = vm.coerce_to_list_mut(result)?;
+            result.push(list);
+        }
+
+        Ok(())
+    })?;
+
+    Ok(result)
+}
+
+/// Gets a list argument along with a non-negative count.
+fn list_and_count(vm: &Vm, args: &Args, function: &str) -> Result<(Vec<Value>, usize)> {
+    match args[..] {
+        [] | [_] => Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [source, count, ..] => {
+            let count = vm.integer_argument(count, function, "count")?;
+            let (List(source), _) = vm.coerce_to_list(source)?;
+
+            Ok((source.clone(), count.max(0) as usize))
+        }
+    }
+}
+
+/// Gets a list argument along with a size which has to be at least 1.
+fn list_and_size(vm: &Vm, args: &Args, function: &str) -> Result<(Vec<Value>, usize)> {
+    match args[..] {
+        [] | [_] => Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [source, size, ..] => {
+            let size = vm.integer_argument(size, function, "size")?;
+            if size < 1 {
+                return Err(vm.exception(
+                    Exception::Custom(format!("expected `size` to be at least 1 in `{function}` but it was {size}"))
+                ));
+            }
+
+            let (List(source), _) = vm.coerce_to_list(source)?;
+
+            Ok((source.clone(), size as usize))
+        }
+    }
+}
+
+pub fn range(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (start, end, step) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [start, end] => (
+            vm.integer_argument(start, "range", "start")?,
+            vm.integer_argument(end, "range", "end")?,
+            1
+        ),
+
+        [start, end, step, ..] => (
+            vm.integer_argument(start, "range", "start")?,
+            vm.integer_argument(end, "range", "end")?,
+            vm.integer_argument(step, "range", "step")?
+        )
+    };
+
+    if step == 0 {
+        return Err(vm.exception(
+            Exception::Custom(String::from("expected `step` to not be 0 in `range`"))
+        ));
+    }
+
+    // Computed using 128-bit integers since the distance between the start and end can be larger than an integer.
+    let (start, end, step) = (start as i128, end as i128, step as i128);
+    let distance = if step > 0 { end - start } else { start - end };
+    let len = if distance > 0 { (distance - 1) / step.abs() + 1 } else { 0 };
+
+    let len = usize::try_from(len)
+        .map_err(|_| vm.exception(Exception::OutOfMemory))?;
+
+    let mut result = list_with_capacity(vm, len)?;
+    result.extend((0..len).map(|i| Value::Integer((start + i as i128 * step) as i64)));
+
+    vm.alloc_list(result)
+}
+
+pub fn replicate(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (val, count) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [val, count, ..] => (val, vm.integer_argument(count, "replicate", "count")?)
+    };
+
+    let count = count.max(0) as usize;
+
+    let mut result = list_with_capacity(vm, count)?;
+    result.extend(iter::repeat_n(val, count));
+
+    vm.alloc_list(result)
+}
+
+pub fn zip(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (a, b) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [a, b, ..] => (
+            vm.coerce_to_list(a)?.0.0.clone(),
+            vm.coerce_to_list(b)?.0.0.clone()
+        )
+    };
+
+    let pairs = a.into_iter()
+        .zip(b)
+        .map(|(a, b)| [a, b])
+        .collect::<Vec<_>>();
+
+    alloc_list_of_lists(vm, pairs.iter().map(|pair| &pair[..]))
+}
+
+pub fn enumerate(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (List(source), _) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [source, ..] => vm.coerce_to_list(source)?
+    };
+
+    let pairs = source.iter()
+        .enumerate()
+        .map(|(i, &x)| [i.into(), x])
+        .collect::<Vec<_>>();
+
+    alloc_list_of_lists(vm, pairs.iter().map(|pair| &pair[..]))
+}
+
+pub fn chunk(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (source, size) = list_and_size(vm, &args, "chunk")?;
+
+    alloc_list_of_lists(vm, source.chunks(size))
+}
+
+pub fn window(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (source, size) = list_and_size(vm, &args, "window")?;
+
+    alloc_list_of_lists(vm, source.windows(size))
+}
+
+pub fn take(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (source, count) = list_and_count(vm, &args, "take")?;
+
+    vm.alloc_list(source.into_iter().take(count))
+}
+
+pub fn skip(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (source, count) = list_and_count(vm, &args, "skip")?;
+
+    vm.alloc_list(source.into_iter().skip(count))
+}
+
+pub fn take_while(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (source, predicate) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [source, predicate, ..] => (source, vm.coerce_to_function(predicate)?)
+    };
+
+    with_elements(vm, source, |vm, elements| {
+        let taken = test_elements(vm, &elements, predicate, Some(false))?
+            .into_iter()
+            .take_while(|&x| x)
+            .count();
+
+        vm.alloc_list(elements.into_iter().take(taken))
+    })
+}
+
+pub fn skip_while(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (source, predicate) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [source, predicate, ..] => (source, vm.coerce_to_function(predicate)?)
+    };
+
+    with_elements(vm, source, |vm, elements| {
+        let skipped = test_elements(vm, &elements, predicate, Some(false))?
+            .into_iter()
+            .take_while(|&x| x)
+            .count();
+
+        vm.alloc_list(elements.into_iter().skip(skipped))
+    })
+}
+
+pub fn group_by(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (source, key) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [source, key, ..] => (source, vm.coerce_to_function(key)?)
+    };
+
+    with_elements(vm, source, |vm, elements| {
+        let groups = vm.heap_alloc(HeapValue::Map(Map::default()))
+            .map(Value::Object)?;
+        vm.keep_alive(groups)?;
+
+        for x in elements {
+            let key_val = vm.call_run(key, &[x])?;
+            let key = vm.to_map_key(key_val)?;
+
+            let (groups_map, _) = vm.coerce_to_map(groups)?;
+
+            match groups_map.get(&key) {
+                Some(entry) => {
+                    let group = entry.val;
+                    let (List(group), _) = vm.coerce_to_list_mut(group)?;
+                    group.push(x);
+                },
+                None => {
+                    // The key isn't reachable until it's inserted into the map.
+                    let group = vm.with_rooted(key_val, |vm| vm.alloc_list([x]))?;
+
+                    let (groups, _) = vm.coerce_to_map_mut(groups)?;
+                    groups.insert(key, key_val, group);
+                }
+            }
+        }
+
+        Ok(groups)
+    })
+}
+
+pub fn partition(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (source, predicate) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [source, predicate, ..] => (source, vm.coerce_to_function(predicate)?)
+    };
+
+    with_elements(vm, source, |vm, elements| {
+        let results = test_elements(vm, &elements, predicate, None)?;
+
+        let (matching, rest): (Vec<_>, Vec<_>) = elements.into_iter()
+            .zip(results)
+            .partition(|&(_, result)| result);
+
+        let matching = matching.into_iter().map(|(x, _)| x).collect::<Vec<_>>();
+        let rest = rest.into_iter().map(|(x, _)| x).collect::<Vec<_>>();
+
+        alloc_list_of_lists(vm, [&matching[..], &rest[..]])
+    })
+}
+
+pub fn sum(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (List(source), _) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [source, ..] => vm.coerce_to_list(source)?
+    };
+
+    // The sum stays an integer for as long as every element is an integer.
+    let mut sum = Value::Integer(0);
+    for &x in source {
+        sum = vm.arithmetic(sum, vm.unbox(x)?, i64::checked_add, |a, b| a + b)?;
+    }
+
+    Ok(sum)
+}
+
+pub fn count(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (source, predicate) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [source, predicate, ..] => (source, vm.coerce_to_function(predicate)?)
+    };
+
+    with_elements(vm, source, |vm, elements| {
+        let count = test_elements(vm, &elements, predicate, None)?
+            .into_iter()
+            .filter(|&x| x)
+            .count();
+
+        Ok(count.into())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted<T: Copy>(mut values: Vec<T>, compare: impl Fn(T, T) -> Ordering) -> Vec<T> {
+        merge_sort(&mut values, |a, b| Ok(compare(a, b))).unwrap();
+        values
+    }
+
+    #[test]
+    fn merge_sort_sorts_runs_of_every_length() {
+        for len in 0..20 {
+            let values = (0..len).rev().collect::<Vec<i32>>();
+            let expected = (0..len).collect::<Vec<i32>>();
+
+            assert_eq!(sorted(values, |a, b| a.cmp(&b)), expected);
+        }
+    }
+
+    #[test]
+    fn merge_sort_keeps_equal_values_in_order() {
+        let values = vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (0, 'e')];
+
+        assert_eq!(
+            sorted(values, |a, b| a.0.cmp(&b.0)),
+            [(0, 'e'), (1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]
+        );
+    }
+
+    #[test]
+    fn merge_sort_with_an_inconsistent_comparison_keeps_every_value() {
+        let mut values = sorted((0..10).collect::<Vec<i32>>(), |_, _| Ordering::Less);
+        values.sort();
+
+        assert_eq!(values, (0..10).collect::<Vec<_>>());
+    }
+}
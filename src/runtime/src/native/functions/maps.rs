@@ -0,0 +1,177 @@
+use crate::exception::Exception;
+use crate::heap::HeapValue;
+use crate::native::Args;
+use crate::vm::{Vm, Result};
+use crate::value::{List, Map, Value};
+
+pub fn map_new(vm: &mut Vm, args: Args) -> Result<Value> {
+    let mut map = Map::default();
+
+    if let [entries, ..] = args[..] {
+        let (List(entries), _) = vm.coerce_to_list(entries)?;
+
+        for entry in entries.clone() {
+            let (List(pair), _) = vm.coerce_to_list(entry)?;
+
+            let (key, val) = match pair[..] {
+                [key, val] => (key, val),
+                _ => return Err(vm.exception(
+                    Exception::Custom(format!("expected map entry to be a list of 2 elements but it had {}", pair.len()))
+                ))
+            };
+
+            let map_key = vm.to_map_key(key)?;
+            map.insert(map_key, key, val);
+        }
+    }
+
+    vm.heap_alloc(HeapValue::Map(map))
+        .map(Value::Object)
+}
+
+pub fn map_get(vm: &mut Vm, args: Args) -> Result<Value> {
+    let ((map, _), key, default) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [map, key] => (
+            vm.coerce_to_map(map)?,
+            vm.find_map_key(key)?,
+            ().into()
+        ),
+
+        [map, key, default, ..] => (
+            vm.coerce_to_map(map)?,
+            vm.find_map_key(key)?,
+            default
+        )
+    };
+
+    let val = match key.and_then(|key| map.get(&key)) {
+        Some(entry) => entry.val,
+        None => default
+    };
+
+    Ok(val)
+}
+
+pub fn map_set(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (key_val, key, val, map) = match args[..] {
+        [] | [_] | [_, _] => return Err(vm.exception(
+            Exception::BadArity { expected: 3, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [map, key, val, ..] => (key, vm.to_map_key(key)?, val, map)
+    };
+
+    let (map, _) = vm.coerce_to_map_mut(map)?;
+
+    map.insert(key, key_val, val);
+
+    Ok(().into())
+}
+
+pub fn map_has(vm: &mut Vm, args: Args) -> Result<Value> {
+    let ((map, _), key) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [map, key, ..] => (
+            vm.coerce_to_map(map)?,
+            vm.find_map_key(key)?
+        )
+    };
+
+    Ok(key.and_then(|key| map.get(&key)).is_some().into())
+}
+
+pub fn map_remove(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (key, map) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [map, key, ..] => (vm.find_map_key(key)?, map)
+    };
+
+    let (map, _) = vm.coerce_to_map_mut(map)?;
+
+    let val = match key.and_then(|key| map.remove(&key)) {
+        Some(entry) => entry.val,
+        None => ().into()
+    };
+
+    Ok(val)
+}
+
+pub fn map_keys(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (map, _) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [map, ..] => vm.coerce_to_map(map)?
+    };
+
+    let keys = map.ordered()
+        .into_iter()
+        .map(|entry| entry.key)
+        .collect::<Vec<_>>();
+
+    vm.alloc_list(keys)
+}
+
+pub fn map_values(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (map, _) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [map, ..] => vm.coerce_to_map(map)?
+    };
+
+    let values = map.ordered()
+        .into_iter()
+        .map(|entry| entry.val)
+        .collect::<Vec<_>>();
+
+    vm.alloc_list(values)
+}
+
+pub fn map_entries(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (map, _) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [map, ..] => vm.coerce_to_map(map)?
+    };
+
+    let pairs = map.ordered()
+        .into_iter()
+        .map(|entry| [entry.key, entry.val])
+        .collect::<Vec<_>>();
+
+    let mut entries = Vec::with_capacity(pairs.len());
+    for pair in pairs {
+        let entry = vm.alloc_list(pair)?;
+        vm.keep_alive(entry)?;
+        entries.push(entry);
+    }
+
+    vm.alloc_list(entries)
+}
+
+pub fn map_size(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (map, _) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [map, ..] => vm.coerce_to_map(map)?
+    };
+
+    Ok(map.entries.len().into())
+}
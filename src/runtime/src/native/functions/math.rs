@@ -0,0 +1,271 @@
+//! Math functions.
+//!
+//! Arguments are coerced into numbers the same way they are by arithmetic operators.
+//! Functions which don't leave the integers, such as `abs` or `min`, return integers when passed integers,
+//! while every other function always returns a number. `min` and `max` also accept strings.
+
+use std::cmp::Ordering;
+use std::f64::consts;
+
+use crate::exception::Exception;
+use crate::native::Args;
+use crate::vm::{Vm, Result};
+use crate::value::{List, Type, Value};
+
+/// Gets the first argument of a function which takes a single argument.
+fn single_argument(vm: &Vm, args: &Args) -> Result<Value> {
+    match args[..] {
+        [] => Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [val, ..] => vm.unbox(val)
+    }
+}
+
+/// Normalizes a value into either an integer or a number.
+fn to_numeric(vm: &Vm, val: Value) -> Result<Value> {
+    match vm.unbox(val)? {
+        Value::Integer(x) => Ok(Value::Integer(x)),
+        val => Ok(Value::Number(vm.coerce_to_number(val)?))
+    }
+}
+
+/// Applies a function which maps integers to integers and numbers to numbers.
+fn integer_preserving(
+    vm: &Vm,
+    args: Args,
+    int_op: impl FnOnce(i64) -> Option<i64>,
+    float_op: impl FnOnce(f64) -> f64
+) -> Result<Value> {
+    match single_argument(vm, &args)? {
+        Value::Integer(x) => int_op(x)
+            .map(Value::Integer)
+            .ok_or_else(|| vm.exception(Exception::IntegerOverflow)),
+
+        val => Ok(Value::Number(float_op(vm.coerce_to_number(val)?)))
+    }
+}
+
+/// Applies a function which always returns a number.
+fn float(vm: &Vm, args: Args, op: impl FnOnce(f64) -> f64) -> Result<Value> {
+    let x = single_argument(vm, &args)?;
+    let x = vm.coerce_to_number(x)?;
+
+    Ok(Value::Number(op(x)))
+}
+
+pub fn abs(vm: &mut Vm, args: Args) -> Result<Value> {
+    integer_preserving(vm, args, i64::checked_abs, f64::abs)
+}
+
+pub fn floor(vm: &mut Vm, args: Args) -> Result<Value> {
+    integer_preserving(vm, args, Some, f64::floor)
+}
+
+pub fn ceil(vm: &mut Vm, args: Args) -> Result<Value> {
+    integer_preserving(vm, args, Some, f64::ceil)
+}
+
+pub fn round(vm: &mut Vm, args: Args) -> Result<Value> {
+    integer_preserving(vm, args, Some, f64::round)
+}
+
+pub fn trunc(vm: &mut Vm, args: Args) -> Result<Value> {
+    integer_preserving(vm, args, Some, f64::trunc)
+}
+
+pub fn sign(vm: &mut Vm, args: Args) -> Result<Value> {
+    // `f64::signum` returns 1 for positive zero, but the sign of zero should be zero.
+    integer_preserving(vm, args, |x| Some(x.signum()), |x| {
+        if x == 0. || x.is_nan() { x } else { x.signum() }
+    })
+}
+
+pub fn sqrt(vm: &mut Vm, args: Args) -> Result<Value> {
+    float(vm, args, f64::sqrt)
+}
+
+pub fn pow(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (base, exp) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [base, exp, ..] => (vm.unbox(base)?, vm.unbox(exp)?)
+    };
+
+    vm.pow(base, exp)
+}
+
+pub fn exp(vm: &mut Vm, args: Args) -> Result<Value> {
+    float(vm, args, f64::exp)
+}
+
+pub fn ln(vm: &mut Vm, args: Args) -> Result<Value> {
+    float(vm, args, f64::ln)
+}
+
+pub fn log(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (x, base) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [x] => (vm.unbox(x)?, None),
+
+        [x, base, ..] => (vm.unbox(x)?, Some(vm.unbox(base)?))
+    };
+
+    let x = vm.coerce_to_number(x)?;
+
+    let result = match base {
+        Some(base) => x.log(vm.coerce_to_number(base)?),
+        None => x.log10()
+    };
+
+    Ok(Value::Number(result))
+}
+
+pub fn sin(vm: &mut Vm, args: Args) -> Result<Value> {
+    float(vm, args, f64::sin)
+}
+
+pub fn cos(vm: &mut Vm, args: Args) -> Result<Value> {
+    float(vm, args, f64::cos)
+}
+
+pub fn tan(vm: &mut Vm, args: Args) -> Result<Value> {
+    float(vm, args, f64::tan)
+}
+
+pub fn asin(vm: &mut Vm, args: Args) -> Result<Value> {
+    float(vm, args, f64::asin)
+}
+
+pub fn acos(vm: &mut Vm, args: Args) -> Result<Value> {
+    float(vm, args, f64::acos)
+}
+
+pub fn atan(vm: &mut Vm, args: Args) -> Result<Value> {
+    float(vm, args, f64::atan)
+}
+
+pub fn atan2(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (y, x) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [y, x, ..] => (
+            vm.coerce_to_number(vm.unbox(y)?)?,
+            vm.coerce_to_number(vm.unbox(x)?)?
+        )
+    };
+
+    Ok(Value::Number(y.atan2(x)))
+}
+
+pub fn min(vm: &mut Vm, args: Args) -> Result<Value> {
+    extremum(vm, args, Ordering::Less)
+}
+
+pub fn max(vm: &mut Vm, args: Args) -> Result<Value> {
+    extremum(vm, args, Ordering::Greater)
+}
+
+/// Finds the smallest or largest of either the arguments or the elements of a single list argument.
+/// Returns `()` if there are no values.
+///
+/// Values are ordered the same way as when sorting, which makes NaN greater than every other number
+/// and strings greater than every number.
+fn extremum(vm: &mut Vm, args: Args, ordering: Ordering) -> Result<Value> {
+    let values = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [val] if vm.get_type(val)? == Type::List => {
+            let (List(list), _) = vm.coerce_to_list(val)?;
+            list.clone()
+        },
+
+        _ => args.to_vec()
+    };
+
+    let mut result = None;
+    for x in values {
+        let x = vm.unbox(x)?;
+        let x = match vm.try_get_string(x)? {
+            Some(_) => x,
+            None => to_numeric(vm, x)?
+        };
+
+        result = match result {
+            Some(current) if vm.compare_total(x, current)? != ordering => Some(current),
+            _ => Some(x)
+        };
+    }
+
+    Ok(result.unwrap_or(().into()))
+}
+
+pub fn clamp(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (x, min, max) = match args[..] {
+        [] | [_] | [_, _] => return Err(vm.exception(
+            Exception::BadArity { expected: 3, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [x, min, max, ..] => (
+            to_numeric(vm, x)?,
+            to_numeric(vm, min)?,
+            to_numeric(vm, max)?
+        )
+    };
+
+    match vm.compare(min, max)? {
+        Some(Ordering::Less | Ordering::Equal) => {},
+        _ => return Err(vm.exception(
+            Exception::Custom(String::from("expected `min` to be less than or equal to `max` in `clamp`"))
+        ))
+    }
+
+    let result = match vm.compare(x, min)? {
+        None => Value::Number(f64::NAN),
+        Some(Ordering::Less) => min,
+        Some(_) if vm.compare(x, max)? == Some(Ordering::Greater) => max,
+        Some(_) => x
+    };
+
+    Ok(result)
+}
+
+pub fn is_nan(vm: &mut Vm, args: Args) -> Result<Value> {
+    match single_argument(vm, &args)? {
+        Value::Integer(_) => Ok(false.into()),
+        val => Ok(vm.coerce_to_number(val)?.is_nan().into())
+    }
+}
+
+pub fn is_finite(vm: &mut Vm, args: Args) -> Result<Value> {
+    match single_argument(vm, &args)? {
+        Value::Integer(_) => Ok(true.into()),
+        val => Ok(vm.coerce_to_number(val)?.is_finite().into())
+    }
+}
+
+pub fn pi(_: &mut Vm, _: Args) -> Result<Value> {
+    Ok(Value::Number(consts::PI))
+}
+
+pub fn e(_: &mut Vm, _: Args) -> Result<Value> {
+    Ok(Value::Number(consts::E))
+}
+
+pub fn infinity(_: &mut Vm, _: Args) -> Result<Value> {
+    Ok(Value::Number(f64::INFINITY))
+}
+
+pub fn nan(_: &mut Vm, _: Args) -> Result<Value> {
+    Ok(Value::Number(f64::NAN))
+}
@@ -0,0 +1,258 @@
+use crate::exception::Exception;
+use crate::native::Args;
+use crate::vm::{Vm, Result};
+use crate::value::Value;
+
+pub fn to_integer(vm: &mut Vm, args: Args) -> Result<Value> {
+    let val = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [val, ..] => val
+    };
+
+    if let Value::Integer(x) = val {
+        return Ok(Value::Integer(x));
+    }
+
+    let x = vm.coerce_to_number(val)?;
+
+    if x.is_nan() {
+        return Err(vm.exception(
+            Exception::Custom(String::from("NaN cannot be converted into an integer"))
+        ));
+    }
+
+    // Truncate towards zero, the same way list indices are.
+    let x = x.trunc();
+
+    // -2^63 is exactly representable as a float while 2^63 - 1 is not,
+    // so the upper bound has to be exclusive.
+    if x < i64::MIN as f64 || x >= -(i64::MIN as f64) {
+        return Err(vm.exception(Exception::IntegerOverflow));
+    }
+
+    Ok(Value::Integer(x as i64))
+}
+
+pub fn to_float(vm: &mut Vm, args: Args) -> Result<Value> {
+    let x = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [val, ..] => vm.coerce_to_number(val)?
+    };
+
+    Ok(Value::Number(x))
+}
+
+pub fn is_integer(vm: &mut Vm, args: Args) -> Result<Value> {
+    let val = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [val, ..] => vm.unbox(val)?
+    };
+
+    Ok(matches!(val, Value::Integer(_)).into())
+}
+
+/// The largest amount of decimals `formatNumber` can format a number with.
+const MAX_DECIMALS: i64 = 100;
+
+pub fn parse_number(vm: &mut Vm, args: Args) -> Result<Value> {
+    let str = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, ..] => vm.string_argument(str, "parseNumber", "string")?
+    };
+
+    match parse_decimal(str) {
+        Some(x) => Ok(Value::Number(x)),
+        None => Ok(().into())
+    }
+}
+
+/// Parses a string containing a decimal number, optionally with an exponent.
+fn parse_decimal(str: &str) -> Option<f64> {
+    // Input read from the console usually ends with a newline, so surrounding whitespace is ignored.
+    let str = str.trim();
+
+    // Rust also parses `inf`, `infinity` and `nan`, which aren't decimal numbers.
+    if !str.chars().all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E')) {
+        return None;
+    }
+
+    str.parse().ok()
+}
+
+pub fn parse_int(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (str, radix) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [str] => (vm.string_argument(str, "parseInt", "string")?, 10),
+
+        [str, radix, ..] => (
+            vm.string_argument(str, "parseInt", "string")?,
+            radix_argument(vm, radix, "parseInt")?
+        )
+    };
+
+    match i64::from_str_radix(str.trim(), radix) {
+        Ok(x) => Ok(Value::Integer(x)),
+        Err(_) => Ok(().into())
+    }
+}
+
+pub fn format_number(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (x, options) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [x] => (vm.unbox(x)?, None),
+
+        [x, options, ..] => match vm.unbox(options)? {
+            Value::Nil => (vm.unbox(x)?, None),
+            options => (vm.unbox(x)?, Some(options))
+        }
+    };
+
+    let x = match x {
+        Value::Integer(_) => x,
+        _ => Value::Number(vm.coerce_to_number(x)?)
+    };
+
+    let Some(options) = options else {
+        let str = vm.to_string(x)?;
+        return vm.alloc_string(str);
+    };
+
+    let (decimals, radix, exponent) = {
+        let (object, _) = vm.coerce_to_object(options)?;
+        let shapes = vm.shapes();
+        let option = |name| object.get(shapes, name)
+            .map(|field| field.val)
+            .filter(|val| *val != Value::Nil);
+
+        (option("decimals"), option("radix"), option("exponent"))
+    };
+
+    let decimals = match decimals {
+        Some(decimals) => match vm.integer_argument(decimals, "formatNumber", "decimals")? {
+            decimals @ 0..=MAX_DECIMALS => Some(decimals as usize),
+            decimals => return Err(vm.exception(Exception::InvalidDecimals(decimals)))
+        },
+        None => None
+    };
+
+    let radix = match radix {
+        Some(radix) => radix_argument(vm, radix, "formatNumber")?,
+        None => 10
+    };
+
+    let exponent = match exponent {
+        Some(exponent) => vm.coerce_to_bool(exponent)?,
+        None => false
+    };
+
+    let str = if radix != 10 {
+        if decimals.is_some() || exponent {
+            return Err(vm.exception(
+                Exception::Custom(String::from("a radix other than 10 cannot be combined with `decimals` or `exponent` in `formatNumber`"))
+            ));
+        }
+
+        format_radix(vm.coerce_to_integer(x)?, radix)
+    } else {
+        match (x, decimals, exponent) {
+            (x, Some(decimals), true) => format!("{:.*e}", decimals, vm.coerce_to_number(x)?),
+            (x, None, true) => format!("{:e}", vm.coerce_to_number(x)?),
+            // Integers are formatted exactly instead of going through a float,
+            // which would round integers with an absolute value greater than 2^53.
+            (Value::Integer(x), Some(decimals), false) if decimals > 0 => format!("{x}.{}", "0".repeat(decimals)),
+            (x, Some(decimals), false) => format!("{:.*}", decimals, vm.coerce_to_number(x)?),
+            (x, None, false) => vm.to_string(x)?,
+        }
+    };
+
+    vm.alloc_string(str)
+}
+
+/// Gets a radix passed as an argument to a native function.
+fn radix_argument(vm: &Vm, val: Value, function: &str) -> Result<u32> {
+    match vm.integer_argument(val, function, "radix")? {
+        radix @ 2..=36 => Ok(radix as u32),
+        radix => Err(vm.exception(Exception::InvalidRadix(radix)))
+    }
+}
+
+/// Formats an integer in a radix between 2 and 36 using lowercase letters for digits above 9.
+fn format_radix(x: i64, radix: u32) -> String {
+    let mut magnitude = x.unsigned_abs();
+    let mut digits = Vec::new();
+
+    loop {
+        let digit = (magnitude % radix as u64) as u32;
+        digits.push(char::from_digit(digit, radix).expect("digit should be less than the radix"));
+        magnitude /= radix as u64;
+
+        if magnitude == 0 {
+            break;
+        }
+    }
+
+    if x < 0 {
+        digits.push('-');
+    }
+
+    digits.iter().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_decimal_parses_decimal_numbers() {
+        assert_eq!(parse_decimal("-1.5e3"), Some(-1500.0));
+        assert_eq!(parse_decimal("+2.25"), Some(2.25));
+        assert_eq!(parse_decimal(".5"), Some(0.5));
+        assert_eq!(parse_decimal("1E-2"), Some(0.01));
+        assert_eq!(parse_decimal("  42\n"), Some(42.0));
+    }
+
+    #[test]
+    fn parse_decimal_rejects_infinity_and_nan() {
+        for str in ["inf", "-inf", "+Infinity", "INFINITY", "nan", "NaN", "-NAN"] {
+            assert_eq!(parse_decimal(str), None, "{str} should not be parsed");
+        }
+    }
+
+    #[test]
+    fn parse_decimal_rejects_non_numbers() {
+        for str in ["", "e", "1.2.3", "0x10", "1_000", "12abc"] {
+            assert_eq!(parse_decimal(str), None, "{str} should not be parsed");
+        }
+    }
+
+    #[test]
+    fn format_radix_formats_digits_above_9_as_letters() {
+        assert_eq!(format_radix(255, 16), "ff");
+        assert_eq!(format_radix(35, 36), "z");
+        assert_eq!(format_radix(0, 2), "0");
+    }
+
+    #[test]
+    fn format_radix_formats_negative_numbers_with_a_sign() {
+        assert_eq!(format_radix(-10, 2), "-1010");
+        assert_eq!(format_radix(i64::MIN, 16), "-8000000000000000");
+    }
+}
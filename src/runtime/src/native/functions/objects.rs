@@ -0,0 +1,187 @@
+//! Object reflection functions.
+//!
+//! Fields are always listed in the order of their indices, which is the order they were added in.
+
+use std::iter;
+
+use crate::exception::Exception;
+use crate::native::Args;
+use crate::vm::{Vm, Result};
+use crate::value::{Field, List, Value};
+
+/// Gets the object and field name arguments of a function.
+fn object_and_name(vm: &Vm, args: &Args, function: &str) -> Result<(Value, String)> {
+    match args[..] {
+        [] | [_] => Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [object, name, ..] => {
+            let name = vm.string_argument(name, function, "name")?.to_owned();
+            Ok((object, name))
+        }
+    }
+}
+
+/// Gets the object argument of a function which takes a single object.
+fn object_argument(vm: &Vm, args: &Args) -> Result<Value> {
+    match args[..] {
+        [] => Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [object, ..] => Ok(object)
+    }
+}
+
+/// Gets the fields of an object along with their names, in order.
+fn fields_of(vm: &Vm, object: Value) -> Result<Vec<(String, Field)>> {
+    let (object, _) = vm.coerce_to_object(object)?;
+
+    Ok(object.fields(vm.shapes())
+        .into_iter()
+        .map(|(name, field)| (name.to_owned(), field))
+        .collect())
+}
+
+pub fn keys(vm: &mut Vm, args: Args) -> Result<Value> {
+    let fields = fields_of(vm, object_argument(vm, &args)?)?;
+
+    vm.alloc_string_list(fields.into_iter().map(|(name, _)| name))
+}
+
+pub fn values(vm: &mut Vm, args: Args) -> Result<Value> {
+    let fields = fields_of(vm, object_argument(vm, &args)?)?;
+
+    vm.alloc_list(fields.into_iter().map(|(_, field)| field.val))
+}
+
+pub fn entries(vm: &mut Vm, args: Args) -> Result<Value> {
+    let fields = fields_of(vm, object_argument(vm, &args)?)?;
+
+    let result = vm.alloc_list(iter::empty())?;
+
+    vm.with_rooted(result, |vm| {
+        for (name, field) in fields {
+            let entry = vm.scoped(|vm| {
+                let name = vm.alloc_string(name)?;
+                vm.keep_alive(name)?;
+
+                vm.alloc_list([name, field.val])
+            })?;
+
+            let (List(result), _) = vm.coerce_to_list_mut(result)?;
+            result.push(entry);
+        }
+
+        Ok(())
+    })?;
+
+    Ok(result)
+}
+
+pub fn has_field(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (object, name) = object_and_name(vm, &args, "hasField")?;
+
+    let (object, _) = vm.coerce_to_object(object)?;
+
+    Ok(object.get(vm.shapes(), &name).is_some().into())
+}
+
+pub fn get_field(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (object, name, default) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [object, name] => (object, vm.string_argument(name, "getField", "name")?, ().into()),
+
+        [object, name, default, ..] => (object, vm.string_argument(name, "getField", "name")?, default)
+    };
+
+    let (object, _) = vm.coerce_to_object(object)?;
+
+    let val = match object.get(vm.shapes(), name) {
+        Some(field) => field.val,
+        None => default
+    };
+
+    Ok(val)
+}
+
+pub fn remove_field(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (object, name) = object_and_name(vm, &args, "removeField")?;
+
+    let (object, shapes) = vm.coerce_to_object_with_shapes(object)?;
+
+    let field = match object.get(shapes, &name) {
+        Some(field) => field,
+        None => return Ok(().into())
+    };
+
+    let exception = if !object.dynamic {
+        Exception::RemoveFromNonDynamic(name)
+    } else if !field.mutable {
+        Exception::WriteToImmutableField(name)
+    } else {
+        object.remove_field(shapes, &name);
+        return Ok(field.val);
+    };
+
+    Err(vm.exception(exception))
+}
+
+pub fn is_mutable(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (object, name) = object_and_name(vm, &args, "isMutable")?;
+
+    let (object, _) = vm.coerce_to_object(object)?;
+
+    match object.get(vm.shapes(), &name) {
+        Some(field) => Ok(field.mutable.into()),
+        None => Err(vm.exception(Exception::MissingField(name)))
+    }
+}
+
+pub fn is_dynamic(vm: &mut Vm, args: Args) -> Result<Value> {
+    let object = object_argument(vm, &args)?;
+
+    let (object, _) = vm.coerce_to_object(object)?;
+
+    Ok(object.dynamic.into())
+}
+
+pub fn merge(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (a, b) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [a, b, ..] => (a, b)
+    };
+
+    let dynamic = vm.coerce_to_object(a)?.0.dynamic || vm.coerce_to_object(b)?.0.dynamic;
+
+    // Fields of the second object replace fields with the same name in place, and are otherwise added at the end.
+    let mut fields = fields_of(vm, a)?;
+    for (name, field) in fields_of(vm, b)? {
+        match fields.iter_mut().find(|(existing, _)| *existing == name) {
+            Some((_, existing)) => *existing = field,
+            None => fields.push((name, field))
+        }
+    }
+
+    let fields = fields.into_iter()
+        .enumerate()
+        .map(|(index, (name, field))| (name, Field { index: index as u32, ..field }));
+
+    vm.alloc_object(fields, dynamic)
+}
+
+pub fn freeze(vm: &mut Vm, args: Args) -> Result<Value> {
+    let object = object_argument(vm, &args)?;
+
+    let (target, shapes) = vm.coerce_to_object_with_shapes(object)?;
+    target.freeze(shapes);
+
+    Ok(object)
+}
@@ -0,0 +1,96 @@
+//! Random number functions.
+//!
+//! Every function draws from the same generator stored in the vm,
+//! so seeding it with `setSeed` makes every subsequent value reproducible.
+
+use crate::exception::Exception;
+use crate::native::Args;
+use crate::vm::{Vm, Result};
+use crate::value::{List, Value};
+
+pub fn random(vm: &mut Vm, _: Args) -> Result<Value> {
+    Ok(Value::Number(vm.random().next_f64()))
+}
+
+pub fn random_int(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (lo, hi) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [lo, hi, ..] => (
+            vm.integer_argument(lo, "randomInt", "lo")?,
+            vm.integer_argument(hi, "randomInt", "hi")?
+        )
+    };
+
+    if lo > hi {
+        return Err(vm.exception(
+            Exception::Custom(String::from("expected `lo` to be less than or equal to `hi` in `randomInt`"))
+        ));
+    }
+
+    // The size of the range wraps around to 0 when it covers every integer.
+    let size = hi.abs_diff(lo).wrapping_add(1);
+    let offset = match size {
+        0 => vm.random().next_u64(),
+        size => vm.random().below(size)
+    };
+
+    Ok(Value::Integer(lo.wrapping_add_unsigned(offset)))
+}
+
+pub fn random_choice(vm: &mut Vm, args: Args) -> Result<Value> {
+    let list = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [list, ..] => list
+    };
+
+    let len = vm.coerce_to_list(list)?.0.0.len();
+    if len == 0 {
+        return Ok(().into());
+    }
+
+    let index = vm.random().below(len as u64) as usize;
+
+    let (List(elements), _) = vm.coerce_to_list(list)?;
+
+    Ok(elements[index])
+}
+
+pub fn shuffle(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (List(source), _) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [source, ..] => vm.coerce_to_list(source)?
+    };
+
+    let mut result = source.clone();
+
+    // Fisher-Yates shuffle.
+    for i in (1..result.len()).rev() {
+        let j = vm.random().below(i as u64 + 1) as usize;
+        result.swap(i, j);
+    }
+
+    vm.alloc_list(result)
+}
+
+pub fn set_seed(vm: &mut Vm, args: Args) -> Result<Value> {
+    let seed = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [seed, ..] => vm.integer_argument(seed, "setSeed", "seed")?
+    };
+
+    vm.set_seed(seed as u64);
+
+    Ok(().into())
+}
@@ -0,0 +1,230 @@
+//! Regular expression functions.
+//!
+//! Patterns use the syntax of the [`regex`] crate, which always matches in linear time
+//! but doesn't support look-around or backreferences.
+//! Like the string functions, the spans of matches are measured in Unicode scalar values.
+//!
+//! Matches are represented as objects with the fields `text`, `start`, `end`, `groups` and `named`.
+//! `groups` is a list of every capture group in the pattern, each of which is either
+//! an object with the fields `text`, `start` and `end`, or `()` if the group didn't participate in the match.
+//! `named` is a dynamic object containing the named capture groups.
+
+use std::iter;
+
+use regex::{Captures, Regex};
+
+use crate::exception::Exception;
+use crate::native::Args;
+use crate::vm::{Vm, Result};
+use crate::value::{Closure, Field, List, Value};
+
+/// Gets the string and pattern arguments of a regex function.
+///
+/// Both are copied out of the heap since matching allocates.
+fn string_and_pattern(vm: &mut Vm, args: &Args, function: &str) -> Result<(String, Regex)> {
+    let (str, pattern) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, pattern, ..] => (
+            vm.string_argument(str, function, "string")?.to_owned(),
+            vm.string_argument(pattern, function, "pattern")?.to_owned()
+        )
+    };
+
+    let regex = vm.regex(&pattern)?;
+
+    // Searching takes time proportional to the length of the string.
+    vm.consume_fuel(str.len())?;
+
+    Ok((str, regex))
+}
+
+/// Constructs the fields of an immutable object.
+fn fields<'a>(fields: impl IntoIterator<Item = (&'a str, Value)>) -> Vec<(String, Field)> {
+    fields.into_iter()
+        .enumerate()
+        .map(|(index, (name, val))| (
+            name.to_owned(),
+            Field { val, mutable: false, index: index as u32 }
+        ))
+        .collect()
+}
+
+/// Allocates an object describing a span of a string.
+/// The object is kept alive until the end of the current [`Vm::scoped`] call.
+fn alloc_span(vm: &mut Vm, text: &str, start: usize) -> Result<Value> {
+    let end = start + text.chars().count();
+
+    let text = vm.alloc_string(text.to_owned())?;
+    vm.keep_alive(text)?;
+
+    let span = vm.alloc_object(fields([
+        ("text", text),
+        ("start", start.into()),
+        ("end", end.into())
+    ]), false)?;
+    vm.keep_alive(span)?;
+
+    Ok(span)
+}
+
+/// Allocates a match object.
+///
+/// `start` is the index of the start of the match in Unicode scalar values,
+/// which is tracked by the caller so that it doesn't have to be counted from the start of the string for every match.
+fn alloc_match(vm: &mut Vm, regex: &Regex, str: &str, captures: &Captures, start: usize) -> Result<Value> {
+    let whole = captures.get_match();
+
+    vm.scoped(|vm| {
+        let mut groups = Vec::new();
+        let mut named = Vec::new();
+
+        for (i, name) in regex.capture_names().enumerate().skip(1) {
+            let group = match captures.get(i) {
+                Some(group) => {
+                    // Groups always start within the match.
+                    let group_start = start + str[whole.start()..group.start()].chars().count();
+                    alloc_span(vm, group.as_str(), group_start)?
+                },
+                None => ().into()
+            };
+
+            groups.push(group);
+
+            if let Some(name) = name {
+                named.push((name.to_owned(), Field { val: group, mutable: true, index: named.len() as u32 }));
+            }
+        }
+
+        let groups = vm.alloc_list(groups)?;
+        vm.keep_alive(groups)?;
+
+        let named = vm.alloc_object(named, true)?;
+        vm.keep_alive(named)?;
+
+        let text = vm.alloc_string(whole.as_str().to_owned())?;
+        vm.keep_alive(text)?;
+
+        let end = start + whole.as_str().chars().count();
+
+        vm.alloc_object(fields([
+            ("text", text),
+            ("start", start.into()),
+            ("end", end.into()),
+            ("groups", groups),
+            ("named", named)
+        ]), false)
+    })
+}
+
+/// Iterates over every match in a string along with the index its match starts at in Unicode scalar values.
+fn matches_with_start<'a>(regex: &'a Regex, str: &'a str) -> impl Iterator<Item = (Captures<'a>, usize)> {
+    let mut last_byte = 0;
+    let mut last_char = 0;
+
+    regex.captures_iter(str).map(move |captures| {
+        let byte = captures.get_match().start();
+        last_char += str[last_byte..byte].chars().count();
+        last_byte = byte;
+
+        (captures, last_char)
+    })
+}
+
+pub fn regex_match(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (str, regex) = string_and_pattern(vm, &args, "regexMatch")?;
+
+    match matches_with_start(&regex, &str).next() {
+        Some((captures, start)) => alloc_match(vm, &regex, &str, &captures, start),
+        None => Ok(().into())
+    }
+}
+
+pub fn regex_find_all(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (str, regex) = string_and_pattern(vm, &args, "regexFindAll")?;
+
+    let list = vm.alloc_list(iter::empty())?;
+
+    vm.with_rooted(list, |vm| {
+        for (captures, start) in matches_with_start(&regex, &str) {
+            let found = alloc_match(vm, &regex, &str, &captures, start)?;
+
+            let (List(list), _) = vm.coerce_to_list_mut(list)?;
+            list.push(found);
+        }
+
+        Ok(())
+    })?;
+
+    Ok(list)
+}
+
+pub fn regex_replace(vm: &mut Vm, args: Args) -> Result<Value> {
+    let replacement = match args[..] {
+        [] | [_] | [_, _] => return Err(vm.exception(
+            Exception::BadArity { expected: 3, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [_, _, replacement, ..] => match vm.unbox(replacement)? {
+            Value::Function(closure) => Replacement::Function(closure),
+            _ => Replacement::String(vm.string_argument(replacement, "regexReplace", "replacement")?.to_owned())
+        }
+    };
+
+    let (str, regex) = string_and_pattern(vm, &args, "regexReplace")?;
+
+    let mut result = String::new();
+    let mut last = 0;
+
+    for (captures, start) in matches_with_start(&regex, &str) {
+        let whole = captures.get_match();
+
+        let replaced = match &replacement {
+            Replacement::String(replacement) => {
+                let mut replaced = String::new();
+                captures.expand(replacement, &mut replaced);
+                replaced
+            },
+            Replacement::Function(function) => {
+                let found = alloc_match(vm, &regex, &str, &captures, start)?;
+                let replaced = vm.call_run(*function, &[found])?;
+                vm.to_string(replaced)?
+            }
+        };
+
+        let piece = &str[last..whole.start()];
+
+        // Replacements can make the string a lot larger, so account for every piece before appending it.
+        vm.consume_fuel(piece.len() + replaced.len())?;
+        result.try_reserve(piece.len() + replaced.len())
+            .map_err(|_| vm.exception(Exception::OutOfMemory))?;
+
+        result.push_str(piece);
+        result.push_str(&replaced);
+        last = whole.end();
+    }
+
+    result.push_str(&str[last..]);
+
+    vm.alloc_string(result)
+}
+
+/// What to replace matches with in `regexReplace`.
+enum Replacement {
+    /// A replacement string, which can refer to capture groups using `$`.
+    String(String),
+    /// A function called with every match object which returns the replacement.
+    Function(Closure),
+}
+
+pub fn regex_split(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (str, regex) = string_and_pattern(vm, &args, "regexSplit")?;
+
+    let parts = regex.split(&str)
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+
+    vm.alloc_string_list(parts)
+}
@@ -0,0 +1,86 @@
+use crate::exception::Exception;
+use crate::heap::HeapValue;
+use crate::native::Args;
+use crate::vm::{Vm, Result};
+use crate::value::Value;
+
+pub fn string_builder(vm: &mut Vm, args: Args) -> Result<Value> {
+    let str = match args[..] {
+        [] => String::new(),
+
+        [initial, ..] => vm.to_string(initial)?
+    };
+
+    vm.heap_alloc(HeapValue::StringBuilder(str))
+        .map(Value::Object)
+}
+
+pub fn sb_append(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (builder, values) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [builder, ref values @ ..] => (builder, values)
+    };
+
+    // Make sure the builder actually is a builder before doing any work.
+    vm.coerce_to_string_builder(builder)?;
+
+    for &val in values {
+        // The value has to be turned into a string before the builder can be borrowed,
+        // since the value might be the builder itself.
+        let str = vm.to_string(val)?;
+        vm.consume_fuel(str.len())?;
+
+        let (builder, _) = vm.coerce_to_string_builder_mut(builder)?;
+        builder.push_str(&str);
+    }
+
+    Ok(builder)
+}
+
+pub fn sb_to_string(vm: &mut Vm, args: Args) -> Result<Value> {
+    let str = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [builder, ..] => {
+            let (builder, _) = vm.coerce_to_string_builder(builder)?;
+            builder.clone()
+        }
+    };
+
+    vm.alloc_string(str)
+}
+
+pub fn sb_length(vm: &mut Vm, args: Args) -> Result<Value> {
+    let length = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [builder, ..] => {
+            let (builder, _) = vm.coerce_to_string_builder(builder)?;
+            builder.chars().count()
+        }
+    };
+
+    Ok(length.into())
+}
+
+pub fn sb_clear(vm: &mut Vm, args: Args) -> Result<Value> {
+    let builder = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [builder, ..] => builder
+    };
+
+    let (str, _) = vm.coerce_to_string_builder_mut(builder)?;
+    str.clear();
+
+    Ok(builder)
+}
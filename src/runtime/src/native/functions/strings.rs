@@ -0,0 +1,484 @@
+//! Natives for working with strings.
+//!
+//! Strings are measured and indexed in Unicode scalar values rather than bytes.
+//! String arguments are never coerced, passing anything other than a string throws [`Exception::BadArgumentType`].
+
+use crate::exception::Exception;
+use crate::native::Args;
+use crate::vm::{Vm, Result};
+use crate::value::{List, Value};
+
+/// Gets the byte offset of a character index into a string, or the length of the string if the index is past its end.
+fn byte_offset(str: &str, index: usize) -> usize {
+    str.char_indices()
+        .nth(index)
+        .map_or(str.len(), |(offset, _)| offset)
+}
+
+/// Gets the character index of a byte offset into a string.
+fn char_index(str: &str, offset: usize) -> usize {
+    str[..offset].chars().count()
+}
+
+/// Clamps an index into a string with a specific length in characters.
+fn clamp_index(index: i64, len: usize) -> usize {
+    index.clamp(0, len as i64) as usize
+}
+
+/// Creates an empty string with room for a specific amount of bytes,
+/// where [`None`] means that the amount overflowed.
+///
+/// Throws [`Exception::OutOfMemory`] instead of aborting if the memory can't be allocated.
+fn string_with_capacity(vm: &Vm, capacity: Option<usize>) -> Result<String> {
+    let capacity = capacity
+        .ok_or_else(|| vm.exception(Exception::OutOfMemory))?;
+
+    vm.consume_fuel(capacity)?;
+
+    let mut str = String::new();
+    str.try_reserve_exact(capacity)
+        .map_err(|_| vm.exception(Exception::OutOfMemory))?;
+
+    Ok(str)
+}
+
+pub fn substring(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (str, start, end) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [str, start] => (
+            vm.string_argument(str, "substring", "string")?,
+            vm.integer_argument(start, "substring", "start")?,
+            None
+        ),
+
+        [str, start, end, ..] => (
+            vm.string_argument(str, "substring", "string")?,
+            vm.integer_argument(start, "substring", "start")?,
+            Some(vm.integer_argument(end, "substring", "end")?)
+        )
+    };
+
+    let len = str.chars().count();
+    let start = clamp_index(start, len);
+    let end = end.map_or(len, |end| clamp_index(end, len));
+
+    let substring = if start < end {
+        str[byte_offset(str, start)..byte_offset(str, end)].to_owned()
+    } else {
+        String::new()
+    };
+
+    vm.alloc_string(substring)
+}
+
+pub fn index_of(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (str, search, from) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [str, search] => (
+            vm.string_argument(str, "indexOf", "string")?,
+            vm.string_argument(search, "indexOf", "search")?,
+            0
+        ),
+
+        [str, search, from, ..] => (
+            vm.string_argument(str, "indexOf", "string")?,
+            vm.string_argument(search, "indexOf", "search")?,
+            vm.integer_argument(from, "indexOf", "from")?
+        )
+    };
+
+    let offset = byte_offset(str, from.max(0) as usize);
+
+    let index = match str[offset..].find(search) {
+        Some(x) => char_index(str, offset + x) as i64,
+        None => -1
+    };
+
+    Ok(Value::Integer(index))
+}
+
+pub fn last_index_of(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (str, search, from) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [str, search] => (
+            vm.string_argument(str, "lastIndexOf", "string")?,
+            vm.string_argument(search, "lastIndexOf", "search")?,
+            None
+        ),
+
+        [str, search, from, ..] => (
+            vm.string_argument(str, "lastIndexOf", "string")?,
+            vm.string_argument(search, "lastIndexOf", "search")?,
+            Some(vm.integer_argument(from, "lastIndexOf", "from")?)
+        )
+    };
+
+    // A match starting at or before the index has to end before the first character boundary
+    // at or after the index plus the length of the searched string.
+    let end = match from {
+        Some(from) if from < 0 => return Ok(Value::Integer(-1)),
+        Some(from) => {
+            let limit = (byte_offset(str, from as usize) + search.len()).min(str.len());
+            (limit..=str.len())
+                .find(|&i| str.is_char_boundary(i))
+                .unwrap_or(str.len())
+        },
+        None => str.len()
+    };
+
+    let index = match str[..end].rfind(search) {
+        Some(x) => char_index(str, x) as i64,
+        None => -1
+    };
+
+    Ok(Value::Integer(index))
+}
+
+pub fn contains(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (str, search) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, search, ..] => (
+            vm.string_argument(str, "contains", "string")?,
+            vm.string_argument(search, "contains", "search")?
+        )
+    };
+
+    Ok(str.contains(search).into())
+}
+
+pub fn starts_with(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (str, prefix) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, prefix, ..] => (
+            vm.string_argument(str, "startsWith", "string")?,
+            vm.string_argument(prefix, "startsWith", "prefix")?
+        )
+    };
+
+    Ok(str.starts_with(prefix).into())
+}
+
+pub fn ends_with(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (str, suffix) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, suffix, ..] => (
+            vm.string_argument(str, "endsWith", "string")?,
+            vm.string_argument(suffix, "endsWith", "suffix")?
+        )
+    };
+
+    Ok(str.ends_with(suffix).into())
+}
+
+pub fn split(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (str, separator) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, separator, ..] => (
+            vm.string_argument(str, "split", "string")?,
+            vm.string_argument(separator, "split", "separator")?
+        )
+    };
+
+    // Splitting by an empty string splits the string into its characters,
+    // rather than also producing an empty string at the start and end.
+    let parts = if separator.is_empty() {
+        str.chars().map(String::from).collect::<Vec<_>>()
+    } else {
+        str.split(separator).map(str::to_owned).collect::<Vec<_>>()
+    };
+
+    vm.alloc_string_list(parts)
+}
+
+pub fn join(vm: &mut Vm, args: Args) -> Result<Value> {
+    let ((List(values), _), separator) = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [values] => (
+            vm.coerce_to_list(values)?,
+            ""
+        ),
+
+        [values, separator, ..] => (
+            vm.coerce_to_list(values)?,
+            vm.string_argument(separator, "join", "separator")?
+        )
+    };
+
+    let mut str = String::new();
+
+    for (i, &val) in values.iter().enumerate() {
+        if i != 0 {
+            str.push_str(separator);
+        }
+
+        str.push_str(&vm.to_str(val)?);
+    }
+
+    vm.alloc_string(str)
+}
+
+pub fn replace(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (str, pattern, replacement) = match args[..] {
+        [] | [_] | [_, _] => return Err(vm.exception(
+            Exception::BadArity { expected: 3, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, pattern, replacement, ..] => (
+            vm.string_argument(str, "replace", "string")?,
+            vm.string_argument(pattern, "replace", "pattern")?,
+            vm.string_argument(replacement, "replace", "replacement")?
+        )
+    };
+
+    // Replacing can make the string a lot larger, so make sure that the result fits before building it.
+    let matches = str.matches(pattern).count();
+    let len = matches.checked_mul(replacement.len())
+        .and_then(|x| x.checked_add(str.len() - matches * pattern.len()));
+
+    let mut result = string_with_capacity(vm, len)?;
+
+    let mut last = 0;
+    for (offset, _) in str.match_indices(pattern) {
+        result.push_str(&str[last..offset]);
+        result.push_str(replacement);
+        last = offset + pattern.len();
+    }
+    result.push_str(&str[last..]);
+
+    vm.alloc_string(result)
+}
+
+fn trim_with(vm: &mut Vm, args: Args, function: &str, trim: fn(&str) -> &str) -> Result<Value> {
+    let str = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, ..] => vm.string_argument(str, function, "string")?
+    };
+
+    let trimmed = trim(str).to_owned();
+
+    vm.alloc_string(trimmed)
+}
+
+pub fn trim(vm: &mut Vm, args: Args) -> Result<Value> {
+    trim_with(vm, args, "trim", str::trim)
+}
+
+pub fn trim_start(vm: &mut Vm, args: Args) -> Result<Value> {
+    trim_with(vm, args, "trimStart", str::trim_start)
+}
+
+pub fn trim_end(vm: &mut Vm, args: Args) -> Result<Value> {
+    trim_with(vm, args, "trimEnd", str::trim_end)
+}
+
+pub fn to_upper(vm: &mut Vm, args: Args) -> Result<Value> {
+    let str = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, ..] => vm.string_argument(str, "toUpper", "string")?
+    };
+
+    let upper = str.to_uppercase();
+
+    vm.alloc_string(upper)
+}
+
+pub fn to_lower(vm: &mut Vm, args: Args) -> Result<Value> {
+    let str = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, ..] => vm.string_argument(str, "toLower", "string")?
+    };
+
+    let lower = str.to_lowercase();
+
+    vm.alloc_string(lower)
+}
+
+pub fn repeat(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (str, count) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, count, ..] => (
+            vm.string_argument(str, "repeat", "string")?,
+            vm.integer_argument(count, "repeat", "count")?
+        )
+    };
+
+    let count = count.max(0) as usize;
+
+    let mut result = string_with_capacity(vm, str.len().checked_mul(count))?;
+    for _ in 0..count {
+        result.push_str(str);
+    }
+
+    vm.alloc_string(result)
+}
+
+fn pad_with(vm: &mut Vm, args: Args, function: &str, at_start: bool) -> Result<Value> {
+    let (str, length, fill) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: true, actual: args.len() as u32 }
+        )),
+
+        [str, length] => (
+            vm.string_argument(str, function, "string")?,
+            vm.integer_argument(length, function, "length")?,
+            " "
+        ),
+
+        [str, length, fill, ..] => (
+            vm.string_argument(str, function, "string")?,
+            vm.integer_argument(length, function, "length")?,
+            vm.string_argument(fill, function, "fill")?
+        )
+    };
+
+    let len = str.chars().count();
+    let padding = (length.max(0) as usize).saturating_sub(len);
+
+    if padding == 0 || fill.is_empty() {
+        let str = str.to_owned();
+        return vm.alloc_string(str);
+    }
+
+    // The padding repeats the fill string as many times as fits, followed by the start of the fill string.
+    let fill_len = fill.chars().count();
+    let partial = &fill[..byte_offset(fill, padding % fill_len)];
+    let padding_bytes = (padding / fill_len).checked_mul(fill.len())
+        .and_then(|x| x.checked_add(partial.len()));
+
+    let mut result = string_with_capacity(vm, padding_bytes.and_then(|x| x.checked_add(str.len())))?;
+
+    if !at_start {
+        result.push_str(str);
+    }
+
+    for _ in 0..padding / fill_len {
+        result.push_str(fill);
+    }
+    result.push_str(partial);
+
+    if at_start {
+        result.push_str(str);
+    }
+
+    vm.alloc_string(result)
+}
+
+pub fn pad_start(vm: &mut Vm, args: Args) -> Result<Value> {
+    pad_with(vm, args, "padStart", true)
+}
+
+pub fn pad_end(vm: &mut Vm, args: Args) -> Result<Value> {
+    pad_with(vm, args, "padEnd", false)
+}
+
+pub fn chars(vm: &mut Vm, args: Args) -> Result<Value> {
+    let str = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, ..] => vm.string_argument(str, "chars", "string")?
+    };
+
+    let chars = str.chars()
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    vm.alloc_string_list(chars)
+}
+
+pub fn code_point_at(vm: &mut Vm, args: Args) -> Result<Value> {
+    let (str, index) = match args[..] {
+        [] | [_] => return Err(vm.exception(
+            Exception::BadArity { expected: 2, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, index, ..] => (
+            vm.string_argument(str, "codePointAt", "string")?,
+            vm.integer_argument(index, "codePointAt", "index")?
+        )
+    };
+
+    let char = usize::try_from(index).ok()
+        .and_then(|i| str.chars().nth(i))
+        .ok_or_else(|| vm.exception(Exception::OutOfBoundsIndex(index, str.chars().count())))?;
+
+    Ok(Value::Integer(char as i64))
+}
+
+pub fn from_code_point(vm: &mut Vm, args: Args) -> Result<Value> {
+    let code_point = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [code_point, ..] => vm.integer_argument(code_point, "fromCodePoint", "codePoint")?
+    };
+
+    let char = u32::try_from(code_point).ok()
+        .and_then(char::from_u32)
+        .ok_or_else(|| vm.exception(Exception::InvalidCodePoint(code_point)))?;
+
+    vm.alloc_string(char.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_offset_counts_characters() {
+        assert_eq!(byte_offset("añb", 0), 0);
+        assert_eq!(byte_offset("añb", 2), 3);
+        assert_eq!(byte_offset("😀a", 1), 4);
+    }
+
+    #[test]
+    fn byte_offset_past_the_end_is_the_length() {
+        assert_eq!(byte_offset("añb", 3), 4);
+        assert_eq!(byte_offset("añb", 10), 4);
+    }
+
+    #[test]
+    fn char_index_is_the_inverse_of_byte_offset() {
+        for index in 0..=3 {
+            assert_eq!(char_index("a😀ñ", byte_offset("a😀ñ", index)), index);
+        }
+    }
+}
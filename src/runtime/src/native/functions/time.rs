@@ -0,0 +1,367 @@
+//! Time and date functions.
+//!
+//! Timestamps are integers counting milliseconds since the Unix epoch.
+//! Dates are objects with the integer fields `year`, `month`, `day`, `hour`, `minute`, `second` and `ms`,
+//! always in UTC and using the proleptic Gregorian calendar.
+
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::exception::Exception;
+use crate::native::Args;
+use crate::vm::{Vm, Result};
+use crate::value::{Field, Value};
+
+const MS_PER_DAY: i64 = 86_400_000;
+
+/// The names of the fields of a date object, in order.
+const DATE_FIELDS: [&str; 7] = ["year", "month", "day", "hour", "minute", "second", "ms"];
+
+/// A date and time in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Date {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+    ms: i64,
+}
+
+impl Date {
+    fn from_timestamp(timestamp: i64) -> Self {
+        let days = timestamp.div_euclid(MS_PER_DAY);
+        let time = timestamp.rem_euclid(MS_PER_DAY);
+
+        let (year, month, day) = civil_from_days(days);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: time / 3_600_000,
+            minute: time / 60_000 % 60,
+            second: time / 1000 % 60,
+            ms: time % 1000,
+        }
+    }
+
+    /// Converts the date into a timestamp, returning [`None`] if it doesn't fit into an integer.
+    /// The date has to be valid.
+    fn to_timestamp(self) -> Option<i64> {
+        let days = days_from_civil(self.year as i128, self.month as i128, self.day as i128);
+        let time = ((self.hour * 60 + self.minute) * 60 + self.second) * 1000 + self.ms;
+
+        i64::try_from(days * MS_PER_DAY as i128 + time as i128).ok()
+    }
+
+    /// Checks that every field of the date is within its range.
+    fn validate(&self) -> std::result::Result<(), String> {
+        let check = |name: &str, value: i64, min: i64, max: i64| {
+            if (min..=max).contains(&value) {
+                Ok(())
+            } else {
+                Err(format!("{name} `{value}` is out of range, expected a {name} between {min} and {max}"))
+            }
+        };
+
+        check("month", self.month, 1, 12)?;
+        check("day", self.day, 1, days_in_month(self.year, self.month))?;
+        check("hour", self.hour, 0, 23)?;
+        check("minute", self.minute, 0, 59)?;
+        check("second", self.second, 0, 59)?;
+        check("ms", self.ms, 0, 999)
+    }
+
+    fn fields(self) -> [i64; 7] {
+        [self.year, self.month, self.day, self.hour, self.minute, self.second, self.ms]
+    }
+
+    /// Formats the date as ISO-8601 with millisecond precision.
+    fn to_iso(self) -> String {
+        // Years outside of the range of four digits use the expanded representation with an explicit sign.
+        let year = if (0..=9999).contains(&self.year) {
+            format!("{:04}", self.year)
+        } else {
+            format!("{}{:06}", if self.year < 0 { '-' } else { '+' }, self.year.unsigned_abs())
+        };
+
+        format!(
+            "{year}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            self.month, self.day, self.hour, self.minute, self.second, self.ms
+        )
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Converts a date into the amount of days since the Unix epoch.
+/// See <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+fn days_from_civil(year: i128, month: i128, day: i128) -> i128 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Converts an amount of days since the Unix epoch into a year, month and day.
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+fn alloc_date(vm: &mut Vm, date: Date) -> Result<Value> {
+    let fields = DATE_FIELDS.iter()
+        .zip(date.fields())
+        .enumerate()
+        .map(|(index, (name, val))| (
+            (*name).to_owned(),
+            Field { val: Value::Integer(val), mutable: false, index: index as u32 }
+        ));
+
+    vm.alloc_object(fields, false)
+}
+
+/// Reads a date object passed as an argument to a native function.
+/// `hour`, `minute`, `second` and `ms` default to 0 if the object doesn't have them.
+fn date_argument(vm: &Vm, val: Value, function: &str) -> Result<Date> {
+    let (object, _) = vm.coerce_to_object(val)?;
+
+    let mut fields = [0; 7];
+    for (i, name) in DATE_FIELDS.iter().enumerate() {
+        match object.get(vm.shapes(), name) {
+            Some(field) => fields[i] = vm.integer_argument(field.val, function, "date")?,
+            None if i < 3 => return Err(vm.exception(Exception::MissingField((*name).to_owned()))),
+            None => {}
+        }
+    }
+
+    let [year, month, day, hour, minute, second, ms] = fields;
+    let date = Date { year, month, day, hour, minute, second, ms };
+
+    date.validate()
+        .map_err(|e| vm.exception(Exception::InvalidDate(e)))?;
+
+    Ok(date)
+}
+
+pub fn now(_: &mut Vm, _: Args) -> Result<Value> {
+    let timestamp = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_millis() as i64,
+        Err(e) => -(e.duration().as_millis() as i64),
+    };
+
+    Ok(Value::Integer(timestamp))
+}
+
+pub fn monotonic(vm: &mut Vm, _: Args) -> Result<Value> {
+    Ok(Value::Number(vm.elapsed().as_secs_f64() * 1000.))
+}
+
+pub fn sleep(vm: &mut Vm, args: Args) -> Result<Value> {
+    let ms = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [ms, ..] => vm.integer_argument(ms, "sleep", "ms")?
+    };
+
+    thread::sleep(Duration::from_millis(ms.max(0) as u64));
+
+    Ok(().into())
+}
+
+pub fn to_date(vm: &mut Vm, args: Args) -> Result<Value> {
+    let timestamp = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [timestamp, ..] => vm.integer_argument(timestamp, "toDate", "timestamp")?
+    };
+
+    alloc_date(vm, Date::from_timestamp(timestamp))
+}
+
+pub fn from_date(vm: &mut Vm, args: Args) -> Result<Value> {
+    let date = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [date, ..] => date_argument(vm, date, "fromDate")?
+    };
+
+    date.to_timestamp()
+        .map(Value::Integer)
+        .ok_or_else(|| vm.exception(Exception::IntegerOverflow))
+}
+
+pub fn format_date(vm: &mut Vm, args: Args) -> Result<Value> {
+    let date = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [date, ..] => match vm.unbox(date)? {
+            Value::Integer(_) | Value::Number(_) => Date::from_timestamp(vm.integer_argument(date, "formatDate", "date")?),
+            _ => date_argument(vm, date, "formatDate")?
+        }
+    };
+
+    vm.alloc_string(date.to_iso())
+}
+
+pub fn parse_date(vm: &mut Vm, args: Args) -> Result<Value> {
+    let str = match args[..] {
+        [] => return Err(vm.exception(
+            Exception::BadArity { expected: 1, or_more: false, actual: args.len() as u32 }
+        )),
+
+        [str, ..] => vm.string_argument(str, "parseDate", "string")?
+    };
+
+    match parse_iso(str.trim()) {
+        Some(date) => alloc_date(vm, date),
+        None => Ok(().into())
+    }
+}
+
+/// Parses an ISO-8601 date with an optional time and UTC offset, such as `2024-01-15`, `2024-01-15T10:30`
+/// or `2024-01-15T10:30:00.123+02:00`. Dates without an offset are in UTC.
+/// The date is converted into UTC, and fractions of seconds beyond milliseconds are truncated.
+fn parse_iso(str: &str) -> Option<Date> {
+    let mut cursor = Cursor { bytes: str.as_bytes(), pos: 0 };
+
+    let year_sign = match cursor.peek() {
+        Some(b'+') => { cursor.pos += 1; 1 },
+        Some(b'-') => { cursor.pos += 1; -1 },
+        _ => 1
+    };
+    let year = year_sign * cursor.digits(4, 9)?;
+    cursor.byte(b'-')?;
+    let month = cursor.digits(2, 2)?;
+    cursor.byte(b'-')?;
+    let day = cursor.digits(2, 2)?;
+
+    let mut date = Date { year, month, day, hour: 0, minute: 0, second: 0, ms: 0 };
+    let mut offset = 0;
+
+    if cursor.peek().is_some() {
+        match cursor.peek()? {
+            b'T' | b't' | b' ' => cursor.pos += 1,
+            _ => return None
+        }
+
+        date.hour = cursor.digits(2, 2)?;
+        cursor.byte(b':')?;
+        date.minute = cursor.digits(2, 2)?;
+
+        if cursor.peek() == Some(b':') {
+            cursor.pos += 1;
+            date.second = cursor.digits(2, 2)?;
+
+            if cursor.peek() == Some(b'.') {
+                cursor.pos += 1;
+                let start = cursor.pos;
+                cursor.digits(1, 9)?;
+
+                // Only the first three digits of the fraction are milliseconds.
+                let fraction = &str[start..cursor.pos];
+                let ms = format!("{:0<3}", &fraction[..fraction.len().min(3)]);
+                date.ms = ms.parse().ok()?;
+            }
+        }
+
+        match cursor.peek() {
+            None => {},
+            Some(b'Z' | b'z') => cursor.pos += 1,
+            Some(sign @ (b'+' | b'-')) => {
+                cursor.pos += 1;
+                let hours = cursor.digits(2, 2)?;
+                if cursor.peek() == Some(b':') {
+                    cursor.pos += 1;
+                }
+                let minutes = cursor.digits(2, 2)?;
+
+                if hours > 23 || minutes > 59 {
+                    return None;
+                }
+
+                offset = (hours * 60 + minutes) * if sign == b'-' { -1 } else { 1 };
+            },
+            _ => return None
+        }
+    }
+
+    if cursor.peek().is_some() || date.validate().is_err() {
+        return None;
+    }
+
+    let timestamp = date.to_timestamp()?.checked_sub(offset * 60_000)?;
+
+    Some(Date::from_timestamp(timestamp))
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn byte(&mut self, byte: u8) -> Option<()> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    /// Parses between `min` and `max` decimal digits.
+    fn digits(&mut self, min: usize, max: usize) -> Option<i64> {
+        let start = self.pos;
+        while self.pos - start < max && self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+
+        if self.pos - start < min {
+            return None;
+        }
+
+        std::str::from_utf8(&self.bytes[start..self.pos]).ok()?
+            .parse()
+            .ok()
+    }
+}
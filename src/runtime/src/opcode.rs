@@ -12,6 +12,7 @@ pub const PUSH_NIL: u8 = 0x17;
 pub const PUSH_STRING: u8 = 0x18;
 pub const PUSH_OBJECT: u8 = 0x19;
 pub const PUSH_LIST: u8 = 0x20;
+pub const PUSH_INT: u8 = 0x21;
 pub const POP: u8 = 0x32;
 pub const DUP: u8 = 0x33;
 pub const SWAP: u8 = 0x34;
@@ -36,4 +37,18 @@ pub const READ_FIELD: u8 = 0x72;
 pub const APPEND_ELEMENT: u8 = 0x73;
 pub const WRITE_ELEMENT: u8 = 0x74;
 pub const READ_ELEMENT: u8 = 0x75;
+pub const INT_DIV: u8 = 0x76;
+pub const MOD: u8 = 0x77;
+pub const NEG: u8 = 0x78;
+pub const POW: u8 = 0x79;
+pub const LESS_THAN_OR_EQUAL: u8 = 0x7A;
+pub const GREATER_THAN_OR_EQUAL: u8 = 0x7B;
+pub const NOT_EQUAL: u8 = 0x7C;
+pub const BIT_AND: u8 = 0x7D;
+pub const BIT_OR: u8 = 0x7E;
+pub const BIT_XOR: u8 = 0x7F;
+pub const BIT_NOT: u8 = 0x80;
+pub const SHIFT_LEFT: u8 = 0x81;
+pub const SHIFT_RIGHT: u8 = 0x82;
+pub const SHIFT_RIGHT_LOGICAL: u8 = 0x83;
 pub const BOUNDARY: u8 = 0xFF;
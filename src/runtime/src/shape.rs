@@ -0,0 +1,184 @@
+//! Object shapes.
+//!
+//! A shape (also known as a hidden class) describes the layout of the fields of an object:
+//! which fields it has, which slot the value of each field is stored in, and whether each field is mutable.
+//! Objects which have had the same fields added in the same order share the same shape,
+//! so this information is stored once per shape instead of once per object,
+//! and objects themselves only store the values of their fields.
+//!
+//! Shapes form a tree of transitions starting from the empty shape. Adding a field to an object moves it
+//! from its current shape to the shape with that field added, which is created the first time the transition is taken.
+//! Since shapes are identified by a [`ShapeId`], code accessing fields can cache which slot a field is stored in
+//! for a specific shape, and skip looking up the field by name when it encounters an object with the same shape again.
+//!
+//! Shapes are never freed, so objects which have fields added dynamically fall back to storing their fields by name
+//! (see [`crate::value::Fields`]) instead of creating a new shape for every field name ever written.
+//! For the same reason, dynamic objects allocated by natives (such as objects parsed from JSON)
+//! store their fields by name from the start, and shapes are only created by `PushObject` and `AddField`.
+
+use std::collections::HashMap;
+
+/// The maximum amount of fields a shape can have.
+/// Objects with more fields than this store their fields by name.
+pub const MAX_SHAPE_FIELDS: usize = 64;
+
+/// The ID of a shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShapeId(u32);
+
+impl ShapeId {
+    /// The shape of an object without any fields.
+    pub const EMPTY: Self = Self(0);
+}
+
+/// A field described by a shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShapeField {
+    /// The slot in the object which stores the value of the field.
+    /// This is also the index of the field in the order fields were added.
+    pub slot: u32,
+    pub mutable: bool,
+}
+
+/// The layout of the fields of an object.
+#[derive(Debug, Default)]
+pub struct Shape {
+    fields: HashMap<String, ShapeField>,
+    /// The names of the fields, indexed by slot.
+    names: Vec<String>,
+    /// The shapes resulting from adding a field to this shape,
+    /// indexed by whether the added field is mutable then by the name of the field.
+    transitions: [HashMap<String, ShapeId>; 2],
+}
+
+impl Shape {
+    /// Gets a field by name.
+    pub fn get(&self, name: &str) -> Option<ShapeField> {
+        self.fields.get(name).copied()
+    }
+
+    /// Gets the names of the fields, indexed by slot.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Gets the amount of fields.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// The table of all shapes created by a vm.
+#[derive(Debug)]
+pub struct Shapes {
+    shapes: Vec<Shape>,
+}
+
+impl Shapes {
+    /// Creates a new shape table containing only the empty shape.
+    pub fn new() -> Self {
+        Self {
+            shapes: vec![Shape::default()]
+        }
+    }
+
+    /// Gets a shape by ID.
+    pub fn get(&self, id: ShapeId) -> &Shape {
+        &self.shapes[id.0 as usize]
+    }
+
+    /// Gets the shape which results from adding a field to a shape.
+    ///
+    /// Returns [`None`] if the shape already has a field with the name,
+    /// or if the shape already has [`MAX_SHAPE_FIELDS`] fields.
+    pub fn with_field(&mut self, id: ShapeId, name: &str, mutable: bool) -> Option<ShapeId> {
+        let shape = self.get(id);
+
+        if let Some(&transition) = shape.transitions[mutable as usize].get(name) {
+            return Some(transition);
+        }
+
+        if shape.len() >= MAX_SHAPE_FIELDS || shape.fields.contains_key(name) {
+            return None;
+        }
+
+        let mut fields = shape.fields.clone();
+        fields.insert(name.to_owned(), ShapeField {
+            slot: shape.len() as u32,
+            mutable
+        });
+
+        let mut names = shape.names.clone();
+        names.push(name.to_owned());
+
+        let transition = ShapeId(self.shapes.len() as u32);
+
+        self.shapes.push(Shape {
+            fields,
+            names,
+            transitions: Default::default()
+        });
+
+        self.shapes[id.0 as usize].transitions[mutable as usize].insert(name.to_owned(), transition);
+
+        Some(transition)
+    }
+}
+
+impl Default for Shapes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A cache of the slot a field was found at for a specific shape,
+/// kept for each instruction which accesses fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldCache {
+    pub shape: ShapeId,
+    pub slot: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adding_the_same_fields_in_the_same_order_shares_shapes() {
+        let mut shapes = Shapes::new();
+
+        let a = shapes.with_field(ShapeId::EMPTY, "x", false).unwrap();
+        let a = shapes.with_field(a, "y", true).unwrap();
+
+        let b = shapes.with_field(ShapeId::EMPTY, "x", false).unwrap();
+        let b = shapes.with_field(b, "y", true).unwrap();
+
+        assert_eq!(a, b);
+        assert_eq!(shapes.get(a).get("y"), Some(ShapeField { slot: 1, mutable: true }));
+        assert_eq!(shapes.get(a).names(), ["x", "y"]);
+    }
+
+    #[test]
+    fn fields_with_different_mutability_produce_different_shapes() {
+        let mut shapes = Shapes::new();
+
+        let a = shapes.with_field(ShapeId::EMPTY, "x", false).unwrap();
+        let b = shapes.with_field(ShapeId::EMPTY, "x", true).unwrap();
+
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn adding_an_existing_field_has_no_transition() {
+        let mut shapes = Shapes::new();
+
+        let a = shapes.with_field(ShapeId::EMPTY, "x", false).unwrap();
+
+        assert_eq!(shapes.with_field(a, "x", false), None);
+        assert_eq!(shapes.with_field(a, "x", true), None);
+    }
+}
@@ -1,22 +1,50 @@
 use std::collections::HashMap;
+use std::fmt::{self, Display};
 
+use crate::exception::Exception;
 use crate::heap::HeapAddress;
+use crate::interner::InternedStr;
 use crate::ark::FuncId;
+use crate::shape::{ShapeId, Shapes};
 
 /// The type of a runtime value.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Type {
     Number,
+    Integer,
     Bool,
     Function,
     String,
     List,
     Object,
+    Map,
+    Bytes,
+    StringBuilder,
     Nil
 }
 
+impl Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Type::Number => "number",
+            Type::Integer => "integer",
+            Type::Bool => "boolean",
+            Type::Function => "function",
+            Type::String => "string",
+            Type::List => "list",
+            Type::Object => "object",
+            Type::Map => "map",
+            Type::Bytes => "bytes",
+            Type::StringBuilder => "string builder",
+            Type::Nil => "()",
+        };
+
+        f.write_str(name)
+    }
+}
+
 /// A closure over a function and an object containing captured variables.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Closure {
     /// The ID of the function the closure calls.
     pub function: FuncId,
@@ -49,10 +77,26 @@ pub struct List(pub Vec<Value>);
 /// An object.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Object {
-    pub fields: HashMap<String, Field>,
+    pub fields: Fields,
     pub dynamic: bool,
 }
 
+/// The fields of an object.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fields {
+    /// Fields laid out according to a shape, where the object only stores the value of each field.
+    /// See [`crate::shape`].
+    Shaped {
+        shape: ShapeId,
+        /// The values of the fields, indexed by slot.
+        values: Vec<Value>,
+    },
+    /// Fields stored by name. Objects fall back to this once they have a field added dynamically,
+    /// a field added twice, or too many fields to be described by a shape.
+    /// Dynamic objects allocated by natives store their fields like this from the start.
+    Dictionary(HashMap<String, Field>),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Field {
     pub val: Value,
@@ -60,11 +104,290 @@ pub struct Field {
     pub index: u32,
 }
 
+impl Object {
+    /// Creates a new object without any fields.
+    pub fn new(dynamic: bool) -> Self {
+        Self {
+            fields: Fields::Shaped {
+                shape: ShapeId::EMPTY,
+                values: Vec::new()
+            },
+            dynamic
+        }
+    }
+
+    /// Creates a new object without any fields which stores its fields by name instead of using a shape.
+    pub fn new_dictionary(dynamic: bool) -> Self {
+        Self {
+            fields: Fields::Dictionary(HashMap::new()),
+            dynamic
+        }
+    }
+
+    /// Gets a field by name.
+    pub fn get(&self, shapes: &Shapes, name: &str) -> Option<Field> {
+        match &self.fields {
+            Fields::Shaped { shape, values } => shapes.get(*shape)
+                .get(name)
+                .map(|field| Field {
+                    val: values[field.slot as usize],
+                    mutable: field.mutable,
+                    index: field.slot
+                }),
+            Fields::Dictionary(fields) => fields.get(name).copied(),
+        }
+    }
+
+    /// Gets the fields along with their names in the order they were added.
+    pub fn fields<'a>(&'a self, shapes: &'a Shapes) -> Vec<(&'a str, Field)> {
+        match &self.fields {
+            Fields::Shaped { shape, .. } => shapes.get(*shape)
+                .names()
+                .iter()
+                .filter_map(|name| self.get(shapes, name).map(|field| (name.as_str(), field)))
+                .collect(),
+            Fields::Dictionary(fields) => {
+                let mut fields = fields.iter()
+                    .map(|(name, field)| (name.as_str(), *field))
+                    .collect::<Vec<_>>();
+                fields.sort_by_key(|(_, field)| field.index);
+                fields
+            },
+        }
+    }
+
+    /// Gets the values of the fields in no particular order.
+    pub fn values(&self) -> impl Iterator<Item = Value> + '_ {
+        let (shaped, dictionary) = match &self.fields {
+            Fields::Shaped { values, .. } => (Some(values.iter().copied()), None),
+            Fields::Dictionary(fields) => (None, Some(fields.values().map(|field| field.val))),
+        };
+
+        shaped.into_iter().flatten()
+            .chain(dictionary.into_iter().flatten())
+    }
+
+    /// Gets the amount of fields.
+    pub fn len(&self) -> usize {
+        match &self.fields {
+            Fields::Shaped { values, .. } => values.len(),
+            Fields::Dictionary(fields) => fields.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds a field, replacing any existing field with the same name in place.
+    pub fn add_field(&mut self, shapes: &mut Shapes, name: String, val: Value, mutable: bool) {
+        if let Fields::Shaped { shape, values } = &mut self.fields &&
+            let Some(transition) = shapes.with_field(*shape, &name, mutable) {
+            *shape = transition;
+            values.push(val);
+            return;
+        }
+
+        let fields = self.make_dictionary(shapes);
+
+        // A replaced field keeps its original position in the field order.
+        let index = fields.get(&name)
+            .map_or(fields.len() as u32, |field| field.index);
+
+        fields.insert(name, Field {
+            val,
+            mutable,
+            index
+        });
+    }
+
+    /// Writes to an existing mutable field.
+    /// If the object is dynamic and doesn't have the field, a new mutable field is added.
+    pub fn write_field(&mut self, shapes: &Shapes, name: String, val: Value) -> Result<(), Exception> {
+        if let Fields::Shaped { shape, values } = &mut self.fields {
+            match shapes.get(*shape).get(&name) {
+                Some(field) if field.mutable => {
+                    values[field.slot as usize] = val;
+                    return Ok(());
+                },
+                Some(_) => return Err(Exception::WriteToImmutableField(name)),
+                None if !self.dynamic => return Err(Exception::MissingField(name)),
+                // Adding fields dynamically would create a shape for every field name ever written.
+                None => {},
+            }
+        }
+
+        let dynamic = self.dynamic;
+        let fields = self.make_dictionary(shapes);
+
+        match fields.get_mut(&name) {
+            Some(field) => {
+                if field.mutable {
+                    // Override value.
+                    field.val = val;
+                } else {
+                    // Cannot write to immutable field.
+                    return Err(Exception::WriteToImmutableField(name));
+                }
+            },
+            None => {
+                if dynamic {
+                    // Writing to a dynamic object, insert a mutable field.
+                    let field_count = fields.len() as u32;
+                    fields.insert(name, Field {
+                        val,
+                        mutable: true,
+                        index: field_count
+                    });
+                } else {
+                    // Missing field.
+                    return Err(Exception::MissingField(name));
+                }
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Removes a field, returning it if the object had it.
+    /// The fields after the removed field move back one index, which keeps the indices of the fields contiguous.
+    pub fn remove_field(&mut self, shapes: &Shapes, name: &str) -> Option<Field> {
+        self.get(shapes, name)?;
+
+        let fields = self.make_dictionary(shapes);
+        let removed = fields.remove(name)?;
+
+        for field in fields.values_mut() {
+            if field.index > removed.index {
+                field.index -= 1;
+            }
+        }
+
+        Some(removed)
+    }
+
+    /// Makes every field immutable and the object non-dynamic.
+    pub fn freeze(&mut self, shapes: &mut Shapes) {
+        // Fields stored by name might have names chosen at runtime, so they're kept that way instead of creating shapes.
+        if let Fields::Dictionary(fields) = &mut self.fields {
+            for field in fields.values_mut() {
+                field.mutable = false;
+            }
+
+            self.dynamic = false;
+            return;
+        }
+
+        // Objects laid out by a shape are rebuilt with a shape where every field is immutable.
+        let fields = self.fields(shapes)
+            .into_iter()
+            .map(|(name, field)| (name.to_owned(), field.val))
+            .collect::<Vec<_>>();
+
+        let mut frozen = Object::new(false);
+        for (name, val) in fields {
+            frozen.add_field(shapes, name, val, false);
+        }
+
+        *self = frozen;
+    }
+
+    /// Converts the fields of the object into a dictionary, if they aren't already.
+    fn make_dictionary(&mut self, shapes: &Shapes) -> &mut HashMap<String, Field> {
+        if let Fields::Shaped { .. } = &self.fields {
+            let fields = self.fields(shapes)
+                .into_iter()
+                .map(|(name, field)| (name.to_owned(), field))
+                .collect();
+
+            self.fields = Fields::Dictionary(fields);
+        }
+
+        match &mut self.fields {
+            Fields::Dictionary(fields) => fields,
+            Fields::Shaped { .. } => unreachable!(),
+        }
+    }
+}
+
+/// A map from keys of any hashable value to values.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Map {
+    pub entries: HashMap<MapKey, MapEntry>,
+    /// The index which will be given to the next inserted entry.
+    pub next_index: u32,
+}
+
+impl Map {
+    /// Gets the entry for a key.
+    pub fn get(&self, key: &MapKey) -> Option<&MapEntry> {
+        self.entries.get(key)
+    }
+
+    /// Sets the value for a key, inserting a new entry if the key isn't already in the map.
+    pub fn insert(&mut self, key: MapKey, key_val: Value, val: Value) {
+        match self.entries.get_mut(&key) {
+            Some(entry) => entry.val = val,
+            None => {
+                let index = self.next_index;
+                self.next_index += 1;
+
+                self.entries.insert(key, MapEntry {
+                    key: key_val,
+                    val,
+                    index
+                });
+            }
+        }
+    }
+
+    /// Removes the entry for a key.
+    pub fn remove(&mut self, key: &MapKey) -> Option<MapEntry> {
+        self.entries.remove(key)
+    }
+
+    /// Gets the entries of the map in the order they were inserted.
+    pub fn ordered(&self) -> Vec<&MapEntry> {
+        let mut entries = self.entries.values().collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.index);
+        entries
+    }
+}
+
+/// An entry in a [`Map`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapEntry {
+    /// The original value of the key.
+    pub key: Value,
+    pub val: Value,
+    pub index: u32,
+}
+
+/// The hashable representation of a value used as the key of a [`Map`].
+/// 
+/// Strings are compared by content through their [`InternedStr`],
+/// while lists, objects, and maps are compared by reference.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    /// The bits of a non-integral number.
+    Number(u64),
+    /// An integer, or a number with an integral value which fits into an integer.
+    Integer(i64),
+    Bool(bool),
+    String(InternedStr),
+    Function(Closure),
+    /// A heap-allocated value compared by its address.
+    Reference(HeapAddress),
+    Nil,
+}
+
 /// A runtime value.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Value {
     /// A number.
     Number(f64),
+    /// A 64-bit integer.
+    Integer(i64),
     /// A boolean.
     Bool(bool),
     /// A reference to a string in the string section.
@@ -83,9 +406,15 @@ impl From<f64> for Value {
     }
 }
 
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
 impl From<usize> for Value {
     fn from(value: usize) -> Self {
-        Self::Number(value as f64)
+        Self::Integer(value as i64)
     }
 }
 
@@ -133,6 +462,17 @@ impl TryFrom<&Value> for f64 {
     }
 }
 
+impl TryFrom<&Value> for i64 {
+    type Error = ();
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Integer(n) => Ok(*n),
+            _ => Err(())
+        }
+    }
+}
+
 impl TryFrom<&Value> for bool {
     type Error = ();
 
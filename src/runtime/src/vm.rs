@@ -1,22 +1,39 @@
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::iter;
+use std::time::{Duration, Instant};
 
 use debugger::Debugger;
 use frame::{Frame, FrameKind};
 use polonius_the_crab::{polonius, polonius_return};
+use random::Random;
+use regex::{Regex, RegexBuilder};
 use stack::Stack;
 
 use crate::ark::Function;
 use crate::exception::{Exception, FormattedException, TraceFrame};
+use crate::instruction::DecodedCode;
+use crate::interner::Interner;
 use crate::native::{functions, NativeFunction};
 use crate::heap::{Heap, HeapAddress, HeapAllocError, HeapGetError, HeapValue};
+use crate::shape::{FieldCache, Shapes};
 use crate::value::{Field, List, Object, Value};
 
 pub mod frame;
 pub mod stack;
 pub mod debugger;
 mod interpret;
+mod random;
 mod value_ops;
 
+pub(crate) use value_ops::MAX_NESTING_DEPTH;
+
+/// The maximum amount of compiled regular expressions which are cached at once.
+const REGEX_CACHE_SIZE: usize = 64;
+
+/// The maximum size in bytes of a single compiled regular expression.
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
 /// The result of a VM operation.
 pub type Result<T> = std::result::Result<T, FormattedException>;
 
@@ -38,6 +55,10 @@ pub struct VmConsts {
     pub strings: Vec<String>,
     /// Bytecode instructions.
     pub code: Vec<u8>,
+    /// The bytecode decoded into instructions.
+    pub decoded: DecodedCode,
+    /// The index of the first decoded instruction of each user function.
+    pub entries: Vec<usize>,
 }
 
 /// The runtime virtual machine.
@@ -49,15 +70,22 @@ pub struct Vm {
     stack: Stack,
     /// The vm's heap where all non-stack memory is allocated.
     heap: Heap,
+    /// The shapes of objects on the heap.
+    shapes: Shapes,
+    /// The intern table of strings used as keys.
+    interner: Interner,
+    /// The inline field cache of each instruction, indexed by instruction index.
+    /// Only used by instructions which access fields.
+    field_caches: Vec<Option<FieldCache>>,
     /// The vm's call stack.
     /// Responsible for keeping track of what function is currently being executed.
     call_stack: Vec<Frame>,
-    /// The instruction pointer. Points to a specific byte in [`VmConsts::code`]
-    /// which is the *next* bytecode instruction to be executed.
+    /// The instruction pointer. Points to a specific instruction in [`VmConsts::decoded`]
+    /// which is the *next* instruction to be executed.
     ip: usize,
     /// The instruction pointer used as reference when constructing a stack trace.
     /// This is not the same as [`Self::ip`] since this will always point at the
-    /// bytecode instruction which is *currently* being executed, to provide
+    /// instruction which is *currently* being executed, to provide
     /// better traces.
     trace_ip: usize,
     /// Input stream.
@@ -66,6 +94,15 @@ pub struct Vm {
     output: Box<dyn Output>,
     /// The debugger interface.
     debugger: Option<Box<dyn Debugger>>,
+    /// The amount of work the vm can do before running out of fuel, or [`None`] if there is no limit.
+    /// Kept in a cell since work is also done by operations which only borrow the vm.
+    fuel: Cell<Option<u64>>,
+    /// Compiled regular expressions, indexed by their pattern.
+    regexes: HashMap<String, Regex>,
+    /// The instant the vm was created, which monotonic time is measured from.
+    created: Instant,
+    /// The generator used by the random number functions.
+    random: Random,
 }
 
 impl Vm {
@@ -82,22 +119,41 @@ impl Vm {
         output: Box<dyn Output>,
         debugger: Option<Box<dyn Debugger>>
     ) -> Self {
+        let decoded = DecodedCode::decode(&code, &functions, strings.len());
+
+        let entries = functions.iter()
+            .map(|function| decoded.resolve(function.address as usize))
+            .collect();
+
+        let field_caches = vec![None; decoded.instructions.len()];
+
+        let interner = Interner::new(&strings);
+
         Self {
             consts: VmConsts {
                 functions,
                 native_functions: functions::get_functions(),
                 strings,
-                code
+                code,
+                decoded,
+                entries
             },
             stack: Stack::new(stack_size),
             heap: Heap::new(heap_size),
+            shapes: Shapes::new(),
+            field_caches,
+            interner,
             call_stack: Vec::with_capacity(call_stack_size),
             // This is just a placeholder, the instruction pointer will be overridden once a function is called.
             ip: 0,
             trace_ip: 0,
             input,
             output,
-            debugger
+            debugger,
+            fuel: Cell::new(None),
+            regexes: HashMap::new(),
+            created: Instant::now(),
+            random: Random::from_entropy()
         }
     }
 
@@ -106,6 +162,11 @@ impl Vm {
         &mut self.heap
     }
 
+    /// Gets the shapes of objects on the heap.
+    pub fn shapes(&self) -> &Shapes {
+        &self.shapes
+    }
+
     /// Gets the input stream.
     pub fn input(&mut self) -> &mut dyn Input  {
         &mut *self.input 
@@ -116,6 +177,40 @@ impl Vm {
         &mut *self.output
     }
     
+    /// Sets the amount of fuel the vm can use before throwing [`Exception::OutOfFuel`],
+    /// or [`None`] to allow the vm to run indefinitely.
+    ///
+    /// Executing an instruction uses one unit of fuel. Operations which do more work than a single instruction
+    /// use fuel in proportion to it: allocating uses one unit for every element or byte allocated,
+    /// comparing values uses one unit for every value visited,
+    /// and converting values into strings uses one unit for every value visited and every byte copied.
+    pub fn set_fuel(&mut self, fuel: Option<u64>) {
+        self.fuel.set(fuel);
+    }
+
+    /// Gets the amount of fuel the vm has left.
+    pub fn fuel(&self) -> Option<u64> {
+        self.fuel.get()
+    }
+
+    /// Uses up an amount of fuel, throwing [`Exception::OutOfFuel`] if there isn't enough left.
+    pub(crate) fn consume_fuel(&self, amount: usize) -> Result<()> {
+        let Some(fuel) = self.fuel.get() else {
+            return Ok(());
+        };
+
+        match fuel.checked_sub(amount as u64) {
+            Some(fuel) => {
+                self.fuel.set(Some(fuel));
+                Ok(())
+            },
+            None => {
+                self.fuel.set(Some(0));
+                Err(self.exception(Exception::OutOfFuel))
+            }
+        }
+    }
+
     /// Gets the debugger interface.
     pub fn debugger(&mut self) -> &mut Option<Box<dyn Debugger>> {
         &mut self.debugger
@@ -159,6 +254,8 @@ impl Vm {
 
     /// Allocates a value on the heap.
     pub fn heap_alloc(&mut self, value: HeapValue) -> Result<HeapAddress> {
+        self.consume_fuel(value.size())?;
+
         match self.heap.alloc(value) {
             Ok(x) => Ok(x),
             Err(HeapAllocError::OutOfMemory(value)) => {
@@ -168,6 +265,9 @@ impl Vm {
                 // in the business of performance anyway.
                 let roots = self.stack.iter().copied();
                 self.heap.collect(roots);
+
+                // Strings only used as keys of maps which have just been freed don't have to stay interned.
+                self.interner.evict_unused();
                 
                 // If we're still out of memory after doing a run of garbage collection,
                 // then we're *truly* out of memory.
@@ -193,17 +293,120 @@ impl Vm {
             .map(Value::Object)
     }
 
+    /// Allocates a list of strings on the heap.
+    ///
+    /// The list is kept rooted while the strings are allocated,
+    /// so that the strings allocated so far aren't collected if allocating another one runs out of memory.
+    pub fn alloc_string_list(&mut self, strings: impl IntoIterator<Item = String>) -> Result<Value> {
+        let list = self.alloc_list(iter::empty())?;
+
+        self.with_rooted(list, |vm| {
+            for str in strings {
+                let str = vm.alloc_string(str)?;
+
+                let (List(list), _) = vm.coerce_to_list_mut(list)?;
+                list.push(str);
+            }
+
+            Ok(())
+        })?;
+
+        Ok(list)
+    }
+
     /// Allocates an object on the heap.
+    ///
+    /// Dynamic objects store their fields by name, since the names of their fields are usually chosen at runtime
+    /// and would otherwise create shapes which are never freed.
     pub fn alloc_object(&mut self, fields: impl IntoIterator<Item = (String, Field)>, dynamic: bool) -> Result<Value> {
-        let object = Object {
-            fields: fields.into_iter().collect(),
-            dynamic
+        let mut fields = fields.into_iter().collect::<Vec<_>>();
+        fields.sort_by_key(|(_, field)| field.index);
+
+        let mut object = if dynamic {
+            Object::new_dictionary(true)
+        } else {
+            Object::new(false)
         };
+        for (name, field) in fields {
+            object.add_field(&mut self.shapes, name, field.val, field.mutable);
+        }
 
         self.heap_alloc(HeapValue::Object(object))
             .map(Value::Object)
     }
 
+    /// Keeps a value from being garbage collected for the rest of the execution of the current native function.
+    /// 
+    /// Natives allocating several values before returning them have to do this,
+    /// since nothing else references the values until they've been returned.
+    /// The value is pushed onto the stack, which is reset once the native function returns.
+    pub fn keep_alive(&mut self, value: Value) -> Result<()> {
+        self.stack.push(value)
+            .map_err(|e| self.exception(e))
+    }
+
+    /// Runs a function, releasing every value it keeps alive through [`Vm::keep_alive`] once it returns.
+    ///
+    /// Lets natives build values out of several allocations one after another without filling up the stack.
+    pub fn scoped<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let head = self.stack.head();
+
+        let result = f(self);
+        self.stack.shrink(head);
+
+        result
+    }
+
+    /// Keeps a value from being garbage collected while running a function.
+    ///
+    /// Unlike [`Vm::keep_alive`], the value stops being kept alive once the function returns.
+    pub fn with_rooted<T>(&mut self, value: Value, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.scoped(|vm| {
+            vm.keep_alive(value)?;
+            f(vm)
+        })
+    }
+
+    /// Gets a compiled regular expression for a pattern.
+    ///
+    /// Compiled patterns are cached by their pattern, so that natives called in a loop don't recompile them every time.
+    pub fn regex(&mut self, pattern: &str) -> Result<Regex> {
+        if let Some(regex) = self.regexes.get(pattern) {
+            return Ok(regex.clone());
+        }
+
+        // Compiling a regex takes time and memory roughly proportional to the length of the pattern.
+        self.consume_fuel(pattern.len())?;
+
+        let regex = RegexBuilder::new(pattern)
+            .size_limit(REGEX_SIZE_LIMIT)
+            .build()
+            .map_err(|e| self.exception(Exception::InvalidRegex(e.to_string())))?;
+
+        // Patterns built at runtime could otherwise grow the cache forever.
+        if self.regexes.len() >= REGEX_CACHE_SIZE {
+            self.regexes.clear();
+        }
+        self.regexes.insert(pattern.to_owned(), regex.clone());
+
+        Ok(regex)
+    }
+
+    /// Seeds the random number generator, making the values generated by the random number functions reproducible.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.random = Random::from_seed(seed);
+    }
+
+    /// Gets the random number generator.
+    pub(crate) fn random(&mut self) -> &mut Random {
+        &mut self.random
+    }
+
+    /// Gets the time which has passed since the vm was created.
+    pub fn elapsed(&self) -> Duration {
+        self.created.elapsed()
+    }
+
     /// Formats an [`Exception`] into a [`FormattedException`].
     pub fn exception(&self, exception: Exception) -> FormattedException {
         let stack_trace = self.construct_stack_trace();
@@ -224,7 +427,7 @@ impl Vm {
 
         if let Some(first) = frames.next() {
             let mut address = match first.kind {
-                FrameKind::UserFunction => Some(self.trace_ip),
+                FrameKind::UserFunction => Some(self.consts.decoded.address_of(self.trace_ip)),
                 FrameKind::NativeFunction => None,
                 FrameKind::Temp { .. } => unreachable!()
             };
@@ -233,7 +436,9 @@ impl Vm {
             let mut previous = first;
     
             for frame in frames {
-                address = previous.ret;
+                // Traces use byte addresses rather than instruction indices.
+                address = previous.ret
+                    .map(|ret| self.consts.decoded.address_of(ret));
                 stack_trace.push(self.construct_trace_frame(frame, address));
                 previous = frame;
             }
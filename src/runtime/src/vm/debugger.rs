@@ -1,4 +1,5 @@
 use crate::heap::Heap;
+use crate::shape::Shapes;
 use crate::vm::VmConsts;
 use crate::vm::stack::Stack;
 use crate::vm::frame::Frame;
@@ -25,6 +26,7 @@ pub struct DebugInspection<'vm> {
     pub consts: &'vm VmConsts,
     pub stack: &'vm Stack,
     pub heap: &'vm Heap,
+    pub shapes: &'vm Shapes,
     pub call_stack: &'vm Vec<Frame>,
     pub ip: usize,
 }
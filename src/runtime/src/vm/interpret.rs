@@ -1,9 +1,9 @@
 //! # The VM interpreter
 //! 
 //! The Noa virtual machine is a bytecode interpreter.
-//! During normal execution of a user function, the vm will sequentially read bytes
-//! from the code section of the Ark file and interpret each byte as an instruction
-//! (and potentially read an additional set of bytes as instruction operands).
+//! When the vm is created, the code section of the Ark file is decoded into a sequence of [`Instruction`]s
+//! (see [`crate::instruction`]), and during normal execution of a user function, the vm will sequentially
+//! interpret each instruction.
 //! Executing an instruction will usually have some effect on the stack, the instruction pointer,
 //! or control flow, causing the vm to potentially enter and execute a new function.
 //! 
@@ -64,12 +64,12 @@
 //! and the [`FrameKind`] of the stack frame (which is always [`FrameKind::UserFunction`]),
 //! and push it onto the call stack.
 //! 
-//! Once everything is set up, the [`Vm::ip`] will be set to the function's specified start address,
+//! Once everything is set up, the [`Vm::ip`] will be set to the index of the first instruction of the function,
 //! and the [`Vm::call_user`] is finished.
 //! 
 //! ## Returning from user functions
 //! 
-//! Returning from a user function is done from bytecode by interpreting the [`opcode::RET`] instruction.
+//! Returning from a user function is done from bytecode by interpreting the [`Instruction::Ret`] instruction.
 //! This will return from [`Vm::interpret_instruction`] with [`InterpretControlFlow::Return`] and in turn
 //! call [`Vm::ret_user`].
 //! 
@@ -118,7 +118,10 @@
 //! [`Vm::call_native`] will also push a stack frame onto the call stack only for the duration of the execution
 //! of the native function and immediately pop it afterwards. It also doesn't account for any superfluous arguments
 //! to the function since native function may be variadic,
-//! instead passing a vector of the raw arguments to the native function.
+//! instead passing the raw arguments to the native function as [`Args`],
+//! which stores the arguments of most calls inline without allocating.
+//! Since a native function returns as soon as it's called, [`Vm::call_run`] calls native functions directly
+//! without running the interpreter.
 //! 
 //! ## Temporary stack frames
 //! 
@@ -126,9 +129,9 @@
 //! (and the compiler not being very smart lol). The compiler cannot know how big the stack will be
 //! when breaking or continuing from a loop, since the `break` or `continue` statement might
 //! be inside some other expression like `1 + break`. In this example, `1` will be on the stack when breaking,
-//! essentially being garbage data. To remedy this, when entering a loop, the [`opcode::ENTER_TEMP_FRAME`]
+//! essentially being garbage data. To remedy this, when entering a loop, the [`Instruction::EnterTempFrame`]
 //! opcode will be emitted, which signifies to push a temporary stack frame onto the call stack,
-//! and when exiting a loop, the [`opcode::EXIT_TEMP_FRAME`] opcode is emitted which does the reverse.
+//! and when exiting a loop, the [`Instruction::ExitTempFrame`] instruction is emitted which does the reverse.
 //! Despite being called the "*call* stack", temporary frames don't really represent a "call" to anything,
 //! instead just being a marker on the call stack. For this reason, several places in the interpreter
 //! have to account for the current stack frame possibly being a temporary frame.
@@ -145,18 +148,19 @@
 //! 
 //! ## Boundaries
 //! 
-//! On a small note, [`opcode::BOUNDARY`] exists to catch any missing return statements.
-//! [`opcode::BOUNDARY`] is emitted after every function, and its only purpose is to cause an exception
+//! On a small note, [`Instruction::Boundary`] exists to catch any missing return statements.
+//! [`Instruction::Boundary`] is emitted after every function, and its only purpose is to cause an exception
 //! if the vm tries to execute it.
 
-use std::assert_matches::assert_matches;
-use std::collections::HashMap;
+use std::cmp::Ordering;
 
 use crate::ark::FuncId;
 use crate::exception::Exception;
 use crate::heap::{HeapGetError, HeapValue};
-use crate::opcode;
-use crate::value::{Closure, Field, List, Object, Value};
+use crate::instruction::Instruction;
+use crate::native::Args;
+use crate::shape::FieldCache;
+use crate::value::{Closure, Fields, List, Object, Type, Value};
 use crate::vm::frame::{Frame, FrameKind};
 
 use super::debugger::DebugInspection;
@@ -174,13 +178,29 @@ enum InterpretControlFlow {
 impl Vm {
     /// Calls a closure with specified arguments, runs until it returns, then returns the return value of the closure.
     pub fn call_run(&mut self, closure: Closure, args: &[Value]) -> Result<Value> {
+        let stack_start = self.stack.head();
+
         // Push arguments onto the stack.
+        // Native functions get their arguments passed directly, but the arguments are still pushed
+        // to keep them from being garbage collected during the call.
         for value in args {
             self.stack.push(*value)
                 .map_err(|e| self.exception(e))?;
         }
 
-        self.call(closure, args.len() as u32)?;
+        // Native functions return as soon as they're called, so there's no interpreter to run.
+        if closure.function.is_native() {
+            if closure.captures.is_some() {
+                return Err(self.exception(Exception::InvalidNativeFunction(closure.function.decode())));
+            }
+
+            let ret = self.call_native(closure.function, Args::from_slice(args), stack_start)?;
+            self.stack.shrink(stack_start);
+
+            return Ok(ret);
+        }
+
+        self.call_user(closure, args.len() as u32)?;
         let res = self.run_function()?;
 
         Ok(res)
@@ -190,8 +210,11 @@ impl Vm {
     fn call(&mut self, closure: Closure, arg_count: u32) -> Result<()> {
         if closure.function.is_native() {
             // It shouldn't be possible in any way for a native function to capture variables.
-            assert!(closure.captures.is_none(), "Native function cannot be called with captures.");
-            self.call_native(closure.function, arg_count)
+            if closure.captures.is_some() {
+                return Err(self.exception(Exception::InvalidNativeFunction(closure.function.decode())));
+            }
+
+            self.call_native_from_stack(closure.function, arg_count)
         } else {
             self.call_user(closure, arg_count)
         }
@@ -207,7 +230,7 @@ impl Vm {
         // Set up properties for the call and stack frame.
         let arity = function.arity;
         let locals_count = function.locals_count;
-        let address = function.address as usize;
+        let entry = self.consts.entries[user_index as usize];
         let ret = self.get_return_address();
 
         // Get rid of any additional arguments outside of what the function expects.
@@ -248,7 +271,7 @@ impl Vm {
             
             let list = match heap_value {
                 HeapValue::List(list) => list,
-                _ => panic!("expected closure captures to be a list")
+                _ => return Err(self.coercion_error(Value::Object(heap_address), Type::List))
             };
             
             for value in &list.0 {
@@ -271,16 +294,39 @@ impl Vm {
             kind: FrameKind::UserFunction,
         };
 
-        self.call_stack.push_within_capacity(frame)
+        self.call_stack.push_within_capacity(frame).map(|_| ())
             .map_err(|_| self.exception(Exception::CallStackOverflow))?;
         
-        self.ip = address;
+        self.ip = entry;
 
         Ok(())
     }
 
-    /// Calls a native function.
-    fn call_native(&mut self, id: FuncId, arg_count: u32) -> Result<()> {
+    /// Calls a native function with a specified amount of arguments from the stack,
+    /// then pushes its return value onto the stack.
+    fn call_native_from_stack(&mut self, id: FuncId, arg_count: u32) -> Result<()> {
+        let stack_start = self.stack.head().checked_sub(arg_count as usize)
+            .ok_or_else(|| self.exception(Exception::StackUnderflow))?;
+
+        let args = self.stack.slice_from_end(arg_count as usize)
+            .map(Args::from_slice)
+            .ok_or_else(|| self.exception(Exception::StackUnderflow))?;
+
+        let ret = self.call_native(id, args, stack_start)?;
+
+        let stack_backtrack_index = self.get_stack_backtrack_index(stack_start)?;
+        self.stack.shrink(stack_backtrack_index);
+
+        // Finally, push the return value onto the stack.
+        self.stack.push(ret)
+            .map_err(|e| self.exception(e))?;
+
+        Ok(())
+    }
+
+    /// Calls a native function with some arguments and returns its return value.
+    /// The arguments are expected to also be on the stack starting at `stack_start`.
+    fn call_native(&mut self, id: FuncId, args: Args, stack_start: usize) -> Result<Value> {
         // Get the function from the decoded function ID.
         // Function pointers implement `Copy`, so retrieving the function pointer here
         // doesn't actually require an immutable borrow, which is incredibly nice.
@@ -289,12 +335,6 @@ impl Vm {
             .ok_or_else(|| self.exception(Exception::InvalidNativeFunction(native_index)))?
             .function;
 
-        let stack_start = self.stack.head() - arg_count as usize;
-
-        let args = self.stack.slice_from_end(arg_count as usize)
-            .ok_or_else(|| self.exception(Exception::StackUnderflow))?
-            .to_vec();
-
         let ret_address = self.get_return_address();
 
         let frame = Frame {
@@ -306,7 +346,7 @@ impl Vm {
 
         // Block to keep track of the 'scope' for the frame.
         let ret = {
-            self.call_stack.push_within_capacity(frame)
+            self.call_stack.push_within_capacity(frame).map(|_| ())
                 .map_err(|_| self.exception(Exception::CallStackOverflow))?;
 
             // Actually call the function.
@@ -323,14 +363,7 @@ impl Vm {
             self.ip = ret_ip;
         }
 
-        let stack_backtrack_index = self.get_stack_backtrack_index(stack_start);
-        self.stack.shrink(stack_backtrack_index);
-
-        // Finally, push the return value onto the stack.
-        self.stack.push(ret)
-            .map_err(|e| self.exception(e))?;
-
-        Ok(())
+        Ok(ret)
     }
 
     /// Gets the return address for a function invocation during the current state of the vm.
@@ -354,17 +387,20 @@ impl Vm {
         let ret = self.stack.pop()
             .map_err(|e| self.exception(e))?;
 
-        let frame = self.call_stack.pop()
-            .expect("call stack cannot be empty when returning from a user function");
+        // Returning from within a temporary frame (e.g. from inside a loop) returns from the function
+        // the frame belongs to, so get rid of any temporary frames on top of the function's frame.
+        while let Some(Frame { kind: FrameKind::Temp { .. }, .. }) = self.call_stack.last() {
+            self.call_stack.pop();
+        }
 
-        assert_matches!(
-            frame.kind,
-            FrameKind::UserFunction | FrameKind::Temp { .. },
-            "top-most stack frame has to be a user function user temporary frame when returning from a user function"
-        );
+        // The top-most frame has to be a user function frame when returning from a user function.
+        let frame = match self.call_stack.pop() {
+            Some(frame @ Frame { kind: FrameKind::UserFunction, .. }) => frame,
+            _ => return Err(self.exception(Exception::UnbalancedTempFrame)),
+        };
 
         let stack_start = frame.stack_start;
-        let stack_backtrack_index = self.get_stack_backtrack_index(stack_start);
+        let stack_backtrack_index = self.get_stack_backtrack_index(stack_start)?;
         self.stack.shrink(stack_backtrack_index);
 
         // If the frame has no assigned return address
@@ -382,7 +418,7 @@ impl Vm {
     }
 
     /// Gets the index on the stack to backtrack to when returning from a function.
-    fn get_stack_backtrack_index(&self, stack_start: usize) -> usize {
+    fn get_stack_backtrack_index(&self, stack_start: usize) -> Result<usize> {
 
         // When calling a function from a user function, the stack will approximately look like this:
         // 
@@ -397,33 +433,30 @@ impl Vm {
         // However, if the function was called from a native function or the execution root,
         // there won't be a closure there, so we don't want to shrink by the additional index backwards.
         
-        match self.get_top_non_temp_frame() {
-            Some(Frame { kind: FrameKind::NativeFunction, .. }) | None => stack_start,
-            _ => stack_start - 1
+        match self.get_top_non_temp_frame()? {
+            Some(Frame { kind: FrameKind::NativeFunction, .. }) | None => Ok(stack_start),
+            _ => stack_start.checked_sub(1)
+                .ok_or_else(|| self.exception(Exception::StackUnderflow))
         }
     }
 
     /// Gets the top-most stack frame off of the call stack which is not a temporary frame.
-    fn get_top_non_temp_frame(&self) -> Option<&Frame> {
+    fn get_top_non_temp_frame(&self) -> Result<Option<&Frame>> {
         match self.call_stack.last() {
-            Some(Frame { kind: FrameKind::Temp { parent_function_index }, .. }) => Some(
-                self.call_stack.get(*parent_function_index)
-                    .expect("parent function index of temporary stack frame should point to a valid stack frame")
-            ),
-            Some(frame) => Some(frame),
-            None => None,
+            Some(Frame { kind: FrameKind::Temp { parent_function_index }, .. }) => self.call_stack.get(*parent_function_index)
+                .map(Some)
+                .ok_or_else(|| self.exception(Exception::UnbalancedTempFrame)),
+            frame => Ok(frame),
         }
     }
 
     /// Enters a temporary stack frame.
     fn enter_temp_frame(&mut self) -> Result<()> {
-        let current_frame = self.call_stack.last()
-            .expect("call stack should not be empty while entering temporary stack frame");
-
-        let current_frame_index = match &current_frame.kind {
-            FrameKind::UserFunction => self.call_stack.len() - 1,
-            FrameKind::NativeFunction => panic!("top-most frame of call stack cannot be a native function frame while enter a temporary stack frame"),
-            FrameKind::Temp { parent_function_index } => *parent_function_index,
+        // Temporary frames can only be entered from within a user function.
+        let (current_frame, current_frame_index) = match self.call_stack.last() {
+            Some(frame @ Frame { kind: FrameKind::UserFunction, .. }) => (frame, self.call_stack.len() - 1),
+            Some(frame @ Frame { kind: FrameKind::Temp { parent_function_index }, .. }) => (frame, *parent_function_index),
+            _ => return Err(self.exception(Exception::UnbalancedTempFrame)),
         };
 
         let stack_start = self.stack.head();
@@ -434,7 +467,7 @@ impl Vm {
             .. *current_frame
         };
 
-        self.call_stack.push_within_capacity(frame)
+        self.call_stack.push_within_capacity(frame).map(|_| ())
             .map_err(|_| self.exception(Exception::CallStackOverflow))?;
 
         Ok(())
@@ -442,29 +475,26 @@ impl Vm {
 
     /// Exits a temporary stack frame.
     fn exit_temp_frame(&mut self) -> Result<()> {
-        let current_frame = self.call_stack.pop()
-            .expect("call stack should not be empty while exiting temporary stack frame");
-
-        assert_matches!(
-            current_frame.kind,
-            FrameKind::Temp { .. },
-            "top-most stack frame while exiting a temporary stack frame should be a temporary stack frame"
-        );
+        let stack_start = match self.call_stack.last() {
+            Some(Frame { kind: FrameKind::Temp { .. }, stack_start, .. }) => *stack_start,
+            _ => return Err(self.exception(Exception::UnbalancedTempFrame))
+        };
 
-        self.stack.shrink(current_frame.stack_start);
+        self.call_stack.pop();
+        self.stack.shrink(stack_start);
 
         Ok(())
     }
 
-    fn get_variable_stack_index(&self, variable_index: usize) -> usize {
-        let frame = self.get_top_non_temp_frame()
-            .expect("top-most stack frame should not be empty when reading a variable");
+    fn get_variable_stack_index(&self, variable_index: usize) -> Result<usize> {
+        let frame = self.get_top_non_temp_frame()?
+            .ok_or_else(|| self.exception(Exception::InvalidVariable(variable_index)))?;
 
-        frame.stack_start + variable_index
+        Ok(frame.stack_start + variable_index)
     }
 
     fn read_variable(&self, variable_index: usize) -> Result<Value> {
-        let stack_index = self.get_variable_stack_index(variable_index);
+        let stack_index = self.get_variable_stack_index(variable_index)?;
 
         let val = self.stack.get(stack_index)
             .ok_or_else(|| self.exception(Exception::InvalidVariable(variable_index)))?;
@@ -473,7 +503,7 @@ impl Vm {
     }
 
     fn write_variable(&mut self, variable_index: usize, value: Value) -> Result<()> {
-        let stack_index = self.get_variable_stack_index(variable_index);
+        let stack_index = self.get_variable_stack_index(variable_index)?;
 
         let stack_value = match self.stack.get_mut(stack_index) {
             Some(x) => x,
@@ -493,6 +523,8 @@ impl Vm {
         while !self.call_stack.is_empty() {
             self.trace_ip = self.ip;
 
+            self.consume_fuel(1)?;
+
             // Todo: only do this when a breakpoint is reached.
             if let Some(debugger) = &mut self.debugger {
                 // Break for the debugger and allow it to inspect the VM's state.
@@ -501,8 +533,9 @@ impl Vm {
                     consts: &self.consts,
                     stack: &self.stack,
                     heap: &self.heap,
+                    shapes: &self.shapes,
                     call_stack: &self.call_stack,
-                    ip: self.ip
+                    ip: self.consts.decoded.address_of(self.ip)
                 };
 
                 debugger.debug_break(inspection);
@@ -538,44 +571,6 @@ impl Vm {
         Err(self.exception(Exception::NoReturn))
     }
 
-    /// Reads a [`u8`] and progresses the instruction pointer by 1.
-    fn read_u8(&mut self) -> Result<u8> {
-        let byte = self.consts.code.get(self.ip)
-            .ok_or_else(|| self.exception(Exception::Overrun))?;
-
-        self.ip += 1;
-
-        Ok(*byte)
-    }
-
-    /// Reads a [`u32`] and progresses the instruction pointer by 4.
-    fn read_u32(&mut self) -> Result<u32> {
-        let bytes: [u8; 4] = self.consts.code.get(self.ip..(self.ip + 4))
-            .ok_or_else(|| self.exception(Exception::Overrun))?
-            .try_into()
-            .unwrap(); // safe because if `get` returns `Some`,
-                       // the slice will always be 4 elements long
-        
-        self.ip += 4;
-        
-        let val = u32::from_be_bytes(bytes);
-        Ok(val)
-    }
-
-    /// Reads a [`f64`] and progresses the instruction pointer by 8.
-    fn read_f64(&mut self) -> Result<f64> {
-        let bytes: [u8; 8] = self.consts.code.get(self.ip..(self.ip + 8))
-            .ok_or_else(|| self.exception(Exception::Overrun))?
-            .try_into()
-            .unwrap(); // safe because if `get` returns `Some`,
-                       // the slice will always be 8 elements long
-        
-        self.ip += 8;
-        
-        let val = f64::from_be_bytes(bytes);
-        Ok(val)
-    }
-
     /// Pops a value off the stack.
     fn pop(&mut self) -> Result<Value> {
         self.stack.pop()
@@ -644,35 +639,119 @@ impl Vm {
         Ok(())
     }
 
+    /// Pops two values off the stack, performs an arithmetic operation on them
+    /// using [`Vm::arithmetic`], then pushes the result back onto the stack.
+    fn arithmetic_op(
+        &mut self,
+        int_op: impl FnOnce(i64, i64) -> Option<i64>,
+        float_op: impl FnOnce(f64, f64) -> f64
+    ) -> Result<()> {
+        let a = self.pop()?;
+        let b = self.pop()?;
+
+        let val = self.arithmetic(a, b, int_op, float_op)?;
+
+        self.push(val)?;
+
+        Ok(())
+    }
+
+    /// Reads the value of a field of an object,
+    /// using and updating the field cache of the instruction at an index.
+    fn read_field(&mut self, obj: Value, name: Value, site: usize) -> Result<Value> {
+        let name = self.to_str(name)?;
+        let (obj, _) = self.coerce_to_object(obj)?;
+
+        let (val, cache) = match &obj.fields {
+            Fields::Shaped { shape, values } => {
+                let shape_id = *shape;
+                let shape = self.shapes.get(shape_id);
+
+                match self.field_caches[site] {
+                    Some(cache) if cache.shape == shape_id && shape.names()[cache.slot as usize] == *name =>
+                        (Some(values[cache.slot as usize]), None),
+
+                    _ => match shape.get(&name) {
+                        Some(field) => (
+                            Some(values[field.slot as usize]),
+                            Some(FieldCache { shape: shape_id, slot: field.slot })
+                        ),
+                        None => (None, None),
+                    },
+                }
+            },
+            Fields::Dictionary(fields) => (fields.get(name.as_ref()).map(|field| field.val), None),
+        };
+
+        let Some(val) = val else {
+            return Err(self.exception(Exception::MissingField(name.into_owned())));
+        };
+
+        if cache.is_some() {
+            self.field_caches[site] = cache;
+        }
+
+        Ok(val)
+    }
+
+    /// Pops two values off the stack, compares them using [`Vm::compare`],
+    /// then pushes the result of a predicate on the ordering back onto the stack.
+    /// 
+    /// Incomparable values (i.e. NaN) always produce `false`.
+    fn comparison_op(&mut self, pred: impl FnOnce(Ordering) -> bool) -> Result<()> {
+        let a = self.pop()?;
+        let b = self.pop()?;
+
+        let val = match self.compare(b, a)? {
+            Some(ord) => pred(ord),
+            None => false,
+        };
+
+        self.push(Value::Bool(val))?;
+
+        Ok(())
+    }
+
+    /// Pops a shift amount and a value off the stack, coerces both into integers,
+    /// shifts the value using a shift operation, then pushes the result back onto the stack.
+    fn shift_op(&mut self, op: impl FnOnce(i64, u32) -> i64) -> Result<()> {
+        let shift = self.pop_val_as(Self::coerce_to_integer)?;
+        let x = self.pop_val_as(Self::coerce_to_integer)?;
+
+        let shift = match u32::try_from(shift) {
+            Ok(shift) if shift < i64::BITS => shift,
+            _ => return Err(self.exception(Exception::InvalidShift(shift))),
+        };
+
+        self.push(Value::Integer(op(x, shift)))?;
+
+        Ok(())
+    }
+
     /// Interprets the current instruction pointed to by `ip`.
     /// Returns the new value the instruction pointer should progress to.
     fn interpret_instruction(&mut self) -> Result<InterpretControlFlow> {
-        let opcode = self.consts.code.get(self.ip)
+        let instruction = *self.consts.decoded.instructions.get(self.ip)
             .ok_or_else(|| self.exception(Exception::Overrun))?;
 
         self.ip += 1;
 
-        match *opcode {
-            opcode::NO_OP => {},
+        match instruction {
+            Instruction::NoOp => {},
 
-            opcode::JUMP => {
-                let address = self.read_u32()? as usize;
-                self.ip = address;
+            Instruction::Jump(target) => {
+                self.ip = target;
             },
 
-            opcode::JUMP_IF => {
-                let address = self.read_u32()? as usize;
-
+            Instruction::JumpIf(target) => {
                 let val = self.pop_val_as(Self::coerce_to_bool)?;
 
                 if val {
-                    self.ip = address;
+                    self.ip = target;
                 }
             },
 
-            opcode::CALL => {
-                let arg_count = self.read_u32()?;
-
+            Instruction::Call(arg_count) => {
                 // When calling a function from a user function, the stack will approximately look like this:
                 // 
                 // [ ..., closure, arg1, arg2, arg3, ... ]
@@ -682,7 +761,8 @@ impl Vm {
                 // The index on the stack where the closure to call is located at will therefore be
                 // the current stack head - the amount of arguments - 1.
 
-                let function_stack_index = self.stack.head() - arg_count as usize - 1;
+                let function_stack_index = self.stack.head().checked_sub(arg_count as usize + 1)
+                    .ok_or_else(|| self.exception(Exception::StackUnderflow))?;
 
                 let val = self.stack.get(function_stack_index)
                     .ok_or_else(|| self.exception(Exception::StackUnderflow))?;
@@ -691,105 +771,91 @@ impl Vm {
                 return Ok(InterpretControlFlow::Call { closure, arg_count });
             },
 
-            opcode::RET => {
+            Instruction::Ret => {
                 return Ok(InterpretControlFlow::Return);
             },
 
-            opcode::ENTER_TEMP_FRAME => {
+            Instruction::EnterTempFrame => {
                 self.enter_temp_frame()?;
             },
 
-            opcode::EXIT_TEMP_FRAME => {
+            Instruction::ExitTempFrame => {
                 self.exit_temp_frame()?;
             },
 
-            opcode::PUSH_FLOAT => {
-                let val = self.read_f64()?;
-
+            Instruction::PushFloat(val) => {
                 self.push(Value::Number(val))?;
             },
 
-            opcode::PUSH_BOOL => {
-                let bool = self.read_u8()? != 0;
+            Instruction::PushInt(val) => {
+                self.push(Value::Integer(val))?;
+            },
 
+            Instruction::PushBool(bool) => {
                 self.push(Value::Bool(bool))?;
             },
 
-            opcode::PUSH_FUNC => {
-                let id = FuncId(self.read_u32()?);
+            Instruction::PushFunc(id) => {
+                self.push(Value::Function(id.into()))?;
+            },
+
+            Instruction::PushClosure(id) => {
                 let index = id.decode();
-                
-                let captures = if id.is_native() {
-                    None
-                } else {
-                    let function = self.consts.functions.get(index as usize)
-                        .ok_or_else(|| self.exception(Exception::InvalidUserFunction(index)))?;
-                    
-                    // Save captured variables as a list.
-                    if !function.captures.is_empty() {
-                        let mut captures = Vec::with_capacity(function.captures.len());
-                        for capture_index in &function.captures {
-                            let val = self.read_variable(*capture_index as usize)?;
-                            captures.push(val);
-                        }
-
-                        let address = self.heap_alloc(HeapValue::List(List(captures)))?;
-
-                        Some(address)
-                    } else {
-                        None
-                    }
-                };
+
+                let function = self.consts.functions.get(index as usize)
+                    .ok_or_else(|| self.exception(Exception::InvalidUserFunction(index)))?;
+
+                // Save captured variables as a list.
+                let mut captures = Vec::with_capacity(function.captures.len());
+                for capture_index in &function.captures {
+                    let val = self.read_variable(*capture_index as usize)?;
+                    captures.push(val);
+                }
+
+                let address = self.heap_alloc(HeapValue::List(List(captures)))?;
 
                 let closure = Closure {
                     function: id,
-                    captures
+                    captures: Some(address)
                 };
 
                 self.push(Value::Function(closure))?;
             },
 
-            opcode::PUSH_NIL => {
+            Instruction::PushNil => {
                 self.push(Value::Nil)?;
             },
 
-            opcode::PUSH_STRING => {
-                let index = self.read_u32()? as usize;
-                
+            Instruction::PushString(index) => {
                 self.push(Value::InternedString(index))?;
             },
 
-            opcode::PUSH_OBJECT => {
-                let dynamic = self.read_u8()? != 0;
-
-                let object = Object {
-                    fields: HashMap::new(),
-                    dynamic
-                };
+            Instruction::PushObject(dynamic) => {
+                let object = Object::new(dynamic);
                 let adr = self.heap_alloc(HeapValue::Object(object))?;
 
                 self.push(Value::Object(adr))?;
             },
 
-            opcode::PUSH_LIST => {
+            Instruction::PushList => {
                 let list = List(Vec::new());
                 let adr = self.heap_alloc(HeapValue::List(list))?;
 
                 self.push(Value::Object(adr))?;
             },
 
-            opcode::POP => {
+            Instruction::Pop => {
                 self.pop()?;
             },
 
-            opcode::DUP => {
+            Instruction::Dup => {
                 let val = self.pop()?;
 
                 self.push(val)?;
                 self.push(val)?;
             },
 
-            opcode::SWAP => {
+            Instruction::Swap => {
                 let a = self.pop()?;
                 let b = self.pop()?;
 
@@ -797,29 +863,25 @@ impl Vm {
                 self.push(b)?;
             },
 
-            opcode::STORE_VAR => {
-                let var_index = self.read_u32()?;
-
+            Instruction::StoreVar(var_index) => {
                 let value = self.pop()?;
 
-                self.write_variable(var_index as usize, value)?;
+                self.write_variable(var_index, value)?;
             },
 
-            opcode::LOAD_VAR => {
-                let var_index = self.read_u32()?;
-
-                let value = self.read_variable(var_index as usize)?;
+            Instruction::LoadVar(var_index) => {
+                let value = self.read_variable(var_index)?;
                 let value = self.unbox(value)?;
 
                 self.push(value)?;
             },
 
-            opcode::STORE_VAR_BOXED => {
-                let var_index = self.read_u32()?;
-
+            Instruction::StoreVarBoxed(var_index) => {
+                // Boxes never contain other boxes, otherwise a box could end up containing itself.
                 let value = self.pop()?;
+                let value = self.unbox(value)?;
 
-                let var = self.read_variable(var_index as usize)?;
+                let var = self.read_variable(var_index)?;
 
                 if let Value::Object(heap_address) = var &&
                     let HeapValue::Box(boxed) = self.get_heap_value_mut(heap_address)?
@@ -829,39 +891,39 @@ impl Vm {
                 } else {
                     // Otherwise, box the value and write to the variable as normal.
                     let boxed = self.heap_alloc(HeapValue::Box(value))?;
-                    self.write_variable(var_index as usize, Value::Object(boxed))?;
+                    self.write_variable(var_index, Value::Object(boxed))?;
                 }
             },
 
-            opcode::ADD => {
-                self.binary_op(
-                    Self::coerce_to_number,
+            Instruction::Add => {
+                self.arithmetic_op(
+                    |a, b| a.checked_add(b),
                     |a, b| a + b
                 )?;
             },
 
-            opcode::SUB => {
-                self.binary_op(
-                    Self::coerce_to_number,
+            Instruction::Sub => {
+                self.arithmetic_op(
+                    |a, b| b.checked_sub(a),
                     |a, b| b - a
                 )?;
             },
 
-            opcode::MULT => {
-                self.binary_op(
-                    Self::coerce_to_number,
+            Instruction::Mult => {
+                self.arithmetic_op(
+                    |a, b| a.checked_mul(b),
                     |a, b| a * b
                 )?;
             },
 
-            opcode::DIV => {
+            Instruction::Div => {
                 self.binary_op(
                     Self::coerce_to_number,
                     |a, b| b / a
                 )?;
             },
 
-            opcode::EQUAL => {
+            Instruction::Equal => {
                 let a = self.pop()?;
                 let b = self.pop()?;
 
@@ -870,46 +932,48 @@ impl Vm {
                 self.push(Value::Bool(val))?;
             },
 
-            opcode::LESS_THAN => {
-                self.binary_op(
-                    Self::coerce_to_number,
-                    |a, b| b < a
-                )?;
+            Instruction::LessThan => {
+                self.comparison_op(Ordering::is_lt)?;
             },
 
-            opcode::NOT => {
+            Instruction::Not => {
                 self.unary_op(
                     Self::coerce_to_bool,
                     |x| !x
                 )?;
             },
 
-            opcode::AND => {
+            Instruction::And => {
                 self.binary_op(
                     Self::coerce_to_bool,
                     |a, b| a && b
                 )?;
             },
 
-            opcode::OR => {
+            Instruction::Or => {
                 self.binary_op(
                     Self::coerce_to_bool,
                     |a, b| a || b
                 )?;
             },
 
-            opcode::GREATER_THAN => {
-                self.binary_op(
-                    Self::coerce_to_number,
-                    |a, b| b > a
-                )?;
+            Instruction::GreaterThan => {
+                self.comparison_op(Ordering::is_gt)?;
             },
 
-            opcode::CONCAT => {
-                let other = self.pop_val_as(Self::to_string)?;
-                let mut str = self.pop_val_as(Self::to_string)?;
+            Instruction::Concat => {
+                let b = self.pop()?;
+                let a = self.pop()?;
 
-                str.push_str(other.as_str());
+                let str = {
+                    let b = self.to_str(b)?;
+                    let a = self.to_str(a)?;
+
+                    let mut str = String::with_capacity(a.len() + b.len());
+                    str.push_str(&a);
+                    str.push_str(&b);
+                    str
+                };
 
                 // Todo: garbage collection is never actually run, so this leaks memory currently.
                 let adr = self.heap_alloc(HeapValue::String(str))?;
@@ -917,7 +981,7 @@ impl Vm {
                 self.push(Value::Object(adr))?;
             },
 
-            opcode::TO_STRING => {
+            Instruction::ToString => {
                 let val = self.pop()?;
 
                 let str = self.to_string(val)?;
@@ -928,125 +992,230 @@ impl Vm {
                 self.push(Value::Object(adr))?;
             },
 
-            opcode::ADD_FIELD => {
-                let mutable = self.read_u8()? != 0;
-
+            Instruction::AddField(mutable) => {
                 let val = self.pop()?;
                 let name = self.pop_val_as(Self::to_string)?;
-                let (obj, _) = self.pop_val_as_mut(Self::coerce_to_object_mut)?;
-
-                let field_count = obj.fields.len() as u32;
-                obj.fields.insert(name, Field {
-                    val,
-                    mutable,
-                    index: field_count
-                });
+                let (obj, shapes) = self.pop_val_as_mut(Self::coerce_to_object_with_shapes)?;
+
+                obj.add_field(shapes, name, val, mutable);
             },
 
-            opcode::WRITE_FIELD => {
+            Instruction::WriteField => {
                 let val = self.pop()?;
                 let name = self.pop_val_as(Self::to_string)?;
-                let (obj, _) = self.pop_val_as_mut(Self::coerce_to_object_mut)?;
-
-                let dynamic = obj.dynamic;
-                
-                match obj.fields.get_mut(&name) {
-                    Some(field) => {
-                        if field.mutable {
-                            // Override value.
-                            field.val = val;
-                        } else {
-                            // Cannot write to immutable field.
-                            return Err(self.exception(Exception::WriteToImmutableField(name)));
-                        }
-                    },
-                    None => {
-                        if dynamic {
-                            // Writing to a dynamic object, insert a mutable field.
-                            let field_count = obj.fields.len() as u32;
-                            obj.fields.insert(name, Field {
-                                val,
-                                mutable: true,
-                                index: field_count
-                            });
-                        } else {
-                            // Missing field.
-                            return Err(self.exception(Exception::MissingField(name)));
-                        }
+                let site = self.trace_ip;
+                let cache = self.field_caches[site];
+
+                let (obj, shapes) = self.pop_val_as_mut(Self::coerce_to_object_with_shapes)?;
+
+                if let Fields::Shaped { shape, values } = &mut obj.fields &&
+                    let Some(cache) = cache &&
+                    cache.shape == *shape &&
+                    shapes.get(*shape).names()[cache.slot as usize] == name {
+                    // Only mutable fields are cached for writes.
+                    values[cache.slot as usize] = val;
+                } else {
+                    let cache = match &obj.fields {
+                        Fields::Shaped { shape, .. } => shapes.get(*shape)
+                            .get(&name)
+                            .filter(|field| field.mutable)
+                            .map(|field| FieldCache { shape: *shape, slot: field.slot }),
+                        Fields::Dictionary(_) => None,
+                    };
+
+                    let result = obj.write_field(shapes, name, val);
+                    result.map_err(|e| self.exception(e))?;
+
+                    if cache.is_some() {
+                        self.field_caches[site] = cache;
                     }
-                };
+                }
             },
 
-            opcode::READ_FIELD => {
-                let name = self.pop_val_as(Self::to_string)?;
-                let (obj, _) = self.pop_val_as(Self::coerce_to_object)?;
+            Instruction::ReadField => {
+                let name = self.pop()?;
+                let obj = self.pop()?;
 
-                match obj.fields.get(&name).copied() {
-                    Some(field) => {
-                        self.push(field.val)?;
-                    },
-                    None => {
-                        return Err(self.exception(Exception::MissingField(name)));
-                    },
-                };
+                let val = self.read_field(obj, name, self.trace_ip)?;
+
+                self.push(val)?;
             },
 
-            opcode::APPEND_ELEMENT => {
+            Instruction::AppendElement => {
                 let value = self.pop()?;
                 let (list, _) = self.pop_val_as_mut(Self::coerce_to_list_mut)?;
 
                 list.0.push(value);
             },
 
-            opcode::WRITE_ELEMENT => {
+            Instruction::WriteElement => {
                 let value = self.pop()?;
 
-                let raw_index = self.pop_val_as(Self::coerce_to_number)?;
-                let index = self.to_integer(raw_index)?;
+                let index = self.pop_val_as(Self::to_index)?;
 
                 let (list, _) = self.pop_val_as_mut(Self::coerce_to_list_mut)?;
 
                 let length = list.0.len();
 
                 if index < 0 {
-                    return Err(self.exception(Exception::OutOfBoundsIndex(raw_index, length)));
+                    return Err(self.exception(Exception::OutOfBoundsIndex(index, length)));
                 }
 
                 let index = index as usize;
 
                 if index >= length {
-                    return Err(self.exception(Exception::OutOfBoundsIndex(raw_index, length)));
+                    return Err(self.exception(Exception::OutOfBoundsIndex(index as i64, length)));
                 }
 
                 let element = list.0.get_mut(index).expect("index should be valid");
                 *element = value;
             },
 
-            opcode::READ_ELEMENT => {
-                let raw_index = self.pop_val_as(Self::coerce_to_number)?;
-                let index = self.to_integer(raw_index)?;
+            Instruction::ReadElement => {
+                let index = self.pop_val_as(Self::to_index)?;
 
                 let (list, _) = self.pop_val_as(Self::coerce_to_list)?;
 
                 let length = list.0.len();
 
                 if index < 0 {
-                    return Err(self.exception(Exception::OutOfBoundsIndex(raw_index, length)));
+                    return Err(self.exception(Exception::OutOfBoundsIndex(index, length)));
                 }
 
                 let index = index as usize;
 
                 if index >= length {
-                    return Err(self.exception(Exception::OutOfBoundsIndex(raw_index, length)));
+                    return Err(self.exception(Exception::OutOfBoundsIndex(index as i64, length)));
                 }
 
                 let element = *list.0.get(index).expect("index should be valid");
                 self.push(element)?;
             },
 
-            opcode::BOUNDARY => return Err(self.exception(Exception::Overrun)),
+            Instruction::IntDiv => {
+                let a = self.pop_val_as(Self::coerce_to_integer)?;
+                let b = self.pop_val_as(Self::coerce_to_integer)?;
+
+                if a == 0 {
+                    return Err(self.exception(Exception::DivisionByZero));
+                }
+
+                let val = b.checked_div(a)
+                    .ok_or_else(|| self.exception(Exception::IntegerOverflow))?;
+
+                self.push(Value::Integer(val))?;
+            },
+
+            Instruction::Mod => {
+                let a = self.pop()?;
+                let b = self.pop()?;
+
+                // The remainder has the same sign as the dividend for both integers and numbers.
+                // Integer remainder by zero is an exception, while number remainder by zero produces NaN.
+                if let (Value::Integer(0), Value::Integer(_)) = (a, b) {
+                    return Err(self.exception(Exception::DivisionByZero));
+                }
+
+                // i64::MIN % -1 overflows in the intermediate division despite the result (0) being representable.
+                let val = self.arithmetic(
+                    a,
+                    b,
+                    |a, b| Some(b.wrapping_rem(a)),
+                    |a, b| b % a
+                )?;
+
+                self.push(val)?;
+            },
+
+            Instruction::Neg => {
+                let val = match self.pop()? {
+                    Value::Integer(x) => x.checked_neg()
+                        .map(Value::Integer)
+                        .ok_or_else(|| self.exception(Exception::IntegerOverflow))?,
+                    val => Value::Number(-self.coerce_to_number(val)?),
+                };
+
+                self.push(val)?;
+            },
+
+            Instruction::Pow => {
+                let a = self.pop()?;
+                let b = self.pop()?;
+
+                let val = self.pow(b, a)?;
+
+                self.push(val)?;
+            },
+
+            Instruction::LessThanOrEqual => {
+                self.comparison_op(Ordering::is_le)?;
+            },
+
+            Instruction::GreaterThanOrEqual => {
+                self.comparison_op(Ordering::is_ge)?;
+            },
+
+            Instruction::NotEqual => {
+                let a = self.pop()?;
+                let b = self.pop()?;
+
+                let val = !self.equal(a, b)?;
+
+                self.push(Value::Bool(val))?;
+            },
+
+            Instruction::BitAnd => {
+                self.binary_op(
+                    Self::coerce_to_integer,
+                    |a, b| a & b
+                )?;
+            },
+
+            Instruction::BitOr => {
+                self.binary_op(
+                    Self::coerce_to_integer,
+                    |a, b| a | b
+                )?;
+            },
+
+            Instruction::BitXor => {
+                self.binary_op(
+                    Self::coerce_to_integer,
+                    |a, b| a ^ b
+                )?;
+            },
+
+            Instruction::BitNot => {
+                self.unary_op(
+                    Self::coerce_to_integer,
+                    |x| !x
+                )?;
+            },
+
+            Instruction::ShiftLeft => {
+                // Bits shifted out to the left are discarded rather than treated as overflow.
+                self.shift_op(|x, shift| x << shift)?;
+            },
+
+            Instruction::ShiftRight => {
+                // Arithmetic shift, fills in with the sign bit.
+                self.shift_op(|x, shift| x >> shift)?;
+            },
+
+            Instruction::ShiftRightLogical => {
+                // Logical shift, fills in with zeroes.
+                self.shift_op(|x, shift| ((x as u64) >> shift) as i64)?;
+            },
+
+            Instruction::Boundary => return Err(self.exception(Exception::Overrun)),
+
+            Instruction::Unknown(opcode) => return Err(self.exception(Exception::UnknownOpcode(opcode))),
+
+            Instruction::Truncated => return Err(self.exception(Exception::Overrun)),
+
+            Instruction::InvalidFunction(index) => return Err(self.exception(Exception::InvalidUserFunction(index))),
 
-            _ => return Err(self.exception(Exception::UnknownOpcode(*opcode)))
+            Instruction::InvalidString(index) => return Err(self.exception(Exception::InvalidString(index))),
         }
         
         Ok(InterpretControlFlow::Continue)
@@ -0,0 +1,119 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// A pseudo-random number generator using the xoshiro256** algorithm.
+///
+/// The generator isn't cryptographically secure, but the same seed always produces the same sequence of values
+/// regardless of platform, which makes runs reproducible.
+pub struct Random {
+    state: [u64; 4],
+}
+
+impl Random {
+    /// Creates a new generator from a seed.
+    pub fn from_seed(seed: u64) -> Self {
+        // The state is expanded from the seed using SplitMix64, which guarantees that it isn't all zeros.
+        let mut seed = seed;
+        let mut split_mix = || {
+            seed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+
+        Self {
+            state: [split_mix(), split_mix(), split_mix(), split_mix()],
+        }
+    }
+
+    /// Creates a new generator with a seed which is different on every run.
+    pub fn from_entropy() -> Self {
+        // The hash keys of the standard library are randomly generated by the operating system.
+        let seed = RandomState::new().build_hasher().finish();
+
+        Self::from_seed(seed)
+    }
+
+    /// Generates a uniformly distributed 64-bit integer.
+    pub fn next_u64(&mut self) -> u64 {
+        let [s0, s1, s2, s3] = &mut self.state;
+
+        let result = s1.wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = *s1 << 17;
+
+        *s2 ^= *s0;
+        *s3 ^= *s1;
+        *s1 ^= *s2;
+        *s0 ^= *s3;
+        *s2 ^= t;
+        *s3 = s3.rotate_left(45);
+
+        result
+    }
+
+    /// Generates a uniformly distributed number in the range `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        // The upper 53 bits fill the mantissa exactly.
+        (self.next_u64() >> 11) as f64 * (1. / (1u64 << 53) as f64)
+    }
+
+    /// Generates a uniformly distributed integer in the range `[0, bound)`. `bound` has to be greater than 0.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        // Values in the incomplete last cycle of `bound` are rejected to avoid biasing towards small values.
+        let zone = u64::MAX - u64::MAX % bound;
+
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return x % bound;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_u64_matches_the_reference_implementation() {
+        let mut random = Random { state: [1, 2, 3, 4] };
+
+        assert_eq!(random.next_u64(), 11520);
+        assert_eq!(random.next_u64(), 0);
+        assert_eq!(random.next_u64(), 1509978240);
+        assert_eq!(random.next_u64(), 1215971899390074240);
+    }
+
+    #[test]
+    fn seeds_are_expanded_with_split_mix() {
+        let random = Random::from_seed(0);
+
+        assert_eq!(random.state, [
+            0xE220_A839_7B1D_CDAF,
+            0x6E78_9E6A_A1B9_65F4,
+            0x06C4_5D18_8009_454F,
+            0xF88B_B8A8_724C_81EC,
+        ]);
+    }
+
+    #[test]
+    fn next_f64_is_below_one() {
+        let mut random = Random { state: [u64::MAX; 4] };
+
+        for _ in 0..100 {
+            let x = random.next_f64();
+            assert!((0.0..1.0).contains(&x));
+        }
+    }
+
+    #[test]
+    fn below_stays_below_the_bound() {
+        let mut random = Random::from_seed(42);
+
+        for bound in [1, 2, 3, 7, u64::MAX] {
+            assert!(random.below(bound) < bound);
+        }
+    }
+}
@@ -31,7 +31,7 @@ impl Stack {
     }
 
     pub fn push(&mut self, value: Value) -> Result<(), Exception> {
-        self.stack.push_within_capacity(value)
+        self.stack.push_within_capacity(value).map(|_| ())
             .map_err(|_| Exception::StackOverflow)
     }
 
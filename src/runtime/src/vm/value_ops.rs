@@ -1,13 +1,19 @@
 use std::borrow::Cow;
+use std::cmp::Ordering;
 
 use polonius_the_crab::{polonius, polonius_return};
 
-use crate::value::{Closure, List, Object, Type, Value};
+use crate::shape::Shapes;
+use crate::value::{Closure, Fields, List, Map, MapKey, Object, Type, Value};
 use crate::heap::{HeapAddress, HeapValue};
 use crate::exception::{Exception, FormattedException};
 
 use super::{Vm, Result};
 
+/// The maximum depth of nested values which can be compared or turned into strings,
+/// so that values which contain themselves can't recurse forever.
+pub(crate) const MAX_NESTING_DEPTH: usize = 256;
+
 impl Vm {
     /// Unboxes a value if it's a boxed value, and does nothing if it's not.
     /// 
@@ -29,6 +35,7 @@ impl Vm {
     pub fn get_type(&self, val: Value) -> Result<Type> {
         match val {
             Value::Number(_) => Ok(Type::Number),
+            Value::Integer(_) => Ok(Type::Integer),
             Value::Bool(_) => Ok(Type::Bool),
             Value::InternedString(_) => Ok(Type::String),
             Value::Function(_) => Ok(Type::Function),
@@ -36,6 +43,9 @@ impl Vm {
                 HeapValue::String(_) => Ok(Type::String),
                 HeapValue::List(_) => Ok(Type::List),
                 HeapValue::Object { .. } => Ok(Type::Object),
+                HeapValue::Map(_) => Ok(Type::Map),
+                HeapValue::Bytes(_) => Ok(Type::Bytes),
+                HeapValue::StringBuilder(_) => Ok(Type::StringBuilder),
                 HeapValue::Box(x) => self.get_type(*x),
             },
             Value::Nil => Ok(Type::Nil),
@@ -44,6 +54,20 @@ impl Vm {
 
     /// Checks whether two values are equal.
     pub fn equal(&self, a: Value, b: Value) -> Result<bool> {
+        self.equal_at(a, b, 0)
+    }
+
+    /// Checks whether two values nested at a specific depth are equal.
+    fn equal_at(&self, a: Value, b: Value, depth: usize) -> Result<bool> {
+        if depth > MAX_NESTING_DEPTH {
+            return Err(self.exception(Exception::TooDeeplyNested));
+        }
+
+        self.consume_fuel(1)?;
+
+        let a = self.unbox(a)?;
+        let b = self.unbox(b)?;
+
         // Try checking whether both values are string-like first.
         if let (Some(a), Some(b)) = (self.try_get_string(a)?, self.try_get_string(b)?) {
             return Ok(a == b);
@@ -52,6 +76,11 @@ impl Vm {
         match (a, b) {
             (Value::Number(a), Value::Number(b)) => Ok(a == b),
 
+            (Value::Integer(a), Value::Integer(b)) => Ok(a == b),
+
+            (Value::Integer(a), Value::Number(b)) | (Value::Number(b), Value::Integer(a)) =>
+                Ok(compare_integer_float(a, b) == Some(Ordering::Equal)),
+
             (Value::Bool(a), Value::Bool(b)) => Ok(a == b),
 
             (Value::InternedString(_), Value::InternedString(_)) => unreachable!(),
@@ -60,8 +89,12 @@ impl Vm {
 
             (Value::Object(a), Value::Object(b)) => match (self.get_heap_value(a)?, self.get_heap_value(b)?) {
                 (HeapValue::String(_), HeapValue::String(_)) => unreachable!(),
-                (HeapValue::List(a), HeapValue::List(b)) => self.list_equal(a, b),
-                (HeapValue::Object(a), HeapValue::Object(b)) => self.object_equal(a, b),
+                (HeapValue::List(a), HeapValue::List(b)) => self.list_equal(a, b, depth + 1),
+                (HeapValue::Object(a), HeapValue::Object(b)) => self.object_equal(a, b, depth + 1),
+                (HeapValue::Map(a), HeapValue::Map(b)) => self.map_equal(a, b, depth + 1),
+                (HeapValue::Bytes(a), HeapValue::Bytes(b)) => Ok(a == b),
+                // String builders are mutable, so they're compared by reference.
+                (HeapValue::StringBuilder(_), HeapValue::StringBuilder(_)) => Ok(a == b),
                 (HeapValue::Box(_), _) | (_, HeapValue::Box(_)) => unreachable!(),
                 _ => Ok(false)
             },
@@ -73,7 +106,7 @@ impl Vm {
     }
 
     /// Checks whether two lists are equal.
-    fn list_equal(&self, a: &List, b: &List) -> Result<bool> {
+    fn list_equal(&self, a: &List, b: &List, depth: usize) -> Result<bool> {
         let a = &a.0;
         let b = &b.0;
 
@@ -81,10 +114,8 @@ impl Vm {
             return Ok(false);
         }
 
-        // Todo: this doesn't account for recursive lists.
-
         for (a, b) in a.iter().zip(b.iter()) {
-            if !self.equal(*a, *b)? {
+            if !self.equal_at(*a, *b, depth)? {
                 return Ok(false);
             }
         }
@@ -93,23 +124,53 @@ impl Vm {
     }
 
     /// Checks whether two objects are equal.
-    fn object_equal(&self, a: &Object, b: &Object) -> Result<bool> {
-        let a = &a.fields;
-        let b = &b.fields;
-
+    fn object_equal(&self, a: &Object, b: &Object, depth: usize) -> Result<bool> {
         if a.len() != b.len() {
             return Ok(false);
         }
 
-        // Todo: this doesn't account for recursive objects.
+        // Objects with the same shape have the same fields in the same slots.
+        if let (Fields::Shaped { shape: shape_a, values: a }, Fields::Shaped { shape: shape_b, values: b }) = (&a.fields, &b.fields) &&
+            shape_a == shape_b {
+            for (a, b) in a.iter().zip(b.iter()) {
+                if !self.equal_at(*a, *b, depth)? {
+                    return Ok(false);
+                }
+            }
+
+            return Ok(true);
+        }
+
+        for (name, field_a) in a.fields(&self.shapes) {
+            let field_b = match b.get(&self.shapes, name) {
+                Some(x) => x,
+                None => return Ok(false)
+            };
+
+            if !self.equal_at(field_a.val, field_b.val, depth)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Checks whether two maps are equal.
+    fn map_equal(&self, a: &Map, b: &Map, depth: usize) -> Result<bool> {
+        let a = &a.entries;
+        let b = &b.entries;
 
-        for (name, field_a) in a.iter() {
-            let field_b = match b.get(name) {
+        if a.len() != b.len() {
+            return Ok(false);
+        }
+
+        for (key, entry_a) in a.iter() {
+            let entry_b = match b.get(key) {
                 Some(x) => x,
                 None => return Ok(false)
             };
 
-            if !self.equal(field_a.val, field_b.val)? {
+            if !self.equal_at(entry_a.val, entry_b.val, depth)? {
                 return Ok(false);
             }
         }
@@ -117,17 +178,70 @@ impl Vm {
         Ok(true)
     }
 
+    /// Turns a value into a key for a [`Map`], interning the value if it's a string.
+    pub fn to_map_key(&mut self, val: Value) -> Result<MapKey> {
+        let val = self.unbox(val)?;
+
+        if let Some(key) = self.find_map_key(val)? {
+            return Ok(key);
+        }
+
+        // The value has to be a string which hasn't been interned yet.
+        let str = self.try_get_string(val)?
+            .expect("only strings which haven't been interned have no map key")
+            .to_owned();
+
+        Ok(MapKey::String(self.interner.intern(&str)))
+    }
+
+    /// Turns a value into a key for a [`Map`] without interning it.
+    ///
+    /// Returns [`None`] if the value is a string which isn't interned,
+    /// since such a string cannot be the key of any map.
+    pub fn find_map_key(&self, val: Value) -> Result<Option<MapKey>> {
+        let val = self.unbox(val)?;
+
+        if let Value::InternedString(index) = val {
+            return self.interner.lookup_const(index)
+                .map(|str| Some(MapKey::String(str)))
+                .ok_or_else(|| self.exception(Exception::InvalidString(index)));
+        }
+
+        if let Some(str) = self.try_get_string(val)? {
+            return Ok(self.interner.lookup(str).map(MapKey::String));
+        }
+
+        let key = match val {
+            Value::Number(x) if x.is_nan() => return Err(self.exception(Exception::InvalidMapKey(
+                "NaN".into()
+            ))),
+            Value::Number(x) => match float_to_integer(x) {
+                // Integral numbers are equal to integers with the same value, so they have to be the same key.
+                // This also takes care of positive and negative zero being the same key.
+                Some(x) => MapKey::Integer(x),
+                None => MapKey::Number(x.to_bits()),
+            },
+            Value::Integer(x) => MapKey::Integer(x),
+            Value::Bool(x) => MapKey::Bool(x),
+            Value::Function(closure) => MapKey::Function(closure),
+            Value::Object(heap_address) => MapKey::Reference(heap_address),
+            Value::Nil => MapKey::Nil,
+            Value::InternedString(_) => unreachable!(),
+        };
+
+        Ok(Some(key))
+    }
+
     /// Tries to get a string from a value without performing any coercion.
-    pub fn try_get_string(&self, val: Value) -> Result<Option<String>> {
+    pub fn try_get_string(&self, val: Value) -> Result<Option<&str>> {
         match val {
             Value::InternedString(index) =>
                 self.consts.strings.get(index)
-                    .cloned()
-                    .ok_or_else(|| self.exception(Exception::InvalidString(index)))
-                    .map(Some),
+                    .map(|str| Some(str.as_str()))
+                    .ok_or_else(|| self.exception(Exception::InvalidString(index))),
             
             Value::Object(heap_addresss) => match self.get_heap_value(heap_addresss)? {
-                HeapValue::String(str) => Ok(Some(str.clone())),
+                HeapValue::String(str) => Ok(Some(str.as_str())),
                 HeapValue::Box(x) => self.try_get_string(*x),
                 _ => Ok(None)
             },
@@ -138,17 +252,36 @@ impl Vm {
 
     /// Turns a value into a string representation.
     pub fn to_string(&self, val: Value) -> Result<String> {
+        self.to_str(val).map(Cow::into_owned)
+    }
+
+    /// Turns a value into a string representation,
+    /// borrowing the string instead of cloning it if the value is a string.
+    pub fn to_str(&self, val: Value) -> Result<Cow<'_, str>> {
+        self.to_str_at(val, 0)
+    }
+
+    /// Turns a value nested at a specific depth into a string representation.
+    fn to_str_at(&self, val: Value, depth: usize) -> Result<Cow<'_, str>> {
+        if depth > MAX_NESTING_DEPTH {
+            return Err(self.exception(Exception::TooDeeplyNested));
+        }
+
+        self.consume_fuel(1)?;
+
         match val {
-            Value::Number(x) => Ok(x.to_string()),
+            Value::Number(x) => Ok(Cow::Owned(x.to_string())),
+
+            Value::Integer(x) => Ok(Cow::Owned(x.to_string())),
 
             Value::Bool(x) => if x {
-                Ok("true".to_string())
+                Ok(Cow::Borrowed("true"))
             } else {
-                Ok("false".to_string())
+                Ok(Cow::Borrowed("false"))
             },
 
             Value::InternedString(index) => self.consts.strings.get(index)
-                .cloned()
+                .map(|str| Cow::Borrowed(str.as_str()))
                 .ok_or_else(|| self.exception(Exception::InvalidString(index))),
             
             Value::Function(closure) => {
@@ -156,21 +289,20 @@ impl Vm {
                 let name = if closure.function.is_native() {
                     self.consts.native_functions.get(&id)
                         .ok_or_else(|| self.exception(Exception::InvalidNativeFunction(id)))?
-                        .name.clone()
+                        .name.as_str()
                 } else {
                     let name_index = self.consts.functions.get(id as usize)
                         .ok_or_else(|| self.exception(Exception::InvalidUserFunction(id)))?
                         .name_index as usize;
 
                     self.consts.strings.get(name_index)
-                        .cloned()
                         .ok_or_else(|| self.exception(Exception::InvalidString(name_index)))?
                 };
-                Ok(name)
+                Ok(Cow::Borrowed(name))
             },
 
             Value::Object(heap_address) => match self.get_heap_value(heap_address)? {
-                HeapValue::String(str) => Ok(str.clone()),
+                HeapValue::String(str) => Ok(Cow::Borrowed(str.as_str())),
 
                 HeapValue::List(list) => {
                     let mut str = String::new();
@@ -185,36 +317,33 @@ impl Vm {
                             first = false;
                         }
 
-                        let element_str = self.to_string(*element)?;
+                        let element_str = self.to_str_at(*element, depth + 1)?;
+                        self.consume_fuel(element_str.len())?;
                         str.push_str(&element_str);
                     }
 
                     str.push(']');
 
-                    Ok(str)
+                    Ok(Cow::Owned(str))
                 },
 
-                HeapValue::Object(Object { fields, dynamic }) => {
+                HeapValue::Object(obj) => {
                     let mut str = String::new();
                     
-                    if *dynamic {
+                    if obj.dynamic {
                         str.push_str("dyn ");
                     }
                     str.push('{');
 
-                    let mut fields = fields.iter().collect::<Vec<_>>();
-                    fields.sort_by_key(|(_, field)| field.index);
-
                     let mut i = 0;
-                    for (field_name, field) in fields {
+                    for (field_name, field) in obj.fields(&self.shapes) {
 
                         if i >= 1 {
                             str.push(',');
                         }
 
-                        // Todo: this doesn't account for recursive objects.
-                        
-                        let value_str = self.to_string(field.val)?;
+                        let value_str = self.to_str_at(field.val, depth + 1)?;
+                        self.consume_fuel(field_name.len() + value_str.len())?;
                         str.push_str(format!(" \"{}\": {}", field_name, value_str).as_str());
 
                         i += 1;
@@ -225,13 +354,67 @@ impl Vm {
                     }
                     str.push('}');
                     
-                    Ok(str)
+                    Ok(Cow::Owned(str))
+                },
+
+                HeapValue::Map(map) => {
+                    let mut str = String::new();
+
+                    str.push_str("map {");
+
+                    let mut i = 0;
+                    for entry in map.ordered() {
+                        if i >= 1 {
+                            str.push(',');
+                        }
+
+                        // Quote string keys to tell them apart from other kinds of keys.
+                        let key_str = match self.try_get_string(entry.key)? {
+                            Some(key) => format!("\"{key}\""),
+                            None => self.to_str_at(entry.key, depth + 1)?.into_owned(),
+                        };
+                        let value_str = self.to_str_at(entry.val, depth + 1)?;
+                        self.consume_fuel(key_str.len() + value_str.len())?;
+                        str.push_str(format!(" {}: {}", key_str, value_str).as_str());
+
+                        i += 1;
+                    }
+
+                    if i >= 1 {
+                        str.push(' ');
+                    }
+                    str.push('}');
+
+                    Ok(Cow::Owned(str))
+                },
+
+                HeapValue::Bytes(bytes) => {
+                    let mut str = String::new();
+
+                    str.push_str("bytes [");
+
+                    let mut first = true;
+                    for byte in bytes {
+                        if !first {
+                            str.push(' ');
+                        } else {
+                            first = false;
+                        }
+
+                        str.push_str(format!("{byte:02x}").as_str());
+                    }
+
+                    str.push(']');
+
+                    Ok(Cow::Owned(str))
                 },
 
-                HeapValue::Box(x) => self.to_string(*x),
+                HeapValue::StringBuilder(str) => Ok(Cow::Borrowed(str.as_str())),
+
+                HeapValue::Box(x) => self.to_str_at(*x, depth + 1),
             },
 
-            Value::Nil => Ok("()".to_string()),
+            Value::Nil => Ok(Cow::Borrowed("()")),
         }
     }
 
@@ -244,16 +427,150 @@ impl Vm {
         }
     }
 
+    /// Turns a value into an integer used to index a list.
+    /// 
+    /// Integers are used as-is, while other values are coerced into a number and truncated.
+    pub fn to_index(&self, val: Value) -> Result<i64> {
+        match val {
+            Value::Integer(x) => Ok(x),
+            _ => {
+                let x = self.coerce_to_number(val)?;
+                self.to_integer(x)
+            }
+        }
+    }
+
+    /// Performs an arithmetic operation on two values.
+    /// 
+    /// If both values are integers, the integer operation is used and an exception is returned if it overflows.
+    /// Otherwise, both values are coerced into numbers and the floating-point operation is used.
+    pub fn arithmetic(
+        &self,
+        a: Value,
+        b: Value,
+        int_op: impl FnOnce(i64, i64) -> Option<i64>,
+        float_op: impl FnOnce(f64, f64) -> f64
+    ) -> Result<Value> {
+        if let (Value::Integer(a), Value::Integer(b)) = (a, b) {
+            return int_op(a, b)
+                .map(Value::Integer)
+                .ok_or_else(|| self.exception(Exception::IntegerOverflow));
+        }
+
+        let a = self.coerce_to_number(a)?;
+        let b = self.coerce_to_number(b)?;
+
+        Ok(Value::Number(float_op(a, b)))
+    }
+
+    /// Raises a value to the power of another value.
+    ///
+    /// Integers raised to negative powers aren't integers,
+    /// so integer exponentiation is only used for integers raised to non-negative integer powers.
+    /// Otherwise, both values are coerced into numbers.
+    pub fn pow(&self, base: Value, exp: Value) -> Result<Value> {
+        match (base, exp) {
+            (Value::Integer(base), Value::Integer(exp)) if exp >= 0 =>
+                integer_pow(base, exp)
+                    .map(Value::Integer)
+                    .ok_or_else(|| self.exception(Exception::IntegerOverflow)),
+            _ => {
+                let base = self.coerce_to_number(base)?;
+                let exp = self.coerce_to_number(exp)?;
+                Ok(Value::Number(base.powf(exp)))
+            }
+        }
+    }
+
+    /// Compares two values numerically.
+    /// 
+    /// Integers and numbers are compared by their exact values,
+    /// so large integers are never rounded when compared to numbers.
+    /// Returns [`None`] if either value is NaN.
+    pub fn compare(&self, a: Value, b: Value) -> Result<Option<Ordering>> {
+        match (a, b) {
+            (Value::Integer(a), Value::Integer(b)) => Ok(Some(a.cmp(&b))),
+
+            (Value::Integer(a), _) => {
+                let b = self.coerce_to_number(b)?;
+                Ok(compare_integer_float(a, b))
+            },
+
+            (_, Value::Integer(b)) => {
+                let a = self.coerce_to_number(a)?;
+                Ok(compare_integer_float(b, a).map(Ordering::reverse))
+            },
+
+            _ => {
+                let a = self.coerce_to_number(a)?;
+                let b = self.coerce_to_number(b)?;
+                Ok(a.partial_cmp(&b))
+            }
+        }
+    }
+
+    /// Compares two values by the total order used for sorting.
+    ///
+    /// Integers and numbers are compared by their exact values, with NaN being greater than every other number.
+    /// Strings are compared lexicographically by Unicode scalar values, and are greater than every number.
+    /// Throws [`Exception::Incomparable`] if either value is neither a number nor a string.
+    pub fn compare_total(&self, a: Value, b: Value) -> Result<Ordering> {
+        self.consume_fuel(1)?;
+
+        let a = self.unbox(a)?;
+        let b = self.unbox(b)?;
+
+        match (self.try_get_string(a)?, self.try_get_string(b)?) {
+            (Some(a), Some(b)) => return Ok(a.cmp(b)),
+            (Some(_), None) if is_numeric(b) => return Ok(Ordering::Greater),
+            (None, Some(_)) if is_numeric(a) => return Ok(Ordering::Less),
+            _ => {}
+        }
+
+        match (a, b) {
+            (Value::Integer(a), Value::Integer(b)) => Ok(a.cmp(&b)),
+
+            (Value::Integer(a), Value::Number(b)) => Ok(compare_integer_float(a, b).unwrap_or(Ordering::Less)),
+
+            (Value::Number(a), Value::Integer(b)) =>
+                Ok(compare_integer_float(b, a).map_or(Ordering::Greater, Ordering::reverse)),
+
+            (Value::Number(a), Value::Number(b)) =>
+                Ok(a.partial_cmp(&b).unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()))),
+
+            _ => Err(self.exception(Exception::Incomparable(
+                self.get_value_type_string(a).into_owned(),
+                self.get_value_type_string(b).into_owned()
+            )))
+        }
+    }
+
     /// Tries to coerce a value into a number.
     pub fn coerce_to_number(&self, val: Value) -> Result<f64> {
         match val {
             Value::Number(x) => Ok(x),
+            Value::Integer(x) => Ok(x as f64),
             Value::Bool(x) => Ok(if x { 1. } else { 0. }),
             Value::Nil => Ok(0.),
             _ => Err(self.coercion_error(val, Type::Number)),
         }
     }
 
+    /// Tries to coerce a value into an integer.
+    /// 
+    /// Numbers are only coerced if they have an integral value which fits into an integer,
+    /// they are never rounded.
+    pub fn coerce_to_integer(&self, val: Value) -> Result<i64> {
+        match val {
+            Value::Integer(x) => Ok(x),
+            Value::Number(x) => float_to_integer(x)
+                .ok_or_else(|| self.exception(Exception::NonIntegral(x))),
+            Value::Bool(x) => Ok(if x { 1 } else { 0 }),
+            Value::Nil => Ok(0),
+            _ => Err(self.coercion_error(val, Type::Integer)),
+        }
+    }
+
     /// Tries to coerce a value into a boolean.
     pub fn coerce_to_bool(&self, val: Value) -> Result<bool> {
         match val {
@@ -331,17 +648,141 @@ impl Vm {
         Err(this.coercion_error(val, Type::Object))
     }
 
+    /// Tries to coerce a value into an object mutably,
+    /// alongside the shapes required to modify the fields of the object.
+    pub fn coerce_to_object_with_shapes(&mut self, val: Value) -> Result<(&mut Object, &mut Shapes)> {
+        let (_, adr) = self.coerce_to_object(val)?;
+
+        match self.heap.get_mut(adr) {
+            Ok(HeapValue::Object(obj)) => Ok((obj, &mut self.shapes)),
+            _ => unreachable!("the value was just coerced into an object"),
+        }
+    }
+
+    /// Tries to coerce a value into a map.
+    pub fn coerce_to_map(&self, val: Value) -> Result<(&Map, HeapAddress)> {
+        if let Value::Object(adr) = val &&
+            let HeapValue::Map(map) = self.get_heap_value(adr)?
+        {
+            return Ok((map, adr))
+        };
+
+        Err(self.coercion_error(val, Type::Map))
+    }
+
+    /// Tries to coerce a value into a map mutably.
+    pub fn coerce_to_map_mut(&mut self, val: Value) -> Result<(&mut Map, HeapAddress)> {
+        // See Vm::get_heap_value_mut for the reasoning behind using Polonius here.
+
+        let mut this = self;
+
+        polonius!(|this| -> Result<(&'polonius mut Map, HeapAddress)> {
+            if let Value::Object(adr) = val {
+                match this.get_heap_value_mut(adr) {
+                    Ok(x) => if let HeapValue::Map(map) = x {
+                        polonius_return!(Ok((map, adr)))
+                    },
+                    Err(e) => polonius_return!(Err(e)),
+                }
+            };
+        });
+
+        Err(this.coercion_error(val, Type::Map))
+    }
+
+    /// Tries to coerce a value into a byte buffer.
+    pub fn coerce_to_bytes(&self, val: Value) -> Result<(&Vec<u8>, HeapAddress)> {
+        if let Value::Object(adr) = val &&
+            let HeapValue::Bytes(bytes) = self.get_heap_value(adr)?
+        {
+            return Ok((bytes, adr))
+        };
+
+        Err(self.coercion_error(val, Type::Bytes))
+    }
+
+    /// Tries to coerce a value into a string builder.
+    pub fn coerce_to_string_builder(&self, val: Value) -> Result<(&String, HeapAddress)> {
+        if let Value::Object(adr) = val &&
+            let HeapValue::StringBuilder(str) = self.get_heap_value(adr)?
+        {
+            return Ok((str, adr))
+        };
+
+        Err(self.coercion_error(val, Type::StringBuilder))
+    }
+
+    /// Tries to coerce a value into a string builder mutably.
+    pub fn coerce_to_string_builder_mut(&mut self, val: Value) -> Result<(&mut String, HeapAddress)> {
+        // See Vm::get_heap_value_mut for the reasoning behind using Polonius here.
+
+        let mut this = self;
+
+        polonius!(|this| -> Result<(&'polonius mut String, HeapAddress)> {
+            if let Value::Object(adr) = val {
+                match this.get_heap_value_mut(adr) {
+                    Ok(x) => if let HeapValue::StringBuilder(str) = x {
+                        polonius_return!(Ok((str, adr)))
+                    },
+                    Err(e) => polonius_return!(Err(e)),
+                }
+            };
+        });
+
+        Err(this.coercion_error(val, Type::StringBuilder))
+    }
+
+    /// Gets a string passed as an argument to a native function without coercing it,
+    /// returning [`Exception::BadArgumentType`] if the argument isn't a string.
+    pub fn string_argument(&self, val: Value, function: &str, param: &str) -> Result<&str> {
+        match self.try_get_string(val)? {
+            Some(str) => Ok(str),
+            None => Err(self.argument_type_error(val, Type::String, function, param))
+        }
+    }
+
+    /// Gets an integer passed as an argument to a native function,
+    /// returning [`Exception::BadArgumentType`] if the argument isn't a number.
+    ///
+    /// Numbers are truncated the same way list indices are.
+    pub fn integer_argument(&self, val: Value, function: &str, param: &str) -> Result<i64> {
+        match self.unbox(val)? {
+            Value::Integer(x) => Ok(x),
+            Value::Number(x) => self.to_integer(x),
+            _ => Err(self.argument_type_error(val, Type::Number, function, param))
+        }
+    }
+
+    /// Constructs a formatted exception for an argument to a native function which has the wrong type.
+    pub(crate) fn argument_type_error(&self, val: Value, expected: Type, function: &str, param: &str) -> FormattedException {
+        let actual = match self.get_type(val) {
+            Ok(ty) => ty.to_string(),
+            Err(e) => return e
+        };
+
+        self.exception(Exception::BadArgumentType {
+            param: param.into(),
+            function: function.into(),
+            expected: expected.to_string(),
+            actual
+        })
+    }
+
     // Constructs a formatted coercion error exception.
-    fn coercion_error(&self, val: Value, ty: Type) -> FormattedException {
+    pub(crate) fn coercion_error(&self, val: Value, ty: Type) -> FormattedException {
         let val = self.get_value_type_string(val);
 
         let ty = match ty {
             Type::Number => "a number",
+            Type::Integer => "an integer",
             Type::Bool => "a boolean",
             Type::Function => "a function",
             Type::String => "a string",
             Type::List => "a list",
             Type::Object => "an object",
+            Type::Map => "a map",
+            Type::Bytes => "bytes",
+            Type::StringBuilder => "a string builder",
             Type::Nil => "()",
         };
 
@@ -352,6 +793,7 @@ impl Vm {
     fn get_value_type_string(&self, val: Value) -> Cow<'static, str> {
         match val {
             Value::Number(_) => Cow::Borrowed("a number"),
+            Value::Integer(_) => Cow::Borrowed("an integer"),
             Value::Bool(_) => Cow::Borrowed("a boolean"),
             Value::InternedString(_) => Cow::Borrowed("a string"),
             Value::Function(_) => Cow::Borrowed("a function"),
@@ -359,6 +801,9 @@ impl Vm {
                 Ok(HeapValue::String(_)) => Cow::Borrowed("a string"),
                 Ok(HeapValue::List(_)) => Cow::Borrowed("a list"),
                 Ok(HeapValue::Object { .. }) => Cow::Borrowed("an object"),
+                Ok(HeapValue::Map(_)) => Cow::Borrowed("a map"),
+                Ok(HeapValue::Bytes(_)) => Cow::Borrowed("bytes"),
+                Ok(HeapValue::StringBuilder(_)) => Cow::Borrowed("a string builder"),
                 Ok(HeapValue::Box(x)) => {
                     let val = self.get_value_type_string(*x);
                     Cow::Owned(format!("{val} (boxed)"))
@@ -369,3 +814,101 @@ impl Vm {
         }
     }
 }
+
+/// Checks whether a value is an integer or a number.
+fn is_numeric(val: Value) -> bool {
+    matches!(val, Value::Integer(_) | Value::Number(_))
+}
+
+/// Converts a floating-point number into an integer
+/// if it has an integral value which fits into an integer.
+pub fn float_to_integer(x: f64) -> Option<i64> {
+    // -2^63 is exactly representable as a float while 2^63 - 1 is not,
+    // so the upper bound has to be exclusive.
+    if x.fract() == 0. && x >= i64::MIN as f64 && x < -(i64::MIN as f64) {
+        Some(x as i64)
+    } else {
+        None
+    }
+}
+
+/// Raises an integer to a non-negative integer power, returning [`None`] if the result overflows.
+fn integer_pow(base: i64, exp: i64) -> Option<i64> {
+    match u32::try_from(exp) {
+        Ok(exp) => base.checked_pow(exp),
+        // Exponents this large only avoid overflowing for bases which don't grow in magnitude.
+        Err(_) => match base {
+            0 | 1 => Some(base),
+            -1 => Some(if exp % 2 == 0 { 1 } else { -1 }),
+            _ => None,
+        },
+    }
+}
+
+/// Compares an integer to a floating-point number by their exact values.
+fn compare_integer_float(a: i64, b: f64) -> Option<Ordering> {
+    // Rounding the integer to the nearest float preserves ordering, except when the rounded integer
+    // ends up equal to the float. In that case the float is integral and within the range of an i128,
+    // so both can be compared exactly as i128s.
+    match (a as f64).partial_cmp(&b)? {
+        Ordering::Equal => Some((a as i128).cmp(&(b as i128))),
+        ord => Some(ord),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn float_to_integer_converts_integral_numbers_in_range() {
+        assert_eq!(float_to_integer(-3.0), Some(-3));
+        assert_eq!(float_to_integer(-0.0), Some(0));
+        assert_eq!(float_to_integer(i64::MIN as f64), Some(i64::MIN));
+    }
+
+    #[test]
+    fn float_to_integer_rejects_fractions_and_numbers_out_of_range() {
+        assert_eq!(float_to_integer(0.5), None);
+        assert_eq!(float_to_integer(2f64.powi(63)), None);
+        assert_eq!(float_to_integer(f64::INFINITY), None);
+        assert_eq!(float_to_integer(f64::NAN), None);
+    }
+
+    #[test]
+    fn integer_pow_of_large_exponents() {
+        let exp = u32::MAX as i64 + 1;
+
+        assert_eq!(integer_pow(0, exp), Some(0));
+        assert_eq!(integer_pow(1, exp), Some(1));
+        assert_eq!(integer_pow(-1, exp), Some(1));
+        assert_eq!(integer_pow(-1, exp + 1), Some(-1));
+        assert_eq!(integer_pow(2, exp), None);
+    }
+
+    #[test]
+    fn integer_pow_overflows_at_the_bounds_of_integers() {
+        assert_eq!(integer_pow(-2, 63), Some(i64::MIN));
+        assert_eq!(integer_pow(2, 63), None);
+    }
+
+    #[test]
+    fn compare_integer_float_compares_exact_values() {
+        let two_pow_53 = 1i64 << 53;
+
+        // 2^53 + 1 rounds to 2^53 as a float.
+        assert_eq!(compare_integer_float(two_pow_53 + 1, two_pow_53 as f64), Some(Ordering::Greater));
+        assert_eq!(compare_integer_float(two_pow_53, two_pow_53 as f64), Some(Ordering::Equal));
+
+        // i64::MAX rounds to 2^63 as a float.
+        assert_eq!(compare_integer_float(i64::MAX, 2f64.powi(63)), Some(Ordering::Less));
+
+        assert_eq!(compare_integer_float(1, 1.5), Some(Ordering::Less));
+        assert_eq!(compare_integer_float(i64::MIN, f64::NEG_INFINITY), Some(Ordering::Greater));
+    }
+
+    #[test]
+    fn compare_integer_float_with_nan_is_unordered() {
+        assert_eq!(compare_integer_float(0, f64::NAN), None);
+    }
+}
@@ -0,0 +1,112 @@
+//! Tests of the edge cases of the arithmetic and comparison opcodes.
+
+mod common;
+
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+
+use common::{eval, natives};
+
+#[test]
+fn int_div_rounds_towards_zero() {
+    assert_eq!(eval(|p| { p.int(7).int(2).op(INT_DIV); }).unwrap(), "3");
+    assert_eq!(eval(|p| { p.int(-7).int(2).op(INT_DIV); }).unwrap(), "-3");
+    assert_eq!(eval(|p| { p.float(7.0).int(-2).op(INT_DIV); }).unwrap(), "-3");
+}
+
+#[test]
+fn int_div_by_zero_throws() {
+    assert!(matches!(eval(|p| { p.int(1).int(0).op(INT_DIV); }), Err(Exception::DivisionByZero)));
+    assert!(matches!(eval(|p| { p.int(0).int(0).op(INT_DIV); }), Err(Exception::DivisionByZero)));
+}
+
+#[test]
+fn int_div_of_min_by_minus_one_overflows() {
+    assert!(matches!(eval(|p| { p.int(i64::MIN).int(-1).op(INT_DIV); }), Err(Exception::IntegerOverflow)));
+}
+
+#[test]
+fn int_div_of_non_integral_numbers_throws() {
+    assert!(eval(|p| { p.float(7.5).int(2).op(INT_DIV); }).is_err());
+}
+
+#[test]
+fn remainder_has_the_sign_of_the_dividend() {
+    assert_eq!(eval(|p| { p.int(-7).int(3).op(MOD); }).unwrap(), "-1");
+    assert_eq!(eval(|p| { p.int(7).int(-3).op(MOD); }).unwrap(), "1");
+    assert_eq!(eval(|p| { p.float(-7.5).float(2.0).op(MOD); }).unwrap(), "-1.5");
+}
+
+#[test]
+fn remainder_of_min_by_minus_one_is_zero() {
+    assert_eq!(eval(|p| { p.int(i64::MIN).int(-1).op(MOD); }).unwrap(), "0");
+}
+
+#[test]
+fn integer_remainder_by_zero_throws() {
+    assert!(matches!(eval(|p| { p.int(7).int(0).op(MOD); }), Err(Exception::DivisionByZero)));
+}
+
+#[test]
+fn number_remainder_by_zero_is_nan() {
+    assert_eq!(eval(|p| { p.native(natives::IS_NAN).float(7.0).float(0.0).op(MOD).call(1); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.native(natives::IS_NAN).int(7).float(0.0).op(MOD).call(1); }).unwrap(), "true");
+}
+
+#[test]
+fn remainder_of_infinity_is_nan_and_by_infinity_is_the_dividend() {
+    assert_eq!(eval(|p| { p.native(natives::IS_NAN).float(f64::INFINITY).float(2.0).op(MOD).call(1); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.float(2.5).float(f64::INFINITY).op(MOD); }).unwrap(), "2.5");
+}
+
+#[test]
+fn negating_min_overflows() {
+    assert!(matches!(eval(|p| { p.int(i64::MIN).op(NEG); }), Err(Exception::IntegerOverflow)));
+    assert_eq!(eval(|p| { p.int(i64::MAX).op(NEG); }).unwrap(), (-i64::MAX).to_string());
+}
+
+#[test]
+fn integer_powers_with_non_negative_exponents_are_integers() {
+    assert_eq!(eval(|p| { p.int(3).int(4).op(POW); }).unwrap(), "81");
+    assert_eq!(eval(|p| { p.native(natives::IS_INTEGER).int(2).int(0).op(POW).call(1); }).unwrap(), "true");
+}
+
+#[test]
+fn overflowing_integer_powers_throw() {
+    assert!(matches!(eval(|p| { p.int(2).int(63).op(POW); }), Err(Exception::IntegerOverflow)));
+}
+
+#[test]
+fn negative_exponents_produce_numbers() {
+    assert_eq!(eval(|p| { p.int(2).int(-1).op(POW); }).unwrap(), "0.5");
+}
+
+#[test]
+fn powers_involving_nan() {
+    assert_eq!(eval(|p| { p.float(f64::NAN).int(0).op(POW); }).unwrap(), "1");
+    assert_eq!(eval(|p| { p.float(1.0).float(f64::NAN).op(POW); }).unwrap(), "1");
+    assert_eq!(eval(|p| { p.native(natives::IS_NAN).float(2.0).float(f64::NAN).op(POW).call(1); }).unwrap(), "true");
+}
+
+#[test]
+fn comparisons_with_nan_are_false() {
+    for opcode in [LESS_THAN, GREATER_THAN, LESS_THAN_OR_EQUAL, GREATER_THAN_OR_EQUAL, EQUAL] {
+        assert_eq!(eval(|p| { p.float(f64::NAN).float(1.0).op(opcode); }).unwrap(), "false");
+        assert_eq!(eval(|p| { p.float(1.0).float(f64::NAN).op(opcode); }).unwrap(), "false");
+        assert_eq!(eval(|p| { p.float(f64::NAN).float(f64::NAN).op(opcode); }).unwrap(), "false");
+    }
+}
+
+#[test]
+fn nan_is_not_equal_to_itself() {
+    assert_eq!(eval(|p| { p.float(f64::NAN).float(f64::NAN).op(NOT_EQUAL); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.float(f64::NAN).int(0).op(NOT_EQUAL); }).unwrap(), "true");
+}
+
+#[test]
+fn inclusive_comparisons() {
+    assert_eq!(eval(|p| { p.int(2).float(2.0).op(LESS_THAN_OR_EQUAL); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.int(2).float(2.0).op(GREATER_THAN_OR_EQUAL); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.int(3).int(2).op(LESS_THAN_OR_EQUAL); }).unwrap(), "false");
+    assert_eq!(eval(|p| { p.float(-0.0).float(0.0).op(GREATER_THAN_OR_EQUAL); }).unwrap(), "true");
+}
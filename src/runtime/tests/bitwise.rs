@@ -0,0 +1,73 @@
+//! Tests of the bitwise and shift opcodes.
+
+mod common;
+
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+
+use common::eval;
+
+#[test]
+fn bitwise_operations() {
+    assert_eq!(eval(|p| { p.int(0b1100).int(0b1010).op(BIT_AND); }).unwrap(), "8");
+    assert_eq!(eval(|p| { p.int(0b1100).int(0b1010).op(BIT_OR); }).unwrap(), "14");
+    assert_eq!(eval(|p| { p.int(0b1100).int(0b1010).op(BIT_XOR); }).unwrap(), "6");
+    assert_eq!(eval(|p| { p.int(0).op(BIT_NOT); }).unwrap(), "-1");
+    assert_eq!(eval(|p| { p.int(-1).int(i64::MIN).op(BIT_XOR); }).unwrap(), i64::MAX.to_string());
+}
+
+#[test]
+fn bitwise_operations_accept_integral_numbers() {
+    assert_eq!(eval(|p| { p.float(12.0).int(10).op(BIT_AND); }).unwrap(), "8");
+}
+
+#[test]
+fn bitwise_operations_on_non_integral_numbers_throw() {
+    assert!(eval(|p| { p.float(1.5).int(1).op(BIT_AND); }).is_err());
+    assert!(eval(|p| { p.float(f64::NAN).op(BIT_NOT); }).is_err());
+    assert!(eval(|p| { p.int(1).float(0.5).op(SHIFT_LEFT); }).is_err());
+}
+
+#[test]
+fn shift_left_discards_bits_past_the_most_significant_bit() {
+    assert_eq!(eval(|p| { p.int(1).int(4).op(SHIFT_LEFT); }).unwrap(), "16");
+    assert_eq!(eval(|p| { p.int(1).int(63).op(SHIFT_LEFT); }).unwrap(), i64::MIN.to_string());
+    assert_eq!(eval(|p| { p.int(3).int(63).op(SHIFT_LEFT); }).unwrap(), i64::MIN.to_string());
+}
+
+#[test]
+fn shifts_by_zero_keep_the_value() {
+    for opcode in [SHIFT_LEFT, SHIFT_RIGHT, SHIFT_RIGHT_LOGICAL] {
+        assert_eq!(eval(|p| { p.int(-5).int(0).op(opcode); }).unwrap(), "-5");
+    }
+}
+
+#[test]
+fn arithmetic_shift_right_keeps_the_sign() {
+    assert_eq!(eval(|p| { p.int(-16).int(2).op(SHIFT_RIGHT); }).unwrap(), "-4");
+    assert_eq!(eval(|p| { p.int(-1).int(63).op(SHIFT_RIGHT); }).unwrap(), "-1");
+    assert_eq!(eval(|p| { p.int(16).int(2).op(SHIFT_RIGHT); }).unwrap(), "4");
+}
+
+#[test]
+fn logical_shift_right_fills_with_zeroes() {
+    assert_eq!(eval(|p| { p.int(-16).int(2).op(SHIFT_RIGHT_LOGICAL); }).unwrap(), ((-16i64 as u64) >> 2).to_string());
+    assert_eq!(eval(|p| { p.int(-1).int(63).op(SHIFT_RIGHT_LOGICAL); }).unwrap(), "1");
+    assert_eq!(eval(|p| { p.int(16).int(2).op(SHIFT_RIGHT_LOGICAL); }).unwrap(), "4");
+}
+
+#[test]
+fn shifts_by_64_or_more_throw() {
+    for opcode in [SHIFT_LEFT, SHIFT_RIGHT, SHIFT_RIGHT_LOGICAL] {
+        assert!(matches!(eval(|p| { p.int(1).int(64).op(opcode); }), Err(Exception::InvalidShift(64))));
+        assert!(matches!(eval(|p| { p.int(1).int(i64::MAX).op(opcode); }), Err(Exception::InvalidShift(i64::MAX))));
+    }
+}
+
+#[test]
+fn negative_shifts_throw() {
+    for opcode in [SHIFT_LEFT, SHIFT_RIGHT, SHIFT_RIGHT_LOGICAL] {
+        assert!(matches!(eval(|p| { p.int(1).int(-1).op(opcode); }), Err(Exception::InvalidShift(-1))));
+        assert!(matches!(eval(|p| { p.int(1).int(i64::MIN).op(opcode); }), Err(Exception::InvalidShift(i64::MIN))));
+    }
+}
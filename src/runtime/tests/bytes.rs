@@ -0,0 +1,139 @@
+//! Tests of the byte buffer natives.
+
+mod common;
+
+use std::{env, fs, process};
+
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+
+use common::{eval, natives, Program};
+
+/// Emits a byte buffer containing `xs`.
+fn bytes(p: &mut Program, xs: &[i64]) {
+    p.native(natives::BYTES).op(PUSH_LIST);
+    for &x in xs {
+        p.op(DUP).int(x).op(APPEND_ELEMENT);
+    }
+    p.call(1);
+}
+
+/// Evaluates `bytesSlice(bytes([0, 1, 2, 3, 4]), start, end)` as hex.
+fn slice(start: i64, end: i64) -> Result<String, Exception> {
+    eval(|p| {
+        p.native(natives::HEX_ENCODE).native(natives::BYTES_SLICE);
+        bytes(p, &[0, 1, 2, 3, 4]);
+        p.int(start).int(end).call(3).call(1);
+    })
+}
+
+#[test]
+fn slice_takes_bytes_between_start_and_end() {
+    assert_eq!(slice(1, 3).unwrap(), "0102");
+    assert_eq!(slice(0, 5).unwrap(), "0001020304");
+}
+
+#[test]
+fn slice_clamps_out_of_bounds_indices() {
+    assert_eq!(slice(-3, 2).unwrap(), "0001");
+    assert_eq!(slice(3, 100).unwrap(), "0304");
+    assert_eq!(slice(5, 6).unwrap(), "");
+}
+
+#[test]
+fn slice_with_start_after_end_is_empty() {
+    assert_eq!(slice(3, 1).unwrap(), "");
+}
+
+#[test]
+fn bytes_outside_of_byte_range_throw() {
+    assert!(matches!(eval(|p| bytes(p, &[256])), Err(Exception::InvalidByte(256))));
+    assert!(matches!(eval(|p| bytes(p, &[-1])), Err(Exception::InvalidByte(-1))));
+}
+
+#[test]
+fn hex_round_trips() {
+    let result = eval(|p| {
+        p.native(natives::HEX_ENCODE).native(natives::HEX_DECODE).str("00ff7fA0").call(1).call(1);
+    });
+
+    assert_eq!(result.unwrap(), "00ff7fa0");
+}
+
+#[test]
+fn invalid_hex_throws() {
+    assert!(matches!(
+        eval(|p| { p.native(natives::HEX_DECODE).str("abc").call(1); }),
+        Err(Exception::InvalidHex(_))
+    ));
+}
+
+#[test]
+fn utf8_round_trips() {
+    let result = eval(|p| {
+        p.native(natives::UTF8_DECODE).native(natives::UTF8_ENCODE).str("héllo 🌍").call(1).call(1);
+    });
+
+    assert_eq!(result.unwrap(), "héllo 🌍");
+}
+
+#[test]
+fn utf8_encoding_uses_one_to_four_bytes_per_character() {
+    let result = eval(|p| {
+        p.native(natives::BYTES_LENGTH).native(natives::UTF8_ENCODE).str("aé€🌍").call(1).call(1);
+    });
+
+    assert_eq!(result.unwrap(), "10");
+}
+
+#[test]
+fn decoding_invalid_utf8_throws_unless_lossy() {
+    assert!(matches!(
+        eval(|p| {
+            p.native(natives::UTF8_DECODE);
+            bytes(p, &[0x61, 0xFF]);
+            p.call(1);
+        }),
+        Err(Exception::NonUtf8(_))
+    ));
+
+    let lossy = eval(|p| {
+        p.native(natives::UTF8_DECODE);
+        bytes(p, &[0x61, 0xFF]);
+        p.bool(true).call(2);
+    });
+
+    assert_eq!(lossy.unwrap(), "a\u{FFFD}");
+}
+
+#[test]
+fn written_bytes_are_read_back() {
+    let path = env::temp_dir().join(format!("noa-bytes-test-{}", process::id()));
+    let path_str = path.to_str().unwrap();
+
+    let written = eval(|p| {
+        p.native(natives::WRITE_FILE_BYTES).str(path_str);
+        bytes(p, &[0, 1, 0x80, 0xFF]);
+        p.call(2);
+    });
+    assert_eq!(written.unwrap(), "true");
+    assert_eq!(fs::read(&path).unwrap(), [0, 1, 0x80, 0xFF]);
+
+    let read = eval(|p| {
+        p.native(natives::HEX_ENCODE).native(natives::READ_FILE_BYTES).str(path_str).call(1).call(1);
+    });
+    assert_eq!(read.unwrap(), "000180ff");
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn reading_a_missing_file_returns_nil() {
+    let path = env::temp_dir().join(format!("noa-bytes-test-missing-{}", process::id()));
+
+    let read = eval(|p| {
+        p.native(natives::READ_FILE_BYTES).str(path.to_str().unwrap()).call(1);
+    });
+
+    assert_eq!(read.unwrap(), "()");
+}
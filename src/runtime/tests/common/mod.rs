@@ -0,0 +1,275 @@
+//! A tiny assembler for writing test and benchmark programs directly in Ark bytecode.
+
+#![allow(dead_code)]
+
+pub mod natives;
+
+use noa_runtime::ark::{FuncId, Function};
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+use noa_runtime::vm::{Input, Output, Vm};
+
+pub const STACK_SIZE: usize = 10_000;
+pub const CALL_STACK_SIZE: usize = 1_000;
+pub const HEAP_SIZE: usize = 100_000;
+
+struct NoInput;
+
+impl Input for NoInput {
+    fn read(&mut self, _: &mut Vec<u8>) -> Result<(), Exception> {
+        Ok(())
+    }
+}
+
+struct NoOutput;
+
+impl Output for NoOutput {
+    fn write(&mut self, _: &[u8]) -> Result<(), Exception> {
+        Ok(())
+    }
+}
+
+/// A position in the code which can be jumped to.
+#[derive(Debug, Clone, Copy)]
+pub struct Label(usize);
+
+/// An Ark program under construction.
+#[derive(Debug, Default)]
+pub struct Program {
+    code: Vec<u8>,
+    strings: Vec<String>,
+    functions: Vec<Function>,
+    labels: Vec<Option<u32>>,
+    /// Positions in the code where the address of a label has to be written.
+    fixups: Vec<(usize, Label)>,
+}
+
+impl Program {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares a function without starting it, so it can be referenced before it's written.
+    pub fn declare(&mut self, name: &str, arity: u32, locals_count: u32, captures: &[u32]) -> FuncId {
+        let id = FuncId(self.functions.len() as u32);
+        let name_index = self.string(name);
+
+        self.functions.push(Function {
+            id,
+            name_index,
+            arity,
+            locals_count,
+            captures_count: captures.len() as u32,
+            address: 0,
+            captures: captures.to_vec(),
+        });
+
+        id
+    }
+
+    /// Starts the code of a declared function at the current position.
+    pub fn begin(&mut self, function: FuncId) -> &mut Self {
+        self.functions[function.0 as usize].address = self.code.len() as u32;
+        self
+    }
+
+    /// Gets the index of a string in the string section, adding it if it's not already there.
+    pub fn string(&mut self, str: &str) -> u32 {
+        let index = self.strings.iter().position(|x| x == str)
+            .unwrap_or_else(|| {
+                self.strings.push(str.to_owned());
+                self.strings.len() - 1
+            });
+
+        index as u32
+    }
+
+    pub fn op(&mut self, opcode: u8) -> &mut Self {
+        self.code.push(opcode);
+        self
+    }
+
+    pub fn op_u32(&mut self, opcode: u8, operand: u32) -> &mut Self {
+        self.code.push(opcode);
+        self.code.extend(operand.to_be_bytes());
+        self
+    }
+
+    pub fn float(&mut self, x: f64) -> &mut Self {
+        self.code.push(PUSH_FLOAT);
+        self.code.extend(x.to_be_bytes());
+        self
+    }
+
+    pub fn int(&mut self, x: i64) -> &mut Self {
+        self.code.push(PUSH_INT);
+        self.code.extend(x.to_be_bytes());
+        self
+    }
+
+    pub fn bool(&mut self, x: bool) -> &mut Self {
+        self.code.extend([PUSH_BOOL, x as u8]);
+        self
+    }
+
+    pub fn str(&mut self, str: &str) -> &mut Self {
+        let index = self.string(str);
+        self.op_u32(PUSH_STRING, index)
+    }
+
+    /// Emits a list containing the values emitted by `element` for each item.
+    pub fn list<T: Copy>(&mut self, items: &[T], mut element: impl FnMut(&mut Self, T)) -> &mut Self {
+        self.op(PUSH_LIST);
+        for &item in items {
+            self.op(DUP);
+            element(self, item);
+            self.op(APPEND_ELEMENT);
+        }
+        self
+    }
+
+    /// Emits a list of integers.
+    pub fn ints(&mut self, xs: &[i64]) -> &mut Self {
+        self.list(xs, |p, x| { p.int(x); })
+    }
+
+    /// Emits a list of strings.
+    pub fn strs(&mut self, strs: &[&str]) -> &mut Self {
+        self.list(strs, |p, str| { p.str(str); })
+    }
+
+    /// Emits an empty object.
+    pub fn object(&mut self, dynamic: bool) -> &mut Self {
+        self.code.extend([PUSH_OBJECT, dynamic as u8]);
+        self
+    }
+
+    /// Adds a field with the value emitted by `value` to the object on top of the stack,
+    /// leaving the object on the stack.
+    pub fn field(&mut self, name: &str, mutable: bool, value: impl FnOnce(&mut Self)) -> &mut Self {
+        self.op(DUP).str(name);
+        value(self);
+        self.code.extend([ADD_FIELD, mutable as u8]);
+        self
+    }
+
+    pub fn func(&mut self, function: FuncId) -> &mut Self {
+        self.op_u32(PUSH_FUNC, function.0)
+    }
+
+    pub fn native(&mut self, id: u32) -> &mut Self {
+        self.op_u32(PUSH_FUNC, id | (1 << 31))
+    }
+
+    pub fn load(&mut self, variable: u32) -> &mut Self {
+        self.op_u32(LOAD_VAR, variable)
+    }
+
+    pub fn store(&mut self, variable: u32) -> &mut Self {
+        self.op_u32(STORE_VAR, variable)
+    }
+
+    pub fn call(&mut self, arg_count: u32) -> &mut Self {
+        self.op_u32(CALL, arg_count)
+    }
+
+    pub fn label(&mut self) -> Label {
+        self.labels.push(None);
+        Label(self.labels.len() - 1)
+    }
+
+    /// Binds a label to the current position.
+    pub fn bind(&mut self, label: Label) -> &mut Self {
+        self.labels[label.0] = Some(self.code.len() as u32);
+        self
+    }
+
+    /// Emits a jump instruction to a label.
+    pub fn jump(&mut self, opcode: u8, label: Label) -> &mut Self {
+        self.code.push(opcode);
+        self.fixups.push((self.code.len(), label));
+        self.code.extend(0u32.to_be_bytes());
+        self
+    }
+
+    /// Creates a vm which runs the program.
+    pub fn vm(&self) -> Vm {
+        self.vm_with_heap(HEAP_SIZE)
+    }
+
+    /// Creates a vm which runs the program with a specific heap size.
+    pub fn vm_with_heap(&self, heap_size: usize) -> Vm {
+        let mut code = self.code.clone();
+
+        for &(at, label) in &self.fixups {
+            let address = self.labels[label.0].expect("label should be bound");
+            code[at..at + 4].copy_from_slice(&address.to_be_bytes());
+        }
+
+        let functions = self.functions.iter()
+            .map(|f| Function {
+                captures: f.captures.clone(),
+                .. *f
+            })
+            .collect();
+
+        Vm::new(
+            functions,
+            self.strings.clone(),
+            code,
+            STACK_SIZE,
+            CALL_STACK_SIZE,
+            heap_size,
+            Box::new(NoInput),
+            Box::new(NoOutput),
+            None
+        )
+    }
+
+    /// Runs the program from a function and converts its return value into a string.
+    pub fn run(&self, main: FuncId) -> Result<String, Exception> {
+        let mut vm = self.vm();
+
+        vm.call_run(main.into(), &[])
+            .and_then(|ret| vm.to_string(ret))
+            .map_err(|e| e.exception)
+    }
+}
+
+/// Runs the code emitted by `body` as a main function with a few local variables, and returns its result.
+pub fn eval(body: impl FnOnce(&mut Program)) -> Result<String, Exception> {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 4, &[]);
+    p.begin(main);
+    body(&mut p);
+    p.op(RET).op(BOUNDARY);
+
+    p.run(main)
+}
+
+/// A program which calculates `fib(n)` recursively.
+pub fn fib(n: f64) -> (Program, FuncId) {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 0, &[]);
+    let fib = p.declare("fib", 1, 0, &[]);
+
+    p.begin(main)
+        .func(fib).float(n).call(1)
+        .op(RET).op(BOUNDARY);
+
+    // fib(x) = if x < 2 { x } else { fib(x - 1) + fib(x - 2) }
+    let recurse = p.label();
+    p.begin(fib)
+        .load(0).float(2.0).op(LESS_THAN).op(NOT)
+        .jump(JUMP_IF, recurse)
+        .load(0).op(RET)
+        .bind(recurse)
+        .func(fib).load(0).float(1.0).op(SUB).call(1)
+        .func(fib).load(0).float(2.0).op(SUB).call(1)
+        .op(ADD).op(RET)
+        .op(BOUNDARY);
+
+    (p, main)
+}
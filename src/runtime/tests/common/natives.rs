@@ -0,0 +1,175 @@
+//! IDs of the native functions, as listed in `docs/builtin-functions.md`.
+
+// Console IO
+pub const PRINT: u32 = 0x0;
+pub const GET_INPUT: u32 = 0x1;
+
+// File IO
+pub const READ_FILE: u32 = 0x80;
+pub const WRITE_FILE: u32 = 0x81;
+
+// Strings
+pub const TO_STRING: u32 = 0x100;
+pub const SUBSTRING: u32 = 0x101;
+pub const INDEX_OF: u32 = 0x102;
+pub const LAST_INDEX_OF: u32 = 0x103;
+pub const CONTAINS: u32 = 0x104;
+pub const STARTS_WITH: u32 = 0x105;
+pub const ENDS_WITH: u32 = 0x106;
+pub const SPLIT: u32 = 0x107;
+pub const JOIN: u32 = 0x108;
+pub const REPLACE: u32 = 0x109;
+pub const TRIM: u32 = 0x10A;
+pub const TRIM_START: u32 = 0x10B;
+pub const TRIM_END: u32 = 0x10C;
+pub const TO_UPPER: u32 = 0x10D;
+pub const TO_LOWER: u32 = 0x10E;
+pub const REPEAT: u32 = 0x10F;
+pub const PAD_START: u32 = 0x110;
+pub const PAD_END: u32 = 0x111;
+pub const CHARS: u32 = 0x112;
+pub const CODE_POINT_AT: u32 = 0x113;
+pub const FROM_CODE_POINT: u32 = 0x114;
+
+// String builders
+pub const STRING_BUILDER: u32 = 0x160;
+pub const SB_APPEND: u32 = 0x161;
+pub const SB_TO_STRING: u32 = 0x162;
+pub const SB_LENGTH: u32 = 0x163;
+pub const SB_CLEAR: u32 = 0x164;
+
+// Lists
+pub const PUSH: u32 = 0x180;
+pub const POP: u32 = 0x181;
+pub const APPEND: u32 = 0x182;
+pub const CONCAT: u32 = 0x183;
+pub const SLICE: u32 = 0x184;
+pub const MAP: u32 = 0x185;
+pub const FLAT_MAP: u32 = 0x186;
+pub const FILTER: u32 = 0x187;
+pub const REDUCE: u32 = 0x188;
+pub const REVERSE: u32 = 0x189;
+pub const ANY: u32 = 0x18A;
+pub const ALL: u32 = 0x18B;
+pub const FIND: u32 = 0x18C;
+pub const LENGTH: u32 = 0x18D;
+pub const SORT: u32 = 0x18E;
+pub const SORT_BY: u32 = 0x18F;
+pub const SORT_WITH: u32 = 0x190;
+pub const BINARY_SEARCH: u32 = 0x191;
+pub const DISTINCT: u32 = 0x192;
+pub const RANGE: u32 = 0x193;
+pub const REPLICATE: u32 = 0x194;
+pub const ZIP: u32 = 0x195;
+pub const ENUMERATE: u32 = 0x196;
+pub const CHUNK: u32 = 0x197;
+pub const WINDOW: u32 = 0x198;
+pub const TAKE: u32 = 0x199;
+pub const SKIP: u32 = 0x19A;
+pub const TAKE_WHILE: u32 = 0x19B;
+pub const SKIP_WHILE: u32 = 0x19C;
+pub const GROUP_BY: u32 = 0x19D;
+pub const PARTITION: u32 = 0x19E;
+pub const SUM: u32 = 0x19F;
+pub const COUNT: u32 = 0x1A0;
+
+// Maps
+pub const MAP_NEW: u32 = 0x200;
+pub const MAP_GET: u32 = 0x201;
+pub const MAP_SET: u32 = 0x202;
+pub const MAP_HAS: u32 = 0x203;
+pub const MAP_REMOVE: u32 = 0x204;
+pub const MAP_KEYS: u32 = 0x205;
+pub const MAP_VALUES: u32 = 0x206;
+pub const MAP_ENTRIES: u32 = 0x207;
+pub const MAP_SIZE: u32 = 0x208;
+
+// Numbers
+pub const TO_INTEGER: u32 = 0x280;
+pub const TO_FLOAT: u32 = 0x281;
+pub const IS_INTEGER: u32 = 0x282;
+pub const PARSE_NUMBER: u32 = 0x283;
+pub const PARSE_INT: u32 = 0x284;
+pub const FORMAT_NUMBER: u32 = 0x285;
+
+// Bytes
+pub const BYTES: u32 = 0x300;
+pub const BYTES_LENGTH: u32 = 0x301;
+pub const BYTES_GET: u32 = 0x302;
+pub const BYTES_SLICE: u32 = 0x303;
+pub const BYTES_CONCAT: u32 = 0x304;
+pub const BYTES_TO_LIST: u32 = 0x305;
+pub const UTF8_ENCODE: u32 = 0x306;
+pub const UTF8_DECODE: u32 = 0x307;
+pub const HEX_ENCODE: u32 = 0x308;
+pub const HEX_DECODE: u32 = 0x309;
+pub const READ_FILE_BYTES: u32 = 0x30A;
+pub const WRITE_FILE_BYTES: u32 = 0x30B;
+pub const GET_INPUT_BYTES: u32 = 0x30C;
+
+// Math
+pub const ABS: u32 = 0x380;
+pub const FLOOR: u32 = 0x381;
+pub const CEIL: u32 = 0x382;
+pub const ROUND: u32 = 0x383;
+pub const TRUNC: u32 = 0x384;
+pub const SIGN: u32 = 0x385;
+pub const SQRT: u32 = 0x386;
+pub const POW: u32 = 0x387;
+pub const EXP: u32 = 0x388;
+pub const LN: u32 = 0x389;
+pub const LOG: u32 = 0x38A;
+pub const SIN: u32 = 0x38B;
+pub const COS: u32 = 0x38C;
+pub const TAN: u32 = 0x38D;
+pub const ASIN: u32 = 0x38E;
+pub const ACOS: u32 = 0x38F;
+pub const ATAN: u32 = 0x390;
+pub const ATAN2: u32 = 0x391;
+pub const MIN: u32 = 0x392;
+pub const MAX: u32 = 0x393;
+pub const CLAMP: u32 = 0x394;
+pub const IS_NAN: u32 = 0x395;
+pub const IS_FINITE: u32 = 0x396;
+pub const PI: u32 = 0x397;
+pub const E: u32 = 0x398;
+pub const INFINITY: u32 = 0x399;
+pub const NAN: u32 = 0x39A;
+
+// JSON
+pub const JSON_PARSE: u32 = 0x400;
+pub const JSON_STRINGIFY: u32 = 0x401;
+
+// Regular expressions
+pub const REGEX_MATCH: u32 = 0x480;
+pub const REGEX_FIND_ALL: u32 = 0x481;
+pub const REGEX_REPLACE: u32 = 0x482;
+pub const REGEX_SPLIT: u32 = 0x483;
+
+// Time
+pub const NOW: u32 = 0x500;
+pub const MONOTONIC: u32 = 0x501;
+pub const SLEEP: u32 = 0x502;
+pub const TO_DATE: u32 = 0x503;
+pub const FROM_DATE: u32 = 0x504;
+pub const FORMAT_DATE: u32 = 0x505;
+pub const PARSE_DATE: u32 = 0x506;
+
+// Random numbers
+pub const RANDOM: u32 = 0x580;
+pub const RANDOM_INT: u32 = 0x581;
+pub const RANDOM_CHOICE: u32 = 0x582;
+pub const SHUFFLE: u32 = 0x583;
+pub const SET_SEED: u32 = 0x584;
+
+// Objects
+pub const KEYS: u32 = 0x600;
+pub const VALUES: u32 = 0x601;
+pub const ENTRIES: u32 = 0x602;
+pub const HAS_FIELD: u32 = 0x603;
+pub const GET_FIELD: u32 = 0x604;
+pub const REMOVE_FIELD: u32 = 0x605;
+pub const IS_MUTABLE: u32 = 0x606;
+pub const IS_DYNAMIC: u32 = 0x607;
+pub const MERGE: u32 = 0x608;
+pub const FREEZE: u32 = 0x609;
@@ -0,0 +1,231 @@
+//! Conformance tests which run precompiled Ark programs and compare what they do against golden files.
+//!
+//! Every `.ark` file in `tests/conformance` is a test case, paired with a `.expected` file of the same name.
+//! The `.ark` files are assembled by hand rather than compiled from Noa source,
+//! so the expected files record what the runtime does with that exact bytecode.
+//! The expected file consists of sections, each starting with a `--- <name>` line:
+//!
+//! - `stdin`: Lines given to the program as input, one line for each call to `getInput`.
+//! - `stdout`: What the program prints. Each line of the section ends with a newline.
+//! - `return`: The return value of the main function converted to a string.
+//!   Only checked if the section is present.
+//! - `exception`: The kind of exception the program ends with (e.g. `MissingField`).
+//! - `exit code`: The code the CLI exits with after running the program,
+//!   which is `0` if the program completes and `1` if it throws.
+//!
+//! All sections are optional. A missing `stdout` section means that the program prints nothing,
+//! a missing `exception` section means that the program doesn't throw,
+//! and a missing `exit code` section means that the exit code is `0`.
+//!
+//! Running the tests with `NOA_BLESS=1` writes the actual results into the expected files,
+//! keeping their `stdin` sections.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use binrw::BinRead;
+use noa_runtime::ark::{Ark, CodeSection, FunctionSection, Header, StringSection};
+use noa_runtime::exception::Exception;
+use noa_runtime::vm::{Input, Output, Vm};
+
+const STACK_SIZE: usize = 100_000;
+const CALL_STACK_SIZE: usize = 10_000;
+const HEAP_SIZE: usize = 100_000;
+
+/// Input which reads from a list of scripted lines.
+struct ScriptedInput(VecDeque<String>);
+
+impl Input for ScriptedInput {
+    fn read(&mut self, buf: &mut Vec<u8>) -> Result<(), Exception> {
+        if let Some(line) = self.0.pop_front() {
+            buf.extend_from_slice(line.as_bytes());
+        }
+
+        Ok(())
+    }
+}
+
+/// Output which writes into a shared buffer.
+struct CapturedOutput(Rc<RefCell<Vec<u8>>>);
+
+impl Output for CapturedOutput {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Exception> {
+        self.0.borrow_mut().extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// The observable results of running a program.
+#[derive(Debug, Default, PartialEq)]
+struct Outcome {
+    stdin: Vec<String>,
+    stdout: String,
+    ret: Option<String>,
+    exception: Option<String>,
+    exit_code: u8,
+}
+
+impl Outcome {
+    fn parse(expected: &str) -> Self {
+        let mut outcome = Self::default();
+        let mut section: Option<(&str, String)> = None;
+
+        for line in expected.lines().chain(["--- end"]) {
+            let Some(name) = line.strip_prefix("--- ") else {
+                let (_, body) = section.as_mut()
+                    .unwrap_or_else(|| panic!("expected a section header, found `{line}`"));
+                body.push_str(line);
+                body.push('\n');
+                continue;
+            };
+
+            if let Some((name, body)) = section.take() {
+                match name {
+                    "stdin" => outcome.stdin = body.lines().map(str::to_owned).collect(),
+                    "stdout" => outcome.stdout = body,
+                    "return" => outcome.ret = Some(body.trim_end().to_owned()),
+                    "exception" => outcome.exception = Some(body.trim().to_owned()),
+                    "exit code" => outcome.exit_code = body.trim().parse()
+                        .unwrap_or_else(|_| panic!("`{}` is not an exit code", body.trim())),
+                    _ => panic!("unknown section `{name}`")
+                }
+            }
+
+            section = Some((name.trim(), String::new()));
+        }
+
+        outcome
+    }
+
+    fn format(&self) -> String {
+        let mut str = String::new();
+
+        if !self.stdin.is_empty() {
+            writeln!(str, "--- stdin").unwrap();
+            for line in &self.stdin {
+                writeln!(str, "{line}").unwrap();
+            }
+        }
+
+        if !self.stdout.is_empty() {
+            write!(str, "--- stdout\n{}", self.stdout).unwrap();
+        }
+
+        if let Some(ret) = &self.ret {
+            writeln!(str, "--- return\n{ret}").unwrap();
+        }
+
+        if let Some(exception) = &self.exception {
+            writeln!(str, "--- exception\n{exception}").unwrap();
+        }
+
+        if self.exit_code != 0 {
+            writeln!(str, "--- exit code\n{}", self.exit_code).unwrap();
+        }
+
+        str
+    }
+}
+
+/// Gets the kind of an exception, which is the name of its variant.
+fn exception_kind(exception: &Exception) -> String {
+    format!("{exception:?}")
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap()
+        .to_owned()
+}
+
+fn run(path: &Path, stdin: &[String]) -> Outcome {
+    let bytes = fs::read(path).unwrap();
+    let Ark {
+        header: Header { main, .. },
+        function_section: FunctionSection { functions, .. },
+        code_section: CodeSection { code, .. },
+        string_section: StringSection { strings, .. },
+        ..
+    } = Ark::read_be(&mut Cursor::new(bytes))
+        .unwrap_or_else(|e| panic!("{} is not a valid ark file: {e}", path.display()));
+
+    let stdout = Rc::new(RefCell::new(Vec::new()));
+
+    let mut vm = Vm::new(
+        functions,
+        strings,
+        code,
+        STACK_SIZE,
+        CALL_STACK_SIZE,
+        HEAP_SIZE,
+        Box::new(ScriptedInput(stdin.iter().cloned().collect())),
+        Box::new(CapturedOutput(stdout.clone())),
+        None
+    );
+
+    let result = vm.call_run(main.into(), &[])
+        .and_then(|ret| vm.to_string(ret));
+
+    // The CLI exits with 1 after an uncaught exception, which tests/exit_code.rs of the CLI checks.
+    let (ret, exception, exit_code) = match result {
+        Ok(ret) => (Some(ret), None, 0),
+        Err(e) => (None, Some(exception_kind(&e.exception)), 1)
+    };
+
+    Outcome {
+        stdin: stdin.to_vec(),
+        stdout: String::from_utf8_lossy(&stdout.borrow()).into_owned(),
+        ret,
+        exception,
+        exit_code,
+    }
+}
+
+fn cases() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/conformance");
+
+    let mut cases = fs::read_dir(dir).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ark"))
+        .collect::<Vec<_>>();
+    cases.sort();
+
+    cases
+}
+
+#[test]
+fn conformance() {
+    let bless = std::env::var_os("NOA_BLESS").is_some();
+    let mut failures = Vec::new();
+
+    for case in cases() {
+        let expected_path = case.with_extension("expected");
+        let expected = fs::read_to_string(&expected_path)
+            .map(|str| Outcome::parse(&str))
+            .unwrap_or_default();
+
+        let mut actual = run(&case, &expected.stdin);
+
+        // The return value is only checked if it's expected,
+        // and isn't worth recording if the program doesn't return anything.
+        if expected.ret.is_none() && (!bless || actual.ret.as_deref() == Some("()")) {
+            actual.ret = None;
+        }
+
+        if bless {
+            fs::write(&expected_path, actual.format()).unwrap();
+        } else if actual != expected {
+            failures.push(format!(
+                "{}\nexpected:\n{}\nactual:\n{}",
+                case.display(),
+                expected.format(),
+                actual.format()
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "{} conformance test(s) failed:\n\n{}", failures.len(), failures.join("\n"));
+}
@@ -0,0 +1,108 @@
+//! Tests of integer arithmetic and how integers mix with numbers.
+
+mod common;
+
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+
+use common::{eval, natives};
+
+fn is_integer(body: impl FnOnce(&mut common::Program)) -> Result<String, Exception> {
+    eval(|p| {
+        p.native(natives::IS_INTEGER);
+        body(p);
+        p.call(1);
+    })
+}
+
+#[test]
+fn arithmetic_on_integers_produces_integers() {
+    assert_eq!(eval(|p| { p.int(7).int(5).op(ADD); }).unwrap(), "12");
+    assert_eq!(eval(|p| { p.int(7).int(5).op(SUB); }).unwrap(), "2");
+    assert_eq!(eval(|p| { p.int(7).int(5).op(MULT); }).unwrap(), "35");
+
+    assert_eq!(is_integer(|p| { p.int(7).int(5).op(ADD); }).unwrap(), "true");
+    assert_eq!(is_integer(|p| { p.int(7).int(5).op(MULT); }).unwrap(), "true");
+}
+
+#[test]
+fn integers_keep_precision_above_2_pow_53() {
+    assert_eq!(eval(|p| { p.int(1 << 53).int(1).op(ADD); }).unwrap(), "9007199254740993");
+}
+
+#[test]
+fn arithmetic_mixing_integers_and_numbers_produces_numbers() {
+    assert_eq!(is_integer(|p| { p.int(1).float(2.0).op(ADD); }).unwrap(), "false");
+    assert_eq!(is_integer(|p| { p.float(1.0).int(2).op(MULT); }).unwrap(), "false");
+    assert_eq!(eval(|p| { p.int(1).float(0.5).op(ADD); }).unwrap(), "1.5");
+}
+
+#[test]
+fn division_of_integers_produces_a_number() {
+    assert_eq!(is_integer(|p| { p.int(6).int(3).op(DIV); }).unwrap(), "false");
+    assert_eq!(eval(|p| { p.int(7).int(2).op(DIV); }).unwrap(), "3.5");
+}
+
+#[test]
+fn overflowing_integer_arithmetic_throws() {
+    assert!(matches!(
+        eval(|p| { p.int(i64::MAX).int(1).op(ADD); }),
+        Err(Exception::IntegerOverflow)
+    ));
+    assert!(matches!(
+        eval(|p| { p.int(i64::MIN).int(1).op(SUB); }),
+        Err(Exception::IntegerOverflow)
+    ));
+    assert!(matches!(
+        eval(|p| { p.int(i64::MAX / 2 + 1).int(2).op(MULT); }),
+        Err(Exception::IntegerOverflow)
+    ));
+    assert!(matches!(
+        eval(|p| { p.int(i64::MIN).op(NEG); }),
+        Err(Exception::IntegerOverflow)
+    ));
+}
+
+#[test]
+fn integers_and_numbers_with_the_same_value_are_equal() {
+    assert_eq!(eval(|p| { p.int(3).float(3.0).op(EQUAL); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.int(3).float(3.5).op(EQUAL); }).unwrap(), "false");
+
+    // 2^53 + 1 rounds to 2^53 when converted to a number, but the comparison uses the exact values.
+    assert_eq!(eval(|p| { p.int((1 << 53) + 1).float(9007199254740992.0).op(EQUAL); }).unwrap(), "false");
+}
+
+#[test]
+fn comparisons_between_integers_and_numbers_use_exact_values() {
+    assert_eq!(eval(|p| { p.int((1 << 53) + 1).float(9007199254740992.0).op(GREATER_THAN); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.int(2).float(2.5).op(LESS_THAN); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.int(i64::MAX).float(f64::INFINITY).op(LESS_THAN); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.int(0).float(f64::NAN).op(LESS_THAN); }).unwrap(), "false");
+    assert_eq!(eval(|p| { p.int(0).float(f64::NAN).op(GREATER_THAN_OR_EQUAL); }).unwrap(), "false");
+}
+
+#[test]
+fn natives_return_lengths_counts_and_indices_as_integers() {
+    // These used to be numbers before integers were added.
+    assert_eq!(is_integer(|p| { p.native(natives::LENGTH).ints(&[1, 2]).call(1); }).unwrap(), "true");
+    assert_eq!(is_integer(|p| { p.native(natives::LENGTH).str("ab").call(1); }).unwrap(), "true");
+    assert_eq!(is_integer(|p| { p.native(natives::INDEX_OF).str("ab").str("b").call(2); }).unwrap(), "true");
+    assert_eq!(is_integer(|p| { p.native(natives::INDEX_OF).ints(&[1, 2]).int(2).call(2); }).unwrap(), "true");
+    assert_eq!(is_integer(|p| { p.native(natives::MAP_SIZE).native(natives::MAP_NEW).call(0).call(1); }).unwrap(), "true");
+}
+
+#[test]
+fn lengths_still_equal_numbers_with_the_same_value() {
+    assert_eq!(eval(|p| { p.native(natives::LENGTH).str("ab").call(1).float(2.0).op(EQUAL); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.native(natives::LENGTH).str("ab").call(1).float(1.5).op(GREATER_THAN); }).unwrap(), "true");
+}
+
+#[test]
+fn arithmetic_on_lengths_is_integer_arithmetic() {
+    assert_eq!(eval(|p| { p.native(natives::LENGTH).str("abc").call(1).int(2).op(DIV); }).unwrap(), "1.5");
+    assert_eq!(is_integer(|p| { p.native(natives::LENGTH).str("abc").call(1).int(2).op(MULT); }).unwrap(), "true");
+    assert!(matches!(
+        eval(|p| { p.native(natives::LENGTH).str("abc").call(1).int(i64::MAX).op(MULT); }),
+        Err(Exception::IntegerOverflow)
+    ));
+}
@@ -0,0 +1,99 @@
+//! Tests of the exceptions the interpreter throws for malformed or runaway programs.
+
+mod common;
+
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+
+use common::{eval, Program};
+
+#[test]
+fn exiting_a_temp_frame_which_was_never_entered_throws() {
+    assert!(matches!(
+        eval(|p| { p.op(EXIT_TEMP_FRAME).op(PUSH_NIL); }),
+        Err(Exception::UnbalancedTempFrame)
+    ));
+}
+
+#[test]
+fn returning_from_a_temp_frame_returns_from_the_function() {
+    let result = eval(|p| {
+        p.op(ENTER_TEMP_FRAME).op(ENTER_TEMP_FRAME).int(1);
+    });
+
+    assert_eq!(result.unwrap(), "1");
+}
+
+#[test]
+fn popping_an_empty_stack_throws() {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 0, &[]);
+    p.begin(main)
+        .op(POP)
+        .op(PUSH_NIL).op(RET).op(BOUNDARY);
+
+    assert!(matches!(p.run(main), Err(Exception::StackUnderflow)));
+}
+
+#[test]
+fn returning_with_an_empty_stack_throws() {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 0, &[]);
+    p.begin(main)
+        .op(RET).op(BOUNDARY);
+
+    assert!(matches!(p.run(main), Err(Exception::StackUnderflow)));
+}
+
+#[test]
+fn comparing_deeply_nested_lists_throws() {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 3, &[]);
+
+    // a = []
+    // b = []
+    // for (i = 0; i < 300; i++) { a = [a]; b = [b] }
+    // a == b
+    let top = p.label();
+    let end = p.label();
+    p.begin(main)
+        .op(PUSH_LIST).store(0)
+        .op(PUSH_LIST).store(1)
+        .int(0).store(2)
+        .bind(top)
+        .load(2).int(300).op(LESS_THAN).op(NOT)
+        .jump(JUMP_IF, end)
+        .op(PUSH_LIST).op(DUP).load(0).op(APPEND_ELEMENT).store(0)
+        .op(PUSH_LIST).op(DUP).load(1).op(APPEND_ELEMENT).store(1)
+        .load(2).int(1).op(ADD).store(2)
+        .jump(JUMP, top)
+        .bind(end)
+        .load(0).load(1).op(EQUAL)
+        .op(RET).op(BOUNDARY);
+
+    assert!(matches!(p.run(main), Err(Exception::TooDeeplyNested)));
+}
+
+#[test]
+fn infinite_loops_run_out_of_fuel() {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 0, &[]);
+
+    let top = p.label();
+    p.begin(main)
+        .bind(top)
+        .jump(JUMP, top)
+        .op(PUSH_NIL).op(RET).op(BOUNDARY);
+
+    let mut vm = p.vm();
+    vm.set_fuel(Some(1000));
+
+    let result = vm.call_run(main.into(), &[]);
+
+    assert!(matches!(result.map_err(|e| e.exception), Err(Exception::OutOfFuel)));
+    assert_eq!(vm.fuel(), Some(0));
+}
@@ -0,0 +1,179 @@
+//! Tests of the JSON natives.
+
+mod common;
+
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+
+use common::{eval, natives};
+
+fn parse(json: &str) -> Result<String, Exception> {
+    eval(|p| { p.native(natives::JSON_PARSE).str(json).call(1); })
+}
+
+/// Parses JSON and stringifies the result again.
+fn round_trip(json: &str) -> String {
+    eval(|p| {
+        p.native(natives::JSON_STRINGIFY)
+            .native(natives::JSON_PARSE).str(json).call(1)
+            .call(1);
+    }).unwrap()
+}
+
+#[test]
+fn parse_maps_json_onto_values() {
+    let json = r#"{"name": "Noa", "tags": ["a", "b"], "nested": {"x": 1.5, "ok": true, "none": null}}"#;
+
+    assert_eq!(
+        parse(json).unwrap(),
+        "dyn { \"name\": Noa, \"tags\": [a, b], \"nested\": dyn { \"x\": 1.5, \"ok\": true, \"none\": () } }"
+    );
+    assert_eq!(parse("[1, -2.5e2, \"x\", [], {}]").unwrap(), "[1, -250, x, [], dyn {}]");
+}
+
+#[test]
+fn parse_produces_numbers_rather_than_integers() {
+    let result = eval(|p| {
+        p.native(natives::IS_INTEGER)
+            .native(natives::JSON_PARSE).str("1").call(1)
+            .call(1);
+    });
+
+    assert_eq!(result.unwrap(), "false");
+}
+
+#[test]
+fn parse_unescapes_strings() {
+    assert_eq!(parse(r#""\"\\\/\b\f\n\r\t""#).unwrap(), "\"\\/\u{8}\u{c}\n\r\t");
+    assert_eq!(parse(r#""é""#).unwrap(), "é");
+}
+
+#[test]
+fn parse_combines_surrogate_pairs() {
+    assert_eq!(parse(r#""😀""#).unwrap(), "😀");
+}
+
+#[test]
+fn parse_rejects_unpaired_surrogates() {
+    assert!(matches!(parse(r#""\ud83d""#), Err(Exception::InvalidJson { .. })));
+    assert!(matches!(parse(r#""\ude00\ud83d""#), Err(Exception::InvalidJson { .. })));
+}
+
+#[test]
+fn parse_rejects_unescaped_control_characters() {
+    assert!(matches!(parse("\"a\nb\""), Err(Exception::InvalidJson { .. })));
+}
+
+#[test]
+fn parse_rejects_invalid_json() {
+    for json in ["{\"a\": 1,\n \"b\": tru}", "[1, 2,]", "01", "1.", "'a'", "[1] 2", ""] {
+        assert!(matches!(parse(json), Err(Exception::InvalidJson { .. })), "{json:?} should not be parsed");
+    }
+}
+
+#[test]
+fn duplicate_keys_replace_the_earlier_value_in_place() {
+    let json = r#"{"a":1,"b":2,"a":3,"c":4}"#;
+
+    let keys = eval(|p| {
+        p.native(natives::KEYS)
+            .native(natives::JSON_PARSE).str(json).call(1)
+            .call(1);
+    });
+
+    assert_eq!(keys.unwrap(), "[a, b, c]");
+    assert_eq!(round_trip(json), r#"{"a":3,"b":2,"c":4}"#);
+}
+
+#[test]
+fn stringify_escapes_strings() {
+    assert_eq!(round_trip(r#""line\nbreak é 😀""#), r#""line\nbreak é 😀""#);
+
+    let result = eval(|p| {
+        p.native(natives::JSON_STRINGIFY).str("quote \" and \\ and \t and \u{1}").call(1);
+    });
+    assert_eq!(result.unwrap(), r#""quote \" and \\ and \t and \u0001""#);
+}
+
+#[test]
+fn stringify_maps_values_onto_json() {
+    let result = eval(|p| {
+        p.native(natives::JSON_STRINGIFY)
+            .object(false)
+            .field("a", false, |p| {
+                p.op(PUSH_LIST)
+                    .op(DUP).int(1).op(APPEND_ELEMENT)
+                    .op(DUP).float(2.5).op(APPEND_ELEMENT)
+                    .op(DUP).op(PUSH_NIL).op(APPEND_ELEMENT)
+                    .op(DUP).bool(true).op(APPEND_ELEMENT);
+            })
+            .field("b", false, |p| { p.str("x"); })
+            .call(1);
+    });
+
+    assert_eq!(result.unwrap(), r#"{"a":[1,2.5,null,true],"b":"x"}"#);
+}
+
+#[test]
+fn stringify_turns_non_finite_numbers_into_null() {
+    let result = eval(|p| {
+        p.native(natives::JSON_STRINGIFY)
+            .op(PUSH_LIST)
+            .op(DUP).float(f64::NAN).op(APPEND_ELEMENT)
+            .op(DUP).float(f64::INFINITY).op(APPEND_ELEMENT)
+            .call(1);
+    });
+
+    assert_eq!(result.unwrap(), "[null,null]");
+}
+
+#[test]
+fn stringify_pretty_indents_by_two_spaces() {
+    let result = eval(|p| {
+        p.native(natives::JSON_STRINGIFY)
+            .object(false)
+            .field("a", false, |p| {
+                p.op(PUSH_LIST)
+                    .op(DUP).int(1).op(APPEND_ELEMENT)
+                    .op(DUP).object(false).field("b", false, |p| { p.op(PUSH_LIST); }).op(APPEND_ELEMENT);
+            })
+            .field("c", false, |p| { p.object(false); })
+            .bool(true)
+            .call(2);
+    });
+
+    assert_eq!(result.unwrap(), "{\n  \"a\": [\n    1,\n    {\n      \"b\": []\n    }\n  ],\n  \"c\": {}\n}");
+}
+
+#[test]
+fn stringify_of_a_list_containing_itself_throws() {
+    let result = eval(|p| {
+        p.op(PUSH_LIST).store(0)
+            .load(0).load(0).op(APPEND_ELEMENT)
+            .native(natives::JSON_STRINGIFY).load(0).call(1);
+    });
+
+    assert!(matches!(result, Err(Exception::CyclicJson)));
+}
+
+#[test]
+fn stringify_of_the_same_list_twice_is_not_cyclic() {
+    let result = eval(|p| {
+        p.op(PUSH_LIST).store(0)
+            .native(natives::JSON_STRINGIFY)
+            .op(PUSH_LIST)
+            .op(DUP).load(0).op(APPEND_ELEMENT)
+            .op(DUP).load(0).op(APPEND_ELEMENT)
+            .call(1);
+    });
+
+    assert_eq!(result.unwrap(), "[[],[]]");
+}
+
+#[test]
+fn stringify_of_a_function_throws() {
+    assert!(matches!(
+        eval(|p| { p.native(natives::JSON_STRINGIFY).native(natives::PRINT).call(1); }),
+        Err(Exception::NonJsonValue(_))
+    ));
+}
@@ -0,0 +1,334 @@
+//! Tests of the list natives.
+
+mod common;
+
+use noa_runtime::ark::FuncId;
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+
+use common::{eval, natives, Program};
+
+/// Runs the code emitted by `body` as a main function which can refer to a function emitted by `function`.
+fn eval_with(
+    arity: u32,
+    function: impl FnOnce(&mut Program),
+    body: impl FnOnce(&mut Program, FuncId)
+) -> Result<String, Exception> {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 4, &[]);
+    let f = p.declare("f", arity, 0, &[]);
+
+    p.begin(main);
+    body(&mut p, f);
+    p.op(RET).op(BOUNDARY);
+
+    p.begin(f);
+    function(&mut p);
+    p.op(RET).op(BOUNDARY);
+
+    p.run(main)
+}
+
+#[test]
+fn sort_orders_numbers_before_nan_before_strings() {
+    let result = eval(|p| {
+        p.native(natives::SORT)
+            .op(PUSH_LIST)
+            .op(DUP).int(3).op(APPEND_ELEMENT)
+            .op(DUP).float(1.5).op(APPEND_ELEMENT)
+            .op(DUP).str("b").op(APPEND_ELEMENT)
+            .op(DUP).int(2).op(APPEND_ELEMENT)
+            .op(DUP).float(f64::NAN).op(APPEND_ELEMENT)
+            .op(DUP).str("a").op(APPEND_ELEMENT)
+            .op(DUP).int(-1).op(APPEND_ELEMENT)
+            .op(DUP).int(1).op(APPEND_ELEMENT)
+            .op(DUP).str("B").op(APPEND_ELEMENT)
+            .call(1);
+    });
+
+    assert_eq!(result.unwrap(), "[-1, 1, 1.5, 2, 3, NaN, B, a, b]");
+    assert_eq!(eval(|p| { p.native(natives::SORT).op(PUSH_LIST).call(1); }).unwrap(), "[]");
+}
+
+#[test]
+fn sort_does_not_change_the_list() {
+    let result = eval(|p| {
+        p.ints(&[2, 1]).store(0)
+            .native(natives::SORT).load(0).call(1).op(POP)
+            .load(0);
+    });
+
+    assert_eq!(result.unwrap(), "[2, 1]");
+}
+
+#[test]
+fn sorting_incomparable_values_throws() {
+    let result = eval(|p| {
+        p.native(natives::SORT)
+            .op(PUSH_LIST)
+            .op(DUP).int(1).op(APPEND_ELEMENT)
+            .op(DUP).bool(true).op(APPEND_ELEMENT)
+            .call(1);
+    });
+
+    assert!(matches!(result, Err(Exception::Incomparable(..))));
+}
+
+#[test]
+fn sort_by_is_stable() {
+    let result = eval_with(1, |p| {
+        p.native(natives::LENGTH).load(0).call(1);
+    }, |p, by_length| {
+        p.native(natives::SORT_BY).strs(&["ccc", "a", "bb", "d"]).func(by_length).call(2);
+    });
+
+    assert_eq!(result.unwrap(), "[a, d, bb, ccc]");
+}
+
+#[test]
+fn sort_with_is_stable() {
+    // (a, b) => a[0] - b[0]
+    let result = eval_with(2, |p| {
+        p.load(0).int(0).op(READ_ELEMENT)
+            .load(1).int(0).op(READ_ELEMENT)
+            .op(SUB);
+    }, |p, by_key| {
+        p.native(natives::SORT_WITH)
+            .list(&[(2, "x"), (1, "y"), (2, "z"), (1, "w")], |p, (key, value)| {
+                p.op(PUSH_LIST)
+                    .op(DUP).int(key).op(APPEND_ELEMENT)
+                    .op(DUP).str(value).op(APPEND_ELEMENT);
+            })
+            .func(by_key)
+            .call(2);
+    });
+
+    assert_eq!(result.unwrap(), "[[1, y], [1, w], [2, x], [2, z]]");
+}
+
+#[test]
+fn sort_with_orders_by_the_sign_of_the_comparison() {
+    // (a, b) => b - a
+    let result = eval_with(2, |p| {
+        p.load(1).load(0).op(SUB);
+    }, |p, descending| {
+        p.native(natives::SORT_WITH).ints(&[1, 5, 3]).func(descending).call(2);
+    });
+
+    assert_eq!(result.unwrap(), "[5, 3, 1]");
+}
+
+#[test]
+fn binary_search_returns_the_insertion_point_of_missing_values() {
+    assert_eq!(eval(|p| { p.native(natives::BINARY_SEARCH).ints(&[1, 3, 5, 7]).int(5).call(2); }).unwrap(), "2");
+
+    // The insertion point `i` is encoded as `-(i + 1)`.
+    assert_eq!(eval(|p| { p.native(natives::BINARY_SEARCH).ints(&[1, 3, 5, 7]).int(4).call(2); }).unwrap(), "-3");
+    assert_eq!(eval(|p| { p.native(natives::BINARY_SEARCH).ints(&[1, 3, 5, 7]).int(8).call(2); }).unwrap(), "-5");
+    assert_eq!(eval(|p| { p.native(natives::BINARY_SEARCH).op(PUSH_LIST).int(1).call(2); }).unwrap(), "-1");
+}
+
+#[test]
+fn index_of_compares_lists_by_value() {
+    let result = eval(|p| {
+        p.native(natives::INDEX_OF)
+            .op(PUSH_LIST)
+            .op(DUP).int(1).op(APPEND_ELEMENT)
+            .op(DUP).str("a").op(APPEND_ELEMENT)
+            .op(DUP).ints(&[2]).op(APPEND_ELEMENT)
+            .ints(&[2])
+            .call(2);
+    });
+
+    assert_eq!(result.unwrap(), "2");
+}
+
+#[test]
+fn index_of_starts_searching_at_the_index() {
+    assert_eq!(eval(|p| { p.native(natives::INDEX_OF).ints(&[1, 2, 1]).int(1).int(1).call(3); }).unwrap(), "2");
+    assert_eq!(eval(|p| { p.native(natives::INDEX_OF).ints(&[1, 2]).int(3).call(2); }).unwrap(), "-1");
+}
+
+#[test]
+fn contains_compares_integers_and_numbers_by_value() {
+    assert_eq!(eval(|p| { p.native(natives::CONTAINS).ints(&[1, 2]).float(2.0).call(2); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.native(natives::CONTAINS).ints(&[1, 2]).str("2").call(2); }).unwrap(), "false");
+}
+
+#[test]
+fn index_of_and_contains_still_search_strings() {
+    assert_eq!(eval(|p| { p.native(natives::INDEX_OF).str("hello").str("l").call(2); }).unwrap(), "2");
+    assert_eq!(eval(|p| { p.native(natives::CONTAINS).str("hello").str("ell").call(2); }).unwrap(), "true");
+}
+
+#[test]
+fn distinct_keeps_the_first_of_equal_values() {
+    let result = eval(|p| {
+        p.native(natives::DISTINCT)
+            .op(PUSH_LIST)
+            .op(DUP).int(1).op(APPEND_ELEMENT)
+            .op(DUP).float(1.0).op(APPEND_ELEMENT)
+            .op(DUP).str("a").op(APPEND_ELEMENT)
+            .op(DUP).str("a").op(APPEND_ELEMENT)
+            .op(DUP).ints(&[1]).op(APPEND_ELEMENT)
+            .op(DUP).ints(&[1]).op(APPEND_ELEMENT)
+            .op(DUP).int(2).op(APPEND_ELEMENT)
+            .call(1);
+    });
+
+    assert_eq!(result.unwrap(), "[1, a, [1], 2]");
+}
+
+#[test]
+fn min_and_max_of_a_list_use_the_sort_order() {
+    assert_eq!(eval(|p| { p.native(natives::MIN).strs(&["b", "a", "c"]).call(1); }).unwrap(), "a");
+
+    let result = eval(|p| {
+        p.native(natives::MAX)
+            .op(PUSH_LIST)
+            .op(DUP).int(1).op(APPEND_ELEMENT)
+            .op(DUP).str("a").op(APPEND_ELEMENT)
+            .call(1);
+    });
+    assert_eq!(result.unwrap(), "a");
+
+    assert_eq!(eval(|p| { p.native(natives::MIN).int(3).int(1).int(2).call(3); }).unwrap(), "1");
+}
+
+#[test]
+fn range_excludes_the_end() {
+    assert_eq!(eval(|p| { p.native(natives::RANGE).int(0).int(5).call(2); }).unwrap(), "[0, 1, 2, 3, 4]");
+    assert_eq!(eval(|p| { p.native(natives::RANGE).int(0).int(10).int(4).call(3); }).unwrap(), "[0, 4, 8]");
+    assert_eq!(eval(|p| { p.native(natives::RANGE).int(10).int(0).int(-3).call(3); }).unwrap(), "[10, 7, 4, 1]");
+    assert_eq!(eval(|p| { p.native(natives::RANGE).int(5).int(0).call(2); }).unwrap(), "[]");
+}
+
+#[test]
+fn range_steps_do_not_overflow() {
+    let result = eval(|p| { p.native(natives::RANGE).int(i64::MIN).int(i64::MAX).int(i64::MAX).call(3); });
+
+    assert_eq!(result.unwrap(), "[-9223372036854775808, -1, 9223372036854775806]");
+}
+
+#[test]
+fn replicate_repeats_strings_into_a_list() {
+    assert_eq!(eval(|p| { p.native(natives::REPLICATE).str("a").int(3).call(2); }).unwrap(), "[a, a, a]");
+}
+
+#[test]
+fn replicate_treats_negative_counts_as_zero() {
+    assert_eq!(eval(|p| { p.native(natives::REPLICATE).int(1).int(-1).call(2); }).unwrap(), "[]");
+}
+
+#[test]
+fn zip_stops_at_the_shorter_list() {
+    assert_eq!(
+        eval(|p| { p.native(natives::ZIP).ints(&[1, 2, 3]).strs(&["a", "b"]).call(2); }).unwrap(),
+        "[[1, a], [2, b]]"
+    );
+}
+
+#[test]
+fn enumerate_pairs_elements_with_their_indices() {
+    assert_eq!(eval(|p| { p.native(natives::ENUMERATE).strs(&["a", "b"]).call(1); }).unwrap(), "[[0, a], [1, b]]");
+}
+
+#[test]
+fn chunk_puts_the_remaining_elements_in_the_last_chunk() {
+    assert_eq!(eval(|p| { p.native(natives::CHUNK).ints(&[1, 2, 3, 4, 5]).int(2).call(2); }).unwrap(), "[[1, 2], [3, 4], [5]]");
+    assert_eq!(eval(|p| { p.native(natives::CHUNK).ints(&[1, 2]).int(1).call(2); }).unwrap(), "[[1], [2]]");
+}
+
+#[test]
+fn chunk_with_a_size_less_than_one_throws() {
+    assert!(matches!(
+        eval(|p| { p.native(natives::CHUNK).ints(&[1, 2]).int(0).call(2); }),
+        Err(Exception::Custom(_))
+    ));
+}
+
+#[test]
+fn window_returns_every_run_of_consecutive_elements() {
+    assert_eq!(eval(|p| { p.native(natives::WINDOW).ints(&[1, 2, 3, 4]).int(3).call(2); }).unwrap(), "[[1, 2, 3], [2, 3, 4]]");
+    assert_eq!(eval(|p| { p.native(natives::WINDOW).ints(&[1, 2]).int(3).call(2); }).unwrap(), "[]");
+}
+
+#[test]
+fn take_and_skip_clamp_counts() {
+    assert_eq!(eval(|p| { p.native(natives::TAKE).ints(&[1, 2, 3]).int(2).call(2); }).unwrap(), "[1, 2]");
+    assert_eq!(eval(|p| { p.native(natives::TAKE).ints(&[1, 2, 3]).int(5).call(2); }).unwrap(), "[1, 2, 3]");
+    assert_eq!(eval(|p| { p.native(natives::SKIP).ints(&[1, 2, 3]).int(2).call(2); }).unwrap(), "[3]");
+    assert_eq!(eval(|p| { p.native(natives::SKIP).ints(&[1, 2, 3]).int(-1).call(2); }).unwrap(), "[1, 2, 3]");
+}
+
+#[test]
+fn take_while_and_skip_while_stop_at_the_first_rejected_element() {
+    // x => x < 3
+    let small = |p: &mut Program| { p.load(0).int(3).op(LESS_THAN); };
+
+    let result = eval_with(1, small, |p, small| {
+        p.native(natives::TAKE_WHILE).ints(&[1, 2, 3, 1]).func(small).call(2);
+    });
+    assert_eq!(result.unwrap(), "[1, 2]");
+
+    let result = eval_with(1, small, |p, small| {
+        p.native(natives::SKIP_WHILE).ints(&[1, 2, 3, 1]).func(small).call(2);
+    });
+    assert_eq!(result.unwrap(), "[3, 1]");
+}
+
+#[test]
+fn group_by_groups_by_key_in_order_of_appearance() {
+    let result = eval_with(1, |p| {
+        p.native(natives::LENGTH).load(0).call(1);
+    }, |p, by_length| {
+        p.native(natives::GROUP_BY).strs(&["a", "bb", "c", "dd", "eee"]).func(by_length).call(2);
+    });
+
+    assert_eq!(result.unwrap(), "map { 1: [a, c], 2: [bb, dd], 3: [eee] }");
+}
+
+#[test]
+fn partition_and_count() {
+    // x => x % 2 == 0
+    let even = |p: &mut Program| { p.load(0).int(2).op(MOD).int(0).op(EQUAL); };
+
+    let result = eval_with(1, even, |p, even| {
+        p.native(natives::PARTITION).ints(&[1, 2, 3, 4, 5]).func(even).call(2);
+    });
+    assert_eq!(result.unwrap(), "[[2, 4], [1, 3, 5]]");
+
+    let result = eval_with(1, even, |p, even| {
+        p.native(natives::COUNT).ints(&[1, 2, 3, 4]).func(even).call(2);
+    });
+    assert_eq!(result.unwrap(), "2");
+}
+
+#[test]
+fn sum_keeps_integers_unless_a_number_is_added() {
+    assert_eq!(eval(|p| { p.native(natives::SUM).ints(&[1, 2, 3]).call(1); }).unwrap(), "6");
+    assert_eq!(eval(|p| { p.native(natives::SUM).op(PUSH_LIST).call(1); }).unwrap(), "0");
+
+    let result = eval(|p| {
+        p.native(natives::IS_INTEGER).native(natives::SUM).ints(&[1, 2]).call(1).call(1);
+    });
+    assert_eq!(result.unwrap(), "true");
+
+    let result = eval(|p| {
+        p.native(natives::SUM)
+            .op(PUSH_LIST)
+            .op(DUP).int(1).op(APPEND_ELEMENT)
+            .op(DUP).float(0.5).op(APPEND_ELEMENT)
+            .call(1);
+    });
+    assert_eq!(result.unwrap(), "1.5");
+}
+
+#[test]
+fn sum_of_integers_which_overflow_throws() {
+    assert!(matches!(
+        eval(|p| { p.native(natives::SUM).ints(&[i64::MAX, 1]).call(1); }),
+        Err(Exception::IntegerOverflow)
+    ));
+}
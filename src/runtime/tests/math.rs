@@ -0,0 +1,139 @@
+//! Tests of the math natives.
+
+mod common;
+
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+
+use common::{eval, natives};
+
+#[test]
+fn min_and_max_order_nan_like_sort() {
+    assert_eq!(eval(|p| { p.native(natives::MIN).int(1).float(f64::NAN).float(-2.5).call(3); }).unwrap(), "-2.5");
+    assert_eq!(eval(|p| { p.native(natives::MAX).int(1).float(f64::NAN).float(-2.5).call(3); }).unwrap(), "NaN");
+    assert_eq!(eval(|p| { p.native(natives::MAX).float(f64::NAN).str("a").call(2); }).unwrap(), "a");
+
+    let sorted = eval(|p| {
+        p.native(natives::SORT)
+            .op(PUSH_LIST)
+            .op(DUP).int(1).op(APPEND_ELEMENT)
+            .op(DUP).float(f64::NAN).op(APPEND_ELEMENT)
+            .op(DUP).float(-2.5).op(APPEND_ELEMENT)
+            .call(1);
+    });
+    assert_eq!(sorted.unwrap(), "[-2.5, 1, NaN]");
+}
+
+#[test]
+fn min_and_max_of_a_single_list_compare_its_elements() {
+    let list = |p: &mut common::Program| {
+        p.op(PUSH_LIST)
+            .op(DUP).float(f64::NAN).op(APPEND_ELEMENT)
+            .op(DUP).int(3).op(APPEND_ELEMENT);
+    };
+
+    assert_eq!(eval(|p| { p.native(natives::MIN); list(p); p.call(1); }).unwrap(), "3");
+    assert_eq!(eval(|p| { p.native(natives::MAX); list(p); p.call(1); }).unwrap(), "NaN");
+}
+
+#[test]
+fn min_and_max_of_an_empty_list_are_nil() {
+    assert_eq!(eval(|p| { p.native(natives::MIN).op(PUSH_LIST).call(1); }).unwrap(), "()");
+    assert_eq!(eval(|p| { p.native(natives::MAX).op(PUSH_LIST).call(1); }).unwrap(), "()");
+}
+
+#[test]
+fn abs_keeps_integers_as_integers() {
+    assert_eq!(eval(|p| { p.native(natives::ABS).int(-3).call(1); }).unwrap(), "3");
+    assert_eq!(eval(|p| { p.native(natives::IS_INTEGER).native(natives::ABS).int(-3).call(1).call(1); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.native(natives::ABS).float(-2.5).call(1); }).unwrap(), "2.5");
+}
+
+#[test]
+fn abs_of_the_smallest_integer_overflows() {
+    assert!(matches!(
+        eval(|p| { p.native(natives::ABS).int(i64::MIN).call(1); }),
+        Err(Exception::IntegerOverflow)
+    ));
+}
+
+#[test]
+fn rounding() {
+    assert_eq!(eval(|p| { p.native(natives::FLOOR).float(2.7).call(1); }).unwrap(), "2");
+    assert_eq!(eval(|p| { p.native(natives::FLOOR).int(5).call(1); }).unwrap(), "5");
+    assert_eq!(eval(|p| { p.native(natives::CEIL).float(-2.7).call(1); }).unwrap(), "-2");
+    assert_eq!(eval(|p| { p.native(natives::TRUNC).float(-2.7).call(1); }).unwrap(), "-2");
+}
+
+#[test]
+fn round_rounds_halfway_cases_away_from_zero() {
+    assert_eq!(eval(|p| { p.native(natives::ROUND).float(2.5).call(1); }).unwrap(), "3");
+    assert_eq!(eval(|p| { p.native(natives::ROUND).float(-2.5).call(1); }).unwrap(), "-3");
+}
+
+#[test]
+fn sign() {
+    assert_eq!(eval(|p| { p.native(natives::SIGN).int(-7).call(1); }).unwrap(), "-1");
+    assert_eq!(eval(|p| { p.native(natives::SIGN).float(0.0).call(1); }).unwrap(), "0");
+    assert_eq!(eval(|p| { p.native(natives::SIGN).float(3.5).call(1); }).unwrap(), "1");
+    assert_eq!(eval(|p| { p.native(natives::IS_NAN).native(natives::SIGN).float(f64::NAN).call(1).call(1); }).unwrap(), "true");
+}
+
+#[test]
+fn pow_is_an_integer_for_integers_with_non_negative_exponents() {
+    assert_eq!(eval(|p| { p.native(natives::POW).int(2).int(10).call(2); }).unwrap(), "1024");
+    assert_eq!(eval(|p| { p.native(natives::IS_INTEGER).native(natives::POW).int(2).int(10).call(2).call(1); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.native(natives::POW).int(2).int(-1).call(2); }).unwrap(), "0.5");
+    assert_eq!(eval(|p| { p.native(natives::POW).float(9.0).float(0.5).call(2); }).unwrap(), "3");
+}
+
+#[test]
+fn exponentials_and_logarithms() {
+    assert_eq!(eval(|p| { p.native(natives::SQRT).int(16).call(1); }).unwrap(), "4");
+    assert_eq!(eval(|p| { p.native(natives::EXP).int(0).call(1); }).unwrap(), "1");
+    assert_eq!(eval(|p| { p.native(natives::LN).int(1).call(1); }).unwrap(), "0");
+    assert_eq!(eval(|p| { p.native(natives::LOG).int(1000).call(1); }).unwrap(), "3");
+    assert_eq!(eval(|p| { p.native(natives::LOG).int(8).int(2).call(2); }).unwrap(), "3");
+}
+
+#[test]
+fn trigonometry() {
+    assert_eq!(eval(|p| { p.native(natives::SIN).int(0).call(1); }).unwrap(), "0");
+    assert_eq!(eval(|p| { p.native(natives::COS).int(0).call(1); }).unwrap(), "1");
+    assert_eq!(eval(|p| { p.native(natives::ATAN2).int(1).int(1).call(2); }).unwrap(), (std::f64::consts::PI / 4.0).to_string());
+    assert_eq!(eval(|p| { p.native(natives::IS_NAN).native(natives::ASIN).int(2).call(1).call(1); }).unwrap(), "true");
+}
+
+#[test]
+fn min_and_max_of_several_numbers() {
+    assert_eq!(eval(|p| { p.native(natives::MIN).int(3).float(1.5).int(2).call(3); }).unwrap(), "1.5");
+    assert_eq!(eval(|p| { p.native(natives::MAX).ints(&[1, 5, 3]).call(1); }).unwrap(), "5");
+}
+
+#[test]
+fn clamp_restricts_to_a_range() {
+    assert_eq!(eval(|p| { p.native(natives::CLAMP).int(15).int(0).int(10).call(3); }).unwrap(), "10");
+    assert_eq!(eval(|p| { p.native(natives::CLAMP).float(-1.0).float(0.0).float(1.0).call(3); }).unwrap(), "0");
+    assert_eq!(eval(|p| { p.native(natives::CLAMP).float(0.5).float(0.0).float(1.0).call(3); }).unwrap(), "0.5");
+}
+
+#[test]
+fn clamp_with_an_empty_range_throws() {
+    assert!(eval(|p| { p.native(natives::CLAMP).int(1).int(2).int(0).call(3); }).is_err());
+}
+
+#[test]
+fn classification() {
+    assert_eq!(eval(|p| { p.native(natives::IS_NAN).float(f64::NAN).call(1); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.native(natives::IS_NAN).int(1).call(1); }).unwrap(), "false");
+    assert_eq!(eval(|p| { p.native(natives::IS_FINITE).float(f64::INFINITY).call(1); }).unwrap(), "false");
+    assert_eq!(eval(|p| { p.native(natives::IS_FINITE).float(1.0).call(1); }).unwrap(), "true");
+}
+
+#[test]
+fn constants() {
+    assert_eq!(eval(|p| { p.native(natives::PI).call(0); }).unwrap(), "3.141592653589793");
+    assert_eq!(eval(|p| { p.native(natives::E).call(0); }).unwrap(), "2.718281828459045");
+    assert_eq!(eval(|p| { p.native(natives::INFINITY).call(0); }).unwrap(), "inf");
+    assert_eq!(eval(|p| { p.native(natives::NAN).call(0); }).unwrap(), "NaN");
+}
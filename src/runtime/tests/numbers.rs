@@ -0,0 +1,106 @@
+//! Tests of the number parsing and formatting natives.
+
+mod common;
+
+use noa_runtime::exception::Exception;
+
+use common::{eval, natives, Program};
+
+fn parse_number(str: &str) -> String {
+    eval(|p| { p.native(natives::PARSE_NUMBER).str(str).call(1); }).unwrap()
+}
+
+fn parse_int(str: &str, radix: Option<i64>) -> Result<String, Exception> {
+    eval(|p| {
+        p.native(natives::PARSE_INT).str(str);
+        match radix {
+            Some(radix) => p.int(radix).call(2),
+            None => p.call(1),
+        };
+    })
+}
+
+/// Evaluates `formatNumber(x, options)`, where `options` emits the fields of the options object.
+fn format_number(x: impl FnOnce(&mut Program), options: impl FnOnce(&mut Program)) -> Result<String, Exception> {
+    eval(|p| {
+        p.native(natives::FORMAT_NUMBER);
+        x(p);
+        p.object(false);
+        options(p);
+        p.call(2);
+    })
+}
+
+#[test]
+fn parse_number_ignores_surrounding_whitespace() {
+    assert_eq!(parse_number("42\n"), "42");
+    assert_eq!(parse_number(" -1.5e3 "), "-1500");
+}
+
+#[test]
+fn parse_number_returns_nil_for_strings_which_are_not_decimal_numbers() {
+    assert_eq!(parse_number("abc"), "()");
+    assert_eq!(parse_number(""), "()");
+    assert_eq!(parse_number("inf"), "()");
+    assert_eq!(parse_number("0x10"), "()");
+}
+
+#[test]
+fn parse_int_parses_integers_in_a_radix() {
+    assert_eq!(parse_int("42", None).unwrap(), "42");
+    assert_eq!(parse_int("ff", Some(16)).unwrap(), "255");
+    assert_eq!(parse_int("-101", Some(2)).unwrap(), "-5");
+    assert_eq!(parse_int("10", Some(36)).unwrap(), "36");
+}
+
+#[test]
+fn parse_int_returns_nil_for_non_integers_and_integers_which_overflow() {
+    assert_eq!(parse_int("1.5", None).unwrap(), "()");
+    assert_eq!(parse_int("99999999999999999999", None).unwrap(), "()");
+}
+
+#[test]
+fn parse_int_with_a_radix_out_of_range_throws() {
+    assert!(matches!(parse_int("10", Some(37)), Err(Exception::InvalidRadix(37))));
+    assert!(matches!(parse_int("10", Some(1)), Err(Exception::InvalidRadix(1))));
+}
+
+#[test]
+fn format_number_without_options_converts_to_a_string() {
+    assert_eq!(eval(|p| { p.native(natives::FORMAT_NUMBER).float(1.5).call(1); }).unwrap(), "1.5");
+}
+
+#[test]
+fn format_number_with_decimals() {
+    assert_eq!(format_number(|p| { p.float(1.23456); }, |p| { p.field("decimals", false, |p| { p.int(2); }); }).unwrap(), "1.23");
+    assert_eq!(format_number(|p| { p.float(2.0); }, |p| { p.field("decimals", false, |p| { p.int(0); }); }).unwrap(), "2");
+    assert_eq!(format_number(|p| { p.int(7); }, |p| { p.field("decimals", false, |p| { p.int(3); }); }).unwrap(), "7.000");
+}
+
+#[test]
+fn format_number_with_an_exponent() {
+    assert_eq!(format_number(|p| { p.float(1234.5); }, |p| { p.field("exponent", false, |p| { p.bool(true); }); }).unwrap(), "1.2345e3");
+
+    let result = format_number(|p| { p.float(0.00012); }, |p| {
+        p.field("exponent", false, |p| { p.bool(true); })
+            .field("decimals", false, |p| { p.int(1); });
+    });
+    assert_eq!(result.unwrap(), "1.2e-4");
+}
+
+#[test]
+fn format_number_with_a_radix() {
+    assert_eq!(format_number(|p| { p.int(255); }, |p| { p.field("radix", false, |p| { p.int(16); }); }).unwrap(), "ff");
+    assert_eq!(format_number(|p| { p.float(-10.0); }, |p| { p.field("radix", false, |p| { p.int(2); }); }).unwrap(), "-1010");
+    assert_eq!(format_number(|p| { p.int(35); }, |p| { p.field("radix", false, |p| { p.int(36); }); }).unwrap(), "z");
+}
+
+#[test]
+fn format_number_with_a_radix_and_decimals_throws() {
+    let result = format_number(|p| { p.int(255); }, |p| {
+        p.field("radix", false, |p| { p.int(16); })
+            .field("decimals", false, |p| { p.int(2); });
+    });
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,147 @@
+//! Tests of the object reflection natives.
+
+mod common;
+
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+
+use common::{eval, natives, Program};
+
+/// Emits a dynamic object `{ mut a = 1, mut b = 2, mut c = 3 }`.
+fn abc(p: &mut Program) {
+    p.object(true)
+        .field("a", true, |p| { p.int(1); })
+        .field("b", true, |p| { p.int(2); })
+        .field("c", true, |p| { p.int(3); });
+}
+
+/// Emits an object `{ mut a = 1, b = 2 }`.
+fn mutable_a(p: &mut Program) {
+    p.object(false)
+        .field("a", true, |p| { p.int(1); })
+        .field("b", false, |p| { p.int(2); });
+}
+
+#[test]
+fn keys_values_and_entries_are_in_declaration_order() {
+    let ba = |p: &mut Program| {
+        p.object(false)
+            .field("b", false, |p| { p.int(1); })
+            .field("a", false, |p| { p.str("x"); });
+    };
+
+    assert_eq!(eval(|p| { p.native(natives::KEYS); ba(p); p.call(1); }).unwrap(), "[b, a]");
+    assert_eq!(eval(|p| { p.native(natives::VALUES); ba(p); p.call(1); }).unwrap(), "[1, x]");
+    assert_eq!(eval(|p| { p.native(natives::ENTRIES); ba(p); p.call(1); }).unwrap(), "[[b, 1], [a, x]]");
+    assert_eq!(eval(|p| { p.native(natives::KEYS).object(false).call(1); }).unwrap(), "[]");
+}
+
+#[test]
+fn has_field_and_get_field() {
+    let a = |p: &mut Program| { p.object(false).field("a", false, |p| { p.int(1); }); };
+
+    assert_eq!(eval(|p| { p.native(natives::HAS_FIELD); a(p); p.str("a").call(2); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.native(natives::HAS_FIELD); a(p); p.str("b").call(2); }).unwrap(), "false");
+    assert_eq!(eval(|p| { p.native(natives::GET_FIELD); a(p); p.str("a").call(2); }).unwrap(), "1");
+    assert_eq!(eval(|p| { p.native(natives::GET_FIELD); a(p); p.str("b").call(2); }).unwrap(), "()");
+    assert_eq!(eval(|p| { p.native(natives::GET_FIELD); a(p); p.str("b").int(5).call(3); }).unwrap(), "5");
+}
+
+#[test]
+fn is_mutable_and_is_dynamic() {
+    assert_eq!(eval(|p| { p.native(natives::IS_MUTABLE); mutable_a(p); p.str("a").call(2); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.native(natives::IS_MUTABLE); mutable_a(p); p.str("b").call(2); }).unwrap(), "false");
+    assert_eq!(eval(|p| { p.native(natives::IS_DYNAMIC).object(true).call(1); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.native(natives::IS_DYNAMIC).object(false).call(1); }).unwrap(), "false");
+}
+
+#[test]
+fn is_mutable_of_a_missing_field_throws() {
+    assert!(matches!(
+        eval(|p| { p.native(natives::IS_MUTABLE).object(false).str("a").call(2); }),
+        Err(Exception::MissingField(field)) if field == "a"
+    ));
+}
+
+#[test]
+fn merge_prefers_the_second_object() {
+    let result = eval(|p| {
+        p.native(natives::MERGE)
+            .object(false)
+            .field("a", false, |p| { p.int(1); })
+            .field("b", false, |p| { p.int(2); })
+            .object(false)
+            .field("b", false, |p| { p.int(3); })
+            .field("c", false, |p| { p.int(4); })
+            .call(2);
+    });
+
+    assert_eq!(result.unwrap(), "{ \"a\": 1, \"b\": 3, \"c\": 4 }");
+}
+
+#[test]
+fn merge_is_dynamic_if_either_object_is() {
+    let result = eval(|p| {
+        p.native(natives::IS_DYNAMIC)
+            .native(natives::MERGE)
+            .object(false).field("a", false, |p| { p.int(1); })
+            .object(true).field("b", true, |p| { p.int(2); })
+            .call(2)
+            .call(1);
+    });
+
+    assert_eq!(result.unwrap(), "true");
+}
+
+#[test]
+fn remove_field_returns_the_removed_value() {
+    let result = eval(|p| {
+        abc(p);
+        p.store(0)
+            .op(PUSH_LIST)
+            .op(DUP).native(natives::REMOVE_FIELD).load(0).str("a").call(2).op(APPEND_ELEMENT)
+            .op(DUP).native(natives::REMOVE_FIELD).load(0).str("missing").call(2).op(APPEND_ELEMENT)
+            .op(DUP).native(natives::ENTRIES).load(0).call(1).op(APPEND_ELEMENT);
+    });
+
+    assert_eq!(result.unwrap(), "[1, (), [[b, 2], [c, 3]]]");
+}
+
+#[test]
+fn removing_from_a_non_dynamic_object_throws() {
+    let result = eval(|p| {
+        p.native(natives::REMOVE_FIELD)
+            .object(false).field("a", false, |p| { p.int(1); })
+            .str("a")
+            .call(2);
+    });
+
+    assert!(matches!(result, Err(Exception::RemoveFromNonDynamic(field)) if field == "a"));
+}
+
+#[test]
+fn freeze_makes_every_field_immutable_and_the_object_non_dynamic() {
+    let result = eval(|p| {
+        abc(p);
+        p.store(0)
+            .op(PUSH_LIST)
+            .op(DUP).native(natives::FREEZE).load(0).call(1).op(APPEND_ELEMENT)
+            .op(DUP).native(natives::IS_DYNAMIC).load(0).call(1).op(APPEND_ELEMENT)
+            .op(DUP).native(natives::IS_MUTABLE).load(0).str("c").call(2).op(APPEND_ELEMENT);
+    });
+
+    assert_eq!(result.unwrap(), "[{ \"a\": 1, \"b\": 2, \"c\": 3 }, false, false]");
+}
+
+#[test]
+fn writing_to_a_frozen_object_throws() {
+    let result = eval(|p| {
+        p.object(false).field("a", true, |p| { p.int(1); }).store(0)
+            .load(0).str("a").int(2).op(WRITE_FIELD)
+            .native(natives::FREEZE).load(0).call(1).op(POP)
+            .load(0).str("a").int(3).op(WRITE_FIELD)
+            .op(PUSH_NIL);
+    });
+
+    assert!(matches!(result, Err(Exception::WriteToImmutableField(field)) if field == "a"));
+}
@@ -0,0 +1,112 @@
+//! Tests of the random natives.
+
+mod common;
+
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+
+use common::{eval, natives, Program};
+
+/// Emits a list of a few values drawn by every random native.
+fn draw(p: &mut Program) {
+    p.op(PUSH_LIST)
+        .op(DUP).native(natives::RANDOM).call(0).op(APPEND_ELEMENT)
+        .op(DUP).native(natives::RANDOM_INT).int(1).int(6).call(2).op(APPEND_ELEMENT)
+        .op(DUP).native(natives::RANDOM_INT).int(i64::MIN).int(i64::MAX).call(2).op(APPEND_ELEMENT)
+        .op(DUP).native(natives::RANDOM_CHOICE).strs(&["a", "b", "c"]).call(1).op(APPEND_ELEMENT)
+        .op(DUP).native(natives::SHUFFLE).ints(&[1, 2, 3, 4, 5]).call(1).op(APPEND_ELEMENT);
+}
+
+#[test]
+fn seeding_makes_values_reproducible() {
+    let result = eval(|p| {
+        p.native(natives::SET_SEED).int(42).call(1).op(POP);
+        draw(p);
+        p.native(natives::TO_STRING).op(SWAP).call(1).store(0);
+
+        p.native(natives::SET_SEED).int(42).call(1).op(POP);
+        draw(p);
+        p.native(natives::TO_STRING).op(SWAP).call(1);
+
+        p.load(0).op(EQUAL);
+    });
+
+    assert_eq!(result.unwrap(), "true");
+}
+
+#[test]
+fn set_seed_returns_nil() {
+    assert_eq!(eval(|p| { p.native(natives::SET_SEED).int(1).call(1); }).unwrap(), "()");
+}
+
+#[test]
+fn random_int_includes_both_bounds() {
+    // 200 throws of a die which never produce 1 or 6 are very unlikely.
+    let result = eval(|p| {
+        p.native(natives::SET_SEED).int(0).call(1).op(POP)
+            .native(natives::SORT).op(PUSH_LIST);
+
+        for _ in 0..200 {
+            p.op(DUP).native(natives::RANDOM_INT).int(1).int(6).call(2).op(APPEND_ELEMENT);
+        }
+
+        p.call(1).store(0)
+            .op(PUSH_LIST)
+            .op(DUP).load(0).int(0).op(READ_ELEMENT).op(APPEND_ELEMENT)
+            .op(DUP).load(0).int(199).op(READ_ELEMENT).op(APPEND_ELEMENT);
+    });
+
+    assert_eq!(result.unwrap(), "[1, 6]");
+}
+
+#[test]
+fn random_int_with_equal_bounds_returns_the_bound() {
+    assert_eq!(eval(|p| { p.native(natives::RANDOM_INT).int(5).int(5).call(2); }).unwrap(), "5");
+}
+
+#[test]
+fn random_int_with_bounds_in_the_wrong_order_throws() {
+    assert!(matches!(
+        eval(|p| { p.native(natives::RANDOM_INT).int(3).int(1).call(2); }),
+        Err(Exception::Custom(_))
+    ));
+}
+
+#[test]
+fn random_is_between_zero_and_one() {
+    let result = eval(|p| {
+        p.native(natives::RANDOM).call(0).store(0)
+            .load(0).float(0.0).op(GREATER_THAN_OR_EQUAL)
+            .load(0).float(1.0).op(LESS_THAN)
+            .op(AND);
+    });
+
+    assert_eq!(result.unwrap(), "true");
+}
+
+#[test]
+fn random_choice_of_an_empty_list_is_nil() {
+    assert_eq!(eval(|p| { p.native(natives::RANDOM_CHOICE).op(PUSH_LIST).call(1); }).unwrap(), "()");
+}
+
+#[test]
+fn shuffle_returns_a_permutation_without_changing_the_list() {
+    let result = eval(|p| {
+        p.ints(&[1, 2, 3, 4, 5]).store(0)
+            .native(natives::SORT)
+                .native(natives::SHUFFLE).load(0).call(1)
+            .call(1)
+            .op(POP)
+            .load(0);
+    });
+    assert_eq!(result.unwrap(), "[1, 2, 3, 4, 5]");
+
+    let result = eval(|p| {
+        p.native(natives::SORT)
+            .native(natives::SHUFFLE).ints(&[3, 1, 2]).call(1)
+            .call(1);
+    });
+    assert_eq!(result.unwrap(), "[1, 2, 3]");
+
+    assert_eq!(eval(|p| { p.native(natives::SHUFFLE).op(PUSH_LIST).call(1); }).unwrap(), "[]");
+}
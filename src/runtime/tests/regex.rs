@@ -0,0 +1,94 @@
+//! Tests of the regular expression natives.
+
+mod common;
+
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+
+use common::{eval, natives, Program};
+
+#[test]
+fn match_measures_spans_in_characters() {
+    let result = eval(|p| { p.native(natives::REGEX_MATCH).str("héllo wörld 42").str(r"w(ö)rld (?<num>\d+)").call(2); });
+
+    assert_eq!(
+        result.unwrap(),
+        "{ \"text\": wörld 42, \"start\": 6, \"end\": 14, \
+        \"groups\": [{ \"text\": ö, \"start\": 7, \"end\": 8 }, { \"text\": 42, \"start\": 12, \"end\": 14 }], \
+        \"named\": dyn { \"num\": { \"text\": 42, \"start\": 12, \"end\": 14 } } }"
+    );
+}
+
+#[test]
+fn match_without_a_match_returns_nil() {
+    assert_eq!(eval(|p| { p.native(natives::REGEX_MATCH).str("abc").str(r"\d").call(2); }).unwrap(), "()");
+}
+
+#[test]
+fn groups_which_do_not_participate_are_nil() {
+    let result = eval(|p| { p.native(natives::REGEX_MATCH).str("ab").str("a(x)?b").call(2); });
+
+    assert_eq!(result.unwrap(), "{ \"text\": ab, \"start\": 0, \"end\": 2, \"groups\": [()], \"named\": dyn {} }");
+}
+
+#[test]
+fn find_all_returns_every_match() {
+    let result = eval(|p| { p.native(natives::REGEX_FIND_ALL).str("é1 é22 é333").str(r"\d+").call(2); });
+
+    assert_eq!(
+        result.unwrap(),
+        "[{ \"text\": 1, \"start\": 1, \"end\": 2, \"groups\": [], \"named\": dyn {} }, \
+        { \"text\": 22, \"start\": 4, \"end\": 6, \"groups\": [], \"named\": dyn {} }, \
+        { \"text\": 333, \"start\": 8, \"end\": 11, \"groups\": [], \"named\": dyn {} }]"
+    );
+    assert_eq!(eval(|p| { p.native(natives::REGEX_FIND_ALL).str("abc").str(r"\d").call(2); }).unwrap(), "[]");
+}
+
+#[test]
+fn replace_expands_group_references() {
+    let result = eval(|p| {
+        p.native(natives::REGEX_REPLACE).str("2024-01-15").str(r"(\d+)-(\d+)-(\d+)").str("$3/$2/$1").call(3);
+    });
+
+    assert_eq!(result.unwrap(), "15/01/2024");
+}
+
+#[test]
+fn replace_with_a_function_calls_it_with_each_match() {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 0, &[]);
+    let shout = p.declare("shout", 1, 0, &[]);
+
+    p.begin(main)
+        .native(natives::REGEX_REPLACE).str("one two").str(r"\w+").func(shout).call(3)
+        .op(RET).op(BOUNDARY);
+
+    p.begin(shout)
+        .native(natives::TO_UPPER).load(0).str("text").op(READ_FIELD).call(1)
+        .op(RET).op(BOUNDARY);
+
+    assert_eq!(p.run(main).unwrap(), "ONE TWO");
+}
+
+#[test]
+fn split_splits_around_matches() {
+    assert_eq!(eval(|p| { p.native(natives::REGEX_SPLIT).str("a, b,c ,d").str(r"\s*,\s*").call(2); }).unwrap(), "[a, b, c, d]");
+    assert_eq!(eval(|p| { p.native(natives::REGEX_SPLIT).str("a1b").str(r"\d").call(2); }).unwrap(), "[a, b]");
+}
+
+#[test]
+fn invalid_patterns_throw() {
+    assert!(matches!(
+        eval(|p| { p.native(natives::REGEX_MATCH).str("abc").str("(unclosed").call(2); }),
+        Err(Exception::InvalidRegex(_))
+    ));
+}
+
+#[test]
+fn look_around_is_not_supported() {
+    assert!(matches!(
+        eval(|p| { p.native(natives::REGEX_MATCH).str("abc").str("a(?=b)").call(2); }),
+        Err(Exception::InvalidRegex(_))
+    ));
+}
@@ -0,0 +1,135 @@
+//! Tests of the string builder natives.
+
+mod common;
+
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+
+use common::{eval, natives, Program};
+
+#[test]
+fn append_adds_every_value_converted_to_a_string() {
+    let result = eval(|p| {
+        p.native(natives::SB_TO_STRING)
+            .native(natives::SB_APPEND)
+            .native(natives::STRING_BUILDER).str("a").call(1)
+            .str("b").int(1).bool(true).op(PUSH_NIL)
+            .call(5)
+            .call(1);
+    });
+
+    assert_eq!(result.unwrap(), "ab1true()");
+}
+
+#[test]
+fn append_returns_the_builder() {
+    let result = eval(|p| {
+        p.native(natives::STRING_BUILDER).call(0).store(0)
+            .native(natives::SB_APPEND)
+            .native(natives::SB_APPEND).load(0).str("x").call(2)
+            .str("y").call(2)
+            .op(POP)
+            .native(natives::SB_TO_STRING).load(0).call(1);
+    });
+
+    assert_eq!(result.unwrap(), "xy");
+}
+
+#[test]
+fn appending_a_builder_to_itself_doubles_it() {
+    let result = eval(|p| {
+        p.native(natives::STRING_BUILDER).str("ab").call(1).store(0)
+            .native(natives::SB_APPEND).load(0).load(0).call(2)
+            .op(POP)
+            .native(natives::SB_TO_STRING).load(0).call(1);
+    });
+
+    assert_eq!(result.unwrap(), "abab");
+}
+
+#[test]
+fn to_string_copies_the_contents() {
+    // Appending after converting to a string doesn't change the string.
+    let result = eval(|p| {
+        p.native(natives::STRING_BUILDER).str("a").call(1).store(0)
+            .native(natives::SB_TO_STRING).load(0).call(1).store(1)
+            .native(natives::SB_APPEND).load(0).str("b").call(2)
+            .op(POP)
+            .load(1);
+    });
+
+    assert_eq!(result.unwrap(), "a");
+}
+
+#[test]
+fn length_counts_characters() {
+    let result = eval(|p| {
+        p.native(natives::SB_LENGTH)
+            .native(natives::STRING_BUILDER).str("héllo 🌍").call(1)
+            .call(1);
+    });
+
+    assert_eq!(result.unwrap(), "7");
+}
+
+#[test]
+fn clear_empties_the_builder() {
+    let result = eval(|p| {
+        p.native(natives::STRING_BUILDER).str("abc").call(1).store(0)
+            .native(natives::SB_CLEAR).load(0).call(1)
+            .op(POP)
+            .native(natives::SB_APPEND).load(0).str("d").call(2)
+            .op(POP)
+            .native(natives::SB_TO_STRING).load(0).call(1);
+    });
+
+    assert_eq!(result.unwrap(), "d");
+}
+
+#[test]
+fn builder_functions_on_other_values_throw() {
+    assert!(matches!(
+        eval(|p| { p.native(natives::SB_APPEND).str("a").str("b").call(2); }),
+        Err(Exception::CoercionError(..))
+    ));
+    assert!(matches!(
+        eval(|p| { p.native(natives::SB_TO_STRING).int(1).call(1); }),
+        Err(Exception::CoercionError(..))
+    ));
+}
+
+#[test]
+fn builders_survive_garbage_collection() {
+    let mut p = Program::new();
+
+    let main = p.declare("main", 0, 2, &[]);
+
+    // builder = stringBuilder()
+    // for (i = 0; i < 100; i++) { []; sbAppend(builder, toString(i)) }
+    // sbToString(builder)
+    let top = p.label();
+    let end = p.label();
+    p.begin(main)
+        .native(natives::STRING_BUILDER).call(0).store(0)
+        .int(0).store(1)
+        .bind(top)
+        .load(1).int(100).op(LESS_THAN).op(NOT)
+        .jump(JUMP_IF, end)
+        .op(PUSH_LIST).op(POP)
+        .native(natives::SB_APPEND).load(0).native(natives::TO_STRING).load(1).call(1).call(2)
+        .op(POP)
+        .load(1).int(1).op(ADD).store(1)
+        .jump(JUMP, top)
+        .bind(end)
+        .native(natives::SB_TO_STRING).load(0).call(1)
+        .op(RET).op(BOUNDARY);
+
+    // The heap only fits a few values, so every iteration has to collect garbage.
+    let mut vm = p.vm_with_heap(4);
+    let result = vm.call_run(main.into(), &[])
+        .and_then(|ret| vm.to_string(ret))
+        .map_err(|e| e.exception);
+
+    let expected = (0..100).map(|i| i.to_string()).collect::<String>();
+    assert_eq!(result.unwrap(), expected);
+}
@@ -0,0 +1,131 @@
+//! Tests of the string natives.
+
+mod common;
+
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+
+use common::{eval, natives};
+
+#[test]
+fn strings_are_measured_in_characters() {
+    assert_eq!(eval(|p| { p.native(natives::LENGTH).str("añb").call(1); }).unwrap(), "3");
+    assert_eq!(eval(|p| { p.native(natives::LENGTH).str("😀a").call(1); }).unwrap(), "2");
+}
+
+#[test]
+fn substring_indexes_characters() {
+    assert_eq!(eval(|p| { p.native(natives::SUBSTRING).str("héllo wörld").int(6).call(2); }).unwrap(), "wörld");
+    assert_eq!(eval(|p| { p.native(natives::SUBSTRING).str("héllo").int(1).int(3).call(3); }).unwrap(), "él");
+    assert_eq!(eval(|p| { p.native(natives::SUBSTRING).str("a😀b").int(1).int(2).call(3); }).unwrap(), "😀");
+}
+
+#[test]
+fn substring_clamps_indices() {
+    assert_eq!(eval(|p| { p.native(natives::SUBSTRING).str("héllo").int(-2).int(99).call(3); }).unwrap(), "héllo");
+    assert_eq!(eval(|p| { p.native(natives::SUBSTRING).str("héllo").int(4).int(1).call(3); }).unwrap(), "");
+}
+
+#[test]
+fn substring_accepts_integral_numbers_as_indices() {
+    assert_eq!(eval(|p| { p.native(natives::SUBSTRING).str("héllo").float(1.0).float(3.0).call(3); }).unwrap(), "él");
+}
+
+#[test]
+fn index_of_returns_character_indices() {
+    assert_eq!(eval(|p| { p.native(natives::INDEX_OF).str("héllo wörld").str("wö").call(2); }).unwrap(), "6");
+    assert_eq!(eval(|p| { p.native(natives::INDEX_OF).str("abcabc").str("bc").int(2).call(3); }).unwrap(), "4");
+    assert_eq!(eval(|p| { p.native(natives::INDEX_OF).str("abc").str("x").call(2); }).unwrap(), "-1");
+}
+
+#[test]
+fn last_index_of_finds_occurrences_starting_at_or_before_the_index() {
+    assert_eq!(eval(|p| { p.native(natives::LAST_INDEX_OF).str("abcabc").str("bc").call(2); }).unwrap(), "4");
+    assert_eq!(eval(|p| { p.native(natives::LAST_INDEX_OF).str("abcabc").str("bc").int(3).call(3); }).unwrap(), "1");
+    assert_eq!(eval(|p| { p.native(natives::LAST_INDEX_OF).str("añbañb").str("ñ").call(2); }).unwrap(), "4");
+}
+
+#[test]
+fn contains_starts_with_and_ends_with() {
+    assert_eq!(eval(|p| { p.native(natives::CONTAINS).str("héllo").str("ll").call(2); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.native(natives::CONTAINS).str("héllo").str("le").call(2); }).unwrap(), "false");
+    assert_eq!(eval(|p| { p.native(natives::STARTS_WITH).str("héllo").str("hé").call(2); }).unwrap(), "true");
+    assert_eq!(eval(|p| { p.native(natives::STARTS_WITH).str("héllo").str("é").call(2); }).unwrap(), "false");
+    assert_eq!(eval(|p| { p.native(natives::ENDS_WITH).str("héllo").str("lo").call(2); }).unwrap(), "true");
+}
+
+#[test]
+fn split_keeps_empty_parts() {
+    assert_eq!(eval(|p| { p.native(natives::SPLIT).str("a,b,,c").str(",").call(2); }).unwrap(), "[a, b, , c]");
+}
+
+#[test]
+fn split_by_an_empty_string_splits_into_characters() {
+    assert_eq!(eval(|p| { p.native(natives::SPLIT).str("añb").str("").call(2); }).unwrap(), "[a, ñ, b]");
+}
+
+#[test]
+fn join_converts_elements_into_strings() {
+    let result = eval(|p| {
+        p.native(natives::JOIN)
+            .op(PUSH_LIST)
+            .op(DUP).str("a").op(APPEND_ELEMENT)
+            .op(DUP).int(1).op(APPEND_ELEMENT)
+            .op(DUP).bool(true).op(APPEND_ELEMENT)
+            .str("-")
+            .call(2);
+    });
+
+    assert_eq!(result.unwrap(), "a-1-true");
+}
+
+#[test]
+fn replace_replaces_every_occurrence() {
+    assert_eq!(eval(|p| { p.native(natives::REPLACE).str("a.b.c").str(".").str("::").call(3); }).unwrap(), "a::b::c");
+}
+
+#[test]
+fn trims() {
+    assert_eq!(eval(|p| { p.native(natives::TRIM).str("  hi  ").call(1); }).unwrap(), "hi");
+    assert_eq!(eval(|p| { p.native(natives::TRIM_START).str("  hi  ").call(1); }).unwrap(), "hi  ");
+    assert_eq!(eval(|p| { p.native(natives::TRIM_END).str("  hi  ").call(1); }).unwrap(), "  hi");
+}
+
+#[test]
+fn case_conversion_follows_unicode() {
+    assert_eq!(eval(|p| { p.native(natives::TO_UPPER).str("straße").call(1); }).unwrap(), "STRASSE");
+    assert_eq!(eval(|p| { p.native(natives::TO_LOWER).str("ÀB").call(1); }).unwrap(), "àb");
+}
+
+#[test]
+fn repeat_treats_negative_counts_as_zero() {
+    assert_eq!(eval(|p| { p.native(natives::REPEAT).str("ab").int(3).call(2); }).unwrap(), "ababab");
+    assert_eq!(eval(|p| { p.native(natives::REPEAT).str("ab").int(-1).call(2); }).unwrap(), "");
+}
+
+#[test]
+fn padding_cuts_off_the_fill_string() {
+    assert_eq!(eval(|p| { p.native(natives::PAD_START).str("7").int(3).str("0").call(3); }).unwrap(), "007");
+    assert_eq!(eval(|p| { p.native(natives::PAD_END).str("ab").int(5).str("xy").call(3); }).unwrap(), "abxyx");
+    assert_eq!(eval(|p| { p.native(natives::PAD_START).str("ñ").int(3).call(2); }).unwrap(), "  ñ");
+}
+
+#[test]
+fn padding_a_long_enough_string_does_nothing() {
+    assert_eq!(eval(|p| { p.native(natives::PAD_START).str("abc").int(2).call(2); }).unwrap(), "abc");
+}
+
+#[test]
+fn code_points() {
+    assert_eq!(eval(|p| { p.native(natives::CHARS).str("añb").call(1); }).unwrap(), "[a, ñ, b]");
+    assert_eq!(eval(|p| { p.native(natives::CODE_POINT_AT).str("añb").int(1).call(2); }).unwrap(), "241");
+    assert_eq!(eval(|p| { p.native(natives::FROM_CODE_POINT).int(0x1F600).call(1); }).unwrap(), "😀");
+}
+
+#[test]
+fn string_parameters_are_not_coerced() {
+    assert!(matches!(
+        eval(|p| { p.native(natives::TO_UPPER).int(1).call(1); }),
+        Err(Exception::BadArgumentType { .. })
+    ));
+}
@@ -0,0 +1,132 @@
+//! Tests of the time natives.
+
+mod common;
+
+use noa_runtime::exception::Exception;
+use noa_runtime::opcode::*;
+
+use common::{eval, natives, Program};
+
+/// Emits a date object with the given fields.
+fn date(p: &mut Program, fields: &[(&str, i64)]) {
+    p.object(false);
+    for &(name, x) in fields {
+        p.field(name, false, |p| { p.int(x); });
+    }
+}
+
+fn to_date(timestamp: i64) -> String {
+    eval(|p| { p.native(natives::TO_DATE).int(timestamp).call(1); }).unwrap()
+}
+
+fn from_date(fields: &[(&str, i64)]) -> Result<String, Exception> {
+    eval(|p| {
+        p.native(natives::FROM_DATE);
+        date(p, fields);
+        p.call(1);
+    })
+}
+
+fn parse_date(str: &str) -> String {
+    eval(|p| { p.native(natives::PARSE_DATE).str(str).call(1); }).unwrap()
+}
+
+#[test]
+fn now_is_a_plausible_unix_timestamp() {
+    assert_eq!(eval(|p| { p.native(natives::NOW).call(0).int(1_600_000_000_000).op(GREATER_THAN); }).unwrap(), "true");
+}
+
+#[test]
+fn monotonic_time_advances_while_sleeping() {
+    let result = eval(|p| {
+        p.native(natives::MONOTONIC).call(0).store(0)
+            .native(natives::SLEEP).int(10).call(1).op(POP)
+            .native(natives::MONOTONIC).call(0).load(0).op(SUB)
+            .int(9).op(GREATER_THAN);
+    });
+
+    assert_eq!(result.unwrap(), "true");
+}
+
+#[test]
+fn to_date_splits_timestamps_into_utc_fields() {
+    assert_eq!(
+        to_date(0),
+        "{ \"year\": 1970, \"month\": 1, \"day\": 1, \"hour\": 0, \"minute\": 0, \"second\": 0, \"ms\": 0 }"
+    );
+    assert_eq!(
+        to_date(1_705_314_600_123),
+        "{ \"year\": 2024, \"month\": 1, \"day\": 15, \"hour\": 10, \"minute\": 30, \"second\": 0, \"ms\": 123 }"
+    );
+    assert_eq!(
+        to_date(-1),
+        "{ \"year\": 1969, \"month\": 12, \"day\": 31, \"hour\": 23, \"minute\": 59, \"second\": 59, \"ms\": 999 }"
+    );
+}
+
+#[test]
+fn from_date_defaults_missing_time_fields_to_zero() {
+    assert_eq!(from_date(&[("year", 2024), ("month", 2), ("day", 29)]).unwrap(), "1709164800000");
+}
+
+#[test]
+fn from_date_is_the_inverse_of_to_date() {
+    let fields = [("year", 2024), ("month", 1), ("day", 15), ("hour", 10), ("minute", 30), ("second", 0), ("ms", 123)];
+    assert_eq!(from_date(&fields).unwrap(), "1705314600123");
+
+    let fields = [("year", 1969), ("month", 12), ("day", 31), ("hour", 23), ("minute", 59), ("second", 59), ("ms", 999)];
+    assert_eq!(from_date(&fields).unwrap(), "-1");
+}
+
+#[test]
+fn from_date_with_a_day_which_does_not_exist_throws() {
+    assert!(matches!(from_date(&[("year", 2023), ("month", 2), ("day", 29)]), Err(Exception::InvalidDate(_))));
+}
+
+#[test]
+fn format_date_uses_iso_8601() {
+    assert_eq!(
+        eval(|p| { p.native(natives::FORMAT_DATE).int(1_705_314_600_123).call(1); }).unwrap(),
+        "2024-01-15T10:30:00.123Z"
+    );
+}
+
+#[test]
+fn format_date_extends_years_outside_of_four_digits() {
+    let result = eval(|p| {
+        p.native(natives::FORMAT_DATE);
+        date(p, &[("year", -1), ("month", 1), ("day", 1)]);
+        p.call(1);
+    });
+    assert_eq!(result.unwrap(), "-000001-01-01T00:00:00.000Z");
+
+    let result = eval(|p| {
+        p.native(natives::FORMAT_DATE);
+        date(p, &[("year", 12024), ("month", 12), ("day", 31), ("hour", 23)]);
+        p.call(1);
+    });
+    assert_eq!(result.unwrap(), "+012024-12-31T23:00:00.000Z");
+}
+
+#[test]
+fn parse_date_converts_offsets_into_utc() {
+    assert_eq!(
+        parse_date("2024-01-15"),
+        "{ \"year\": 2024, \"month\": 1, \"day\": 15, \"hour\": 0, \"minute\": 0, \"second\": 0, \"ms\": 0 }"
+    );
+    assert_eq!(
+        parse_date("2024-01-15T10:30:00.123456+02:00"),
+        "{ \"year\": 2024, \"month\": 1, \"day\": 15, \"hour\": 8, \"minute\": 30, \"second\": 0, \"ms\": 123 }"
+    );
+    assert_eq!(
+        parse_date("2024-01-01 00:30-0100"),
+        "{ \"year\": 2024, \"month\": 1, \"day\": 1, \"hour\": 1, \"minute\": 30, \"second\": 0, \"ms\": 0 }"
+    );
+}
+
+#[test]
+fn parse_date_returns_nil_for_invalid_dates() {
+    assert_eq!(parse_date("2024-02-30"), "()");
+    assert_eq!(parse_date("2024-01-15T25:00"), "()");
+    assert_eq!(parse_date("not a date"), "()");
+}
@@ -16,6 +16,10 @@ pub struct Args {
     /// Whether to enable debugging through the debugger TUI.
     #[arg(long = "debug")]
     pub debug: bool,
+
+    /// Seeds the random number generator to make random numbers reproducible across runs.
+    #[arg(long = "seed", value_name = "seed")]
+    pub seed: Option<u64>,
 }
 
 fn file_exists(s: &str) -> Result<PathBuf, String> {
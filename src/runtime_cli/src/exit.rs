@@ -1,4 +1,4 @@
-use std::{ops::{ControlFlow, FromResidual, Try}, process::{ExitCode, Termination}};
+use std::{ops::{ControlFlow, FromResidual, Residual, Try}, process::{ExitCode, Termination}};
 
 /// Return type from main function which can be used with the `?` operator.
 /// 
@@ -73,7 +73,7 @@ impl<T: Termination> Termination for Exit<T> {
 impl<T> Try for Exit<T> {
     type Output = T;
 
-    type Residual = (u8, Option<String>);
+    type Residual = ExitResidual;
 
     fn from_output(output: Self::Output) -> Self {
         Self::Continue(output)
@@ -82,17 +82,23 @@ impl<T> Try for Exit<T> {
     fn branch(self) -> ControlFlow<Self::Residual, Self::Output> {
         match self {
             Exit::Continue(x) => ControlFlow::Continue(x),
-            Exit::Exit { code, message } => ControlFlow::Break((code, message))
+            Exit::Exit { code, message } => ControlFlow::Break(ExitResidual(code, message))
         }
     }
 }
 
 impl<T> FromResidual for Exit<T> {
-    fn from_residual((code, message): <Self as Try>::Residual) -> Self {
+    fn from_residual(ExitResidual(code, message): <Self as Try>::Residual) -> Self {
         Self::Exit { code, message }
     }
 }
 
+pub struct ExitResidual(u8, Option<String>);
+
+impl<T> Residual<T> for ExitResidual {
+    type TryType = Exit<T>;
+}
+
 /// Trait for things which can be converted into [`Exit`].
 pub trait IntoExit {
     type Output;
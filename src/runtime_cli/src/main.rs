@@ -1,4 +1,5 @@
 #![feature(try_trait_v2)]
+#![feature(try_trait_v2_residual)]
 
 use std::fs;
 use std::io::Cursor;
@@ -79,14 +80,19 @@ fn main() -> Exit<()> {
         debugger
     );
 
+    if let Some(seed) = args.seed {
+        vm.set_seed(seed);
+    }
+
     let result = run(&mut vm, main, args.print_return_value);
 
     match result {
-        Ok(_) => {},
-        Err(ex) => print_exception(ex),
+        Ok(_) => Exit::ok(),
+        Err(ex) => {
+            print_exception(ex);
+            Exit::fail()
+        },
     }
-
-    Exit::ok()
 }
 
 fn run(vm: &mut Vm, main: FuncId, print_ret: bool) -> Result<(), FormattedException> {
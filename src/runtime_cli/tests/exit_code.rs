@@ -0,0 +1,140 @@
+//! Tests of the exit code of the CLI.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::{env, fs, process};
+
+use noa_runtime::opcode::*;
+
+const PRINT: u32 = 1 << 31;
+const TO_UPPER: u32 = 0x10D | (1 << 31);
+
+/// Encodes an Ark file with a single main function and the strings `"main"`, `"fine"` and `"Hello, world!"`.
+fn ark(code: &[u8]) -> Vec<u8> {
+    let mut ark = b"totheark".to_vec();
+    ark.extend(0u32.to_be_bytes());
+
+    ark.extend(1u32.to_be_bytes());
+    // id, name index, arity, locals count, captures count, address
+    for x in [0u32; 6] {
+        ark.extend(x.to_be_bytes());
+    }
+
+    ark.extend((code.len() as u32).to_be_bytes());
+    ark.extend(code);
+
+    let strings = ["main", "fine", "Hello, world!"];
+    ark.extend((strings.len() as u32).to_be_bytes());
+    for str in strings {
+        ark.extend((str.len() as u32).to_be_bytes());
+        ark.extend(str.as_bytes());
+    }
+
+    ark
+}
+
+/// Emits an instruction with a 32-bit operand.
+fn op_u32(code: &mut Vec<u8>, opcode: u8, operand: u32) {
+    code.push(opcode);
+    code.extend(operand.to_be_bytes());
+}
+
+/// Runs an Ark file with the CLI, giving it lines of input.
+fn run_file(path: &Path, stdin: &[&str]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_noa_runtime_cli"))
+        .arg("-f")
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut input = child.stdin.take().unwrap();
+    for line in stdin {
+        writeln!(input, "{line}").unwrap();
+    }
+    drop(input);
+
+    child.wait_with_output().unwrap()
+}
+
+fn run(name: &str, code: &[u8]) -> Output {
+    let path = env::temp_dir().join(format!("noa-exit-code-{name}-{}.ark", process::id()));
+    fs::write(&path, ark(code)).unwrap();
+
+    let output = run_file(&path, &[]);
+
+    fs::remove_file(&path).unwrap();
+
+    output
+}
+
+#[test]
+fn exits_with_0_when_the_program_completes() {
+    // print("Hello, world!");
+    let mut code = Vec::new();
+    op_u32(&mut code, PUSH_FUNC, PRINT);
+    op_u32(&mut code, PUSH_STRING, 2);
+    op_u32(&mut code, CALL, 1);
+    code.extend([POP, PUSH_NIL, RET, BOUNDARY]);
+
+    let output = run("completes", &code);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Hello, world!\n");
+}
+
+#[test]
+fn exits_with_1_after_an_uncaught_exception() {
+    // print(toUpper("fine"));
+    // print(toUpper(()));
+    let mut code = Vec::new();
+    op_u32(&mut code, PUSH_FUNC, PRINT);
+    op_u32(&mut code, PUSH_FUNC, TO_UPPER);
+    op_u32(&mut code, PUSH_STRING, 1);
+    op_u32(&mut code, CALL, 1);
+    op_u32(&mut code, CALL, 1);
+    code.push(POP);
+    op_u32(&mut code, PUSH_FUNC, PRINT);
+    op_u32(&mut code, PUSH_FUNC, TO_UPPER);
+    code.push(PUSH_NIL);
+    op_u32(&mut code, CALL, 1);
+    op_u32(&mut code, CALL, 1);
+    code.extend([POP, PUSH_NIL, RET, BOUNDARY]);
+
+    let output = run("exception", &code);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout.starts_with("FINE\nAn exception occurred:"), "unexpected output: {stdout}");
+}
+
+#[test]
+fn exits_with_the_exit_codes_of_the_conformance_tests() {
+    // The conformance tests of the runtime run their programs without the CLI,
+    // so this checks that the exit codes in their expected files are the ones the CLI exits with.
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../runtime/tests/conformance");
+
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "ark") {
+            continue;
+        }
+
+        let expected = fs::read_to_string(path.with_extension("expected")).unwrap_or_default();
+
+        let section = |name: &str| expected.split("--- ")
+            .find_map(|section| section.strip_prefix(name)?.strip_prefix('\n'))
+            .map(|body| body.lines().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let stdin = section("stdin");
+        let exit_code = section("exit code").first().map_or(0, |code| code.parse().unwrap());
+
+        let output = run_file(&path, &stdin);
+
+        assert_eq!(output.status.code(), Some(exit_code), "{} exited with the wrong code", path.display());
+    }
+}